@@ -3,6 +3,7 @@ pub enum ActionState {
     Enabled,
     Reversed,
     Disabled,
+    Mixed,
 }
 
 impl From<&wysiwyg::ActionState> for ActionState {
@@ -11,6 +12,7 @@ impl From<&wysiwyg::ActionState> for ActionState {
             wysiwyg::ActionState::Enabled => Self::Enabled,
             wysiwyg::ActionState::Reversed => Self::Reversed,
             wysiwyg::ActionState::Disabled => Self::Disabled,
+            wysiwyg::ActionState::Mixed => Self::Mixed,
         }
     }
 }
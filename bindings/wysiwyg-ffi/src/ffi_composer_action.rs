@@ -5,6 +5,8 @@ pub enum ComposerAction {
     StrikeThrough,
     Underline,
     InlineCode,
+    Superscript,
+    Subscript,
     Link,
     Undo,
     Redo,
@@ -14,6 +16,9 @@ pub enum ComposerAction {
     Unindent,
     CodeBlock,
     Quote,
+    ClearFormatting,
+    TextColor,
+    BackgroundColor,
 }
 
 impl From<&ComposerAction> for wysiwyg::ComposerAction {
@@ -24,6 +29,8 @@ impl From<&ComposerAction> for wysiwyg::ComposerAction {
             ComposerAction::StrikeThrough => Self::StrikeThrough,
             ComposerAction::Underline => Self::Underline,
             ComposerAction::InlineCode => Self::InlineCode,
+            ComposerAction::Superscript => Self::Superscript,
+            ComposerAction::Subscript => Self::Subscript,
             ComposerAction::Link => Self::Link,
             ComposerAction::Undo => Self::Undo,
             ComposerAction::Redo => Self::Redo,
@@ -33,6 +40,9 @@ impl From<&ComposerAction> for wysiwyg::ComposerAction {
             ComposerAction::Unindent => Self::Unindent,
             ComposerAction::CodeBlock => Self::CodeBlock,
             ComposerAction::Quote => Self::Quote,
+            ComposerAction::ClearFormatting => Self::ClearFormatting,
+            ComposerAction::TextColor => Self::TextColor,
+            ComposerAction::BackgroundColor => Self::BackgroundColor,
         }
     }
 }
@@ -45,6 +55,8 @@ impl From<&wysiwyg::ComposerAction> for ComposerAction {
             wysiwyg::ComposerAction::StrikeThrough => Self::StrikeThrough,
             wysiwyg::ComposerAction::Underline => Self::Underline,
             wysiwyg::ComposerAction::InlineCode => Self::InlineCode,
+            wysiwyg::ComposerAction::Superscript => Self::Superscript,
+            wysiwyg::ComposerAction::Subscript => Self::Subscript,
             wysiwyg::ComposerAction::Link => Self::Link,
             wysiwyg::ComposerAction::Undo => Self::Undo,
             wysiwyg::ComposerAction::Redo => Self::Redo,
@@ -54,6 +66,9 @@ impl From<&wysiwyg::ComposerAction> for ComposerAction {
             wysiwyg::ComposerAction::Unindent => Self::Unindent,
             wysiwyg::ComposerAction::CodeBlock => Self::CodeBlock,
             wysiwyg::ComposerAction::Quote => Self::Quote,
+            wysiwyg::ComposerAction::ClearFormatting => Self::ClearFormatting,
+            wysiwyg::ComposerAction::TextColor => Self::TextColor,
+            wysiwyg::ComposerAction::BackgroundColor => Self::BackgroundColor,
         }
     }
 }
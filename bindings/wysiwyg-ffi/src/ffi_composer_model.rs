@@ -7,8 +7,14 @@ use crate::ffi_composer_state::ComposerState;
 use crate::ffi_composer_update::ComposerUpdate;
 use crate::ffi_dom_creation_error::DomCreationError;
 use crate::ffi_link_actions::LinkAction;
+use crate::ffi_markdown_error::MarkdownError;
 use crate::into_ffi::IntoFfi;
-use crate::{ActionState, ComposerAction, SuggestionPattern};
+use crate::{
+    ActionState, ComposerAction, ContentLimit, ContentMetrics, ContextState,
+    CustomKeyError, Direction, DomNode, FindMatch, FindOptions, Granularity,
+    MessageContent, MessageContentOptions, ParsedCommand, ReplyParent,
+    SlashCommand, SuggestionPattern, SuggestionTrigger,
+};
 
 #[derive(Default)]
 pub struct ComposerModel {
@@ -22,6 +28,28 @@ impl ComposerModel {
         }
     }
 
+    pub fn from_html(
+        html: &str,
+        start_utf16_codeunit: u32,
+        end_utf16_codeunit: u32,
+    ) -> Result<Self, DomCreationError> {
+        Ok(Self {
+            inner: Mutex::new(wysiwyg::ComposerModel::try_from_html(
+                html,
+                usize::try_from(start_utf16_codeunit).unwrap(),
+                usize::try_from(end_utf16_codeunit).unwrap(),
+            )?),
+        })
+    }
+
+    pub fn from_example_format(text: String) -> Self {
+        Self {
+            inner: Mutex::new(wysiwyg::ComposerModel::from_example_format(
+                &text,
+            )),
+        }
+    }
+
     pub fn set_content_from_html(
         self: &Arc<Self>,
         html: String,
@@ -56,12 +84,32 @@ impl ComposerModel {
             .to_string()
     }
 
-    pub fn get_content_as_markdown(self: &Arc<Self>) -> String {
-        self.inner
+    pub fn get_content_as_markdown(
+        self: &Arc<Self>,
+    ) -> Result<String, MarkdownError> {
+        Ok(self
+            .inner
             .lock()
             .unwrap()
-            .get_content_as_markdown()
-            .to_string()
+            .try_get_content_as_markdown()?
+            .to_string())
+    }
+
+    pub fn switch_to_markdown_mode(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().switch_to_markdown_mode(),
+        ))
+    }
+
+    pub fn switch_to_rich_mode(
+        self: &Arc<Self>,
+    ) -> Result<Arc<ComposerUpdate>, DomCreationError> {
+        let update = self.inner.lock().unwrap().switch_to_rich_mode()?;
+        Ok(Arc::new(ComposerUpdate::from(update)))
+    }
+
+    pub fn is_markdown_mode(self: &Arc<Self>) -> bool {
+        self.inner.lock().unwrap().is_markdown_mode()
     }
 
     pub fn get_content_as_plain_text(self: &Arc<Self>) -> String {
@@ -93,6 +141,266 @@ impl ComposerModel {
         ))
     }
 
+    pub fn move_cursor(
+        self: &Arc<Self>,
+        direction: Direction,
+        granularity: Granularity,
+        extend_selection: bool,
+    ) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().move_cursor(
+                direction.into(),
+                granularity.into(),
+                extend_selection,
+            ),
+        ))
+    }
+
+    pub fn select_word_at_cursor(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().select_word_at_cursor(),
+        ))
+    }
+
+    pub fn select_block_at_cursor(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().select_block_at_cursor(),
+        ))
+    }
+
+    pub fn select_all(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().select_all(),
+        ))
+    }
+
+    pub fn selection_start(self: &Arc<Self>) -> u32 {
+        let (start, _) = self.inner.lock().unwrap().get_selection();
+        u32::try_from(usize::from(start)).unwrap()
+    }
+
+    pub fn selection_end(self: &Arc<Self>) -> u32 {
+        let (_, end) = self.inner.lock().unwrap().get_selection();
+        u32::try_from(usize::from(end)).unwrap()
+    }
+
+    pub fn composition_start(self: &Arc<Self>) -> Option<u32> {
+        let model = self.inner.lock().unwrap();
+        model
+            .get_current_state()
+            .composition
+            .map(|(start, _)| u32::try_from(usize::from(start)).unwrap())
+    }
+
+    pub fn composition_end(self: &Arc<Self>) -> Option<u32> {
+        let model = self.inner.lock().unwrap();
+        model
+            .get_current_state()
+            .composition
+            .map(|(_, end)| u32::try_from(usize::from(end)).unwrap())
+    }
+
+    pub fn has_selection(self: &Arc<Self>) -> bool {
+        self.inner.lock().unwrap().has_selection()
+    }
+
+    pub fn has_cursor(self: &Arc<Self>) -> bool {
+        self.inner.lock().unwrap().has_cursor()
+    }
+
+    pub fn to_message_content(
+        self: &Arc<Self>,
+        options: MessageContentOptions,
+    ) -> MessageContent {
+        MessageContent::from(
+            self.inner
+                .lock()
+                .unwrap()
+                .to_message_content(options.into()),
+        )
+    }
+
+    pub fn to_reply_message_content(
+        self: &Arc<Self>,
+        options: MessageContentOptions,
+        parent: ReplyParent,
+    ) -> MessageContent {
+        MessageContent::from(
+            self.inner
+                .lock()
+                .unwrap()
+                .to_reply_message_content(options.into(), &parent.into()),
+        )
+    }
+
+    pub fn content_metrics(self: &Arc<Self>) -> ContentMetrics {
+        ContentMetrics::from(self.inner.lock().unwrap().content_metrics())
+    }
+
+    pub fn set_content_limit(self: &Arc<Self>, limit: Option<ContentLimit>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .set_content_limit(limit.map(Into::into));
+    }
+
+    pub fn remove_content_limit(self: &Arc<Self>) {
+        self.inner.lock().unwrap().set_content_limit(None);
+    }
+
+    pub fn content_limit(self: &Arc<Self>) -> Option<ContentLimit> {
+        self.inner.lock().unwrap().content_limit().map(Into::into)
+    }
+
+    pub fn set_emoji_suggestion_min_length(self: &Arc<Self>, min_length: u32) {
+        self.inner.lock().unwrap().set_emoji_suggestion_min_length(
+            usize::try_from(min_length).unwrap(),
+        );
+    }
+
+    /// Fails, leaving the triggers unchanged, if the key of any of
+    /// [triggers] isn't a single character.
+    pub fn set_suggestion_triggers(
+        self: &Arc<Self>,
+        triggers: Vec<SuggestionTrigger>,
+    ) -> Result<(), CustomKeyError> {
+        let triggers = triggers
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?;
+        self.inner.lock().unwrap().set_suggestion_triggers(triggers);
+        Ok(())
+    }
+
+    /// Fails if the key of [trigger] isn't a single character.
+    pub fn add_suggestion_trigger(
+        self: &Arc<Self>,
+        trigger: SuggestionTrigger,
+    ) -> Result<(), CustomKeyError> {
+        let trigger = trigger.try_into()?;
+        self.inner.lock().unwrap().add_suggestion_trigger(trigger);
+        Ok(())
+    }
+
+    pub fn clear_suggestion_triggers(self: &Arc<Self>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .set_suggestion_triggers(Vec::new());
+    }
+
+    pub fn suggestion_triggers(self: &Arc<Self>) -> Vec<SuggestionTrigger> {
+        self.inner
+            .lock()
+            .unwrap()
+            .suggestion_triggers()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn set_mention_suggestion_max_words(self: &Arc<Self>, max_words: u32) {
+        self.inner.lock().unwrap().set_mention_suggestion_max_words(
+            usize::try_from(max_words).unwrap(),
+        );
+    }
+
+    pub fn set_slash_commands(self: &Arc<Self>, commands: Vec<SlashCommand>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .set_slash_commands(commands.into_iter().map(Into::into).collect());
+    }
+
+    pub fn add_slash_command(self: &Arc<Self>, command: SlashCommand) {
+        self.inner.lock().unwrap().add_slash_command(command.into());
+    }
+
+    pub fn clear_slash_commands(self: &Arc<Self>) {
+        self.inner.lock().unwrap().set_slash_commands(Vec::new());
+    }
+
+    pub fn slash_commands(self: &Arc<Self>) -> Vec<SlashCommand> {
+        self.inner
+            .lock()
+            .unwrap()
+            .slash_commands()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn commands_for_suggestion(
+        self: &Arc<Self>,
+        suggestion: SuggestionPattern,
+    ) -> Vec<SlashCommand> {
+        let Ok(suggestion) = wysiwyg::SuggestionPattern::try_from(suggestion)
+        else {
+            return Vec::new();
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .commands_for_suggestion(&suggestion)
+            .into_iter()
+            .map(SlashCommand::from)
+            .collect()
+    }
+
+    pub fn parse_command(self: &Arc<Self>) -> Option<ParsedCommand> {
+        self.inner
+            .lock()
+            .unwrap()
+            .parse_command()
+            .map(ParsedCommand::from)
+    }
+
+    pub fn find(
+        self: &Arc<Self>,
+        query: String,
+        options: FindOptions,
+    ) -> Vec<FindMatch> {
+        let query = Utf16String::from_str(&query);
+        self.inner
+            .lock()
+            .unwrap()
+            .find(query, options.into())
+            .into_iter()
+            .map(FindMatch::from)
+            .collect()
+    }
+
+    pub fn replace_all(
+        self: &Arc<Self>,
+        query: String,
+        replacement: String,
+        options: FindOptions,
+    ) -> Arc<ComposerUpdate> {
+        let query = Utf16String::from_str(&query);
+        let replacement = Utf16String::from_str(&replacement);
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().replace_all(
+                query,
+                replacement,
+                options.into(),
+            ),
+        ))
+    }
+
+    pub fn replace_match(
+        self: &Arc<Self>,
+        index: u32,
+        replacement: String,
+    ) -> Arc<ComposerUpdate> {
+        let index = usize::try_from(index).unwrap();
+        let replacement = Utf16String::from_str(&replacement);
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().replace_match(index, replacement),
+        ))
+    }
+
     pub fn replace_text(
         self: &Arc<Self>,
         new_text: String,
@@ -127,22 +435,64 @@ impl ComposerModel {
         new_text: String,
         suggestion: SuggestionPattern,
     ) -> Arc<ComposerUpdate> {
+        let Ok(suggestion) = wysiwyg::SuggestionPattern::try_from(suggestion)
+        else {
+            return Arc::new(ComposerUpdate::from(
+                wysiwyg::ComposerUpdate::keep(),
+            ));
+        };
         Arc::new(ComposerUpdate::from(
             self.inner.lock().unwrap().replace_text_suggestion(
                 Utf16String::from_str(&new_text),
-                wysiwyg::SuggestionPattern::from(suggestion),
+                suggestion,
             ),
         ))
     }
 
+    pub fn set_composing_text(
+        self: &Arc<Self>,
+        text: String,
+    ) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner
+                .lock()
+                .unwrap()
+                .set_composing_text(Utf16String::from_str(&text)),
+        ))
+    }
+
+    pub fn commit_composition(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().commit_composition(),
+        ))
+    }
+
+    pub fn cancel_composition(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().cancel_composition(),
+        ))
+    }
+
     pub fn backspace(self: &Arc<Self>) -> Arc<ComposerUpdate> {
         Arc::new(ComposerUpdate::from(self.inner.lock().unwrap().backspace()))
     }
 
+    pub fn backspace_word(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().backspace_word(),
+        ))
+    }
+
     pub fn delete(self: &Arc<Self>) -> Arc<ComposerUpdate> {
         Arc::new(ComposerUpdate::from(self.inner.lock().unwrap().delete()))
     }
 
+    pub fn delete_word(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().delete_word(),
+        ))
+    }
+
     pub fn delete_in(
         self: &Arc<Self>,
         start: u32,
@@ -183,6 +533,48 @@ impl ComposerModel {
         ))
     }
 
+    pub fn superscript(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().superscript(),
+        ))
+    }
+
+    pub fn subscript(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(self.inner.lock().unwrap().subscript()))
+    }
+
+    pub fn clear_formatting(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().clear_formatting(),
+        ))
+    }
+
+    pub fn clear_formatting_and_links(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().clear_formatting_and_links(),
+        ))
+    }
+
+    pub fn set_text_color(
+        self: &Arc<Self>,
+        color: Option<String>,
+    ) -> Arc<ComposerUpdate> {
+        let color = color.map(|color| Utf16String::from_str(&color));
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().set_text_color(color),
+        ))
+    }
+
+    pub fn set_background_color(
+        self: &Arc<Self>,
+        color: Option<String>,
+    ) -> Arc<ComposerUpdate> {
+        let color = color.map(|color| Utf16String::from_str(&color));
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().set_background_color(color),
+        ))
+    }
+
     pub fn code_block(self: &Arc<Self>) -> Arc<ComposerUpdate> {
         Arc::new(ComposerUpdate::from(
             self.inner.lock().unwrap().code_block(),
@@ -193,6 +585,60 @@ impl ComposerModel {
         Arc::new(ComposerUpdate::from(self.inner.lock().unwrap().quote()))
     }
 
+    pub fn insert_horizontal_rule(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().insert_horizontal_rule(),
+        ))
+    }
+
+    pub fn insert_table(
+        self: &Arc<Self>,
+        rows: u32,
+        columns: u32,
+    ) -> Arc<ComposerUpdate> {
+        let rows = usize::try_from(rows).unwrap();
+        let columns = usize::try_from(columns).unwrap();
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().insert_table(rows, columns),
+        ))
+    }
+
+    pub fn add_table_row(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().add_table_row(),
+        ))
+    }
+
+    pub fn remove_table_row(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().remove_table_row(),
+        ))
+    }
+
+    pub fn add_table_column(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().add_table_column(),
+        ))
+    }
+
+    pub fn remove_table_column(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().remove_table_column(),
+        ))
+    }
+
+    pub fn next_table_cell(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().next_table_cell(),
+        ))
+    }
+
+    pub fn previous_table_cell(self: &Arc<Self>) -> Arc<ComposerUpdate> {
+        Arc::new(ComposerUpdate::from(
+            self.inner.lock().unwrap().previous_table_cell(),
+        ))
+    }
+
     pub fn ordered_list(self: &Arc<Self>) -> Arc<ComposerUpdate> {
         Arc::new(ComposerUpdate::from(
             self.inner.lock().unwrap().ordered_list(),
@@ -270,7 +716,12 @@ impl ComposerModel {
     ) -> Arc<ComposerUpdate> {
         let url = Utf16String::from_str(&url);
         let text = Utf16String::from_str(&text);
-        let suggestion = wysiwyg::SuggestionPattern::from(suggestion);
+        let Ok(suggestion) = wysiwyg::SuggestionPattern::try_from(suggestion)
+        else {
+            return Arc::new(ComposerUpdate::from(
+                wysiwyg::ComposerUpdate::keep(),
+            ));
+        };
         let attrs = attributes
             .iter()
             .map(|attr| {
@@ -294,6 +745,43 @@ impl ComposerModel {
         ))
     }
 
+    pub fn insert_custom_emoji(
+        self: &Arc<Self>,
+        mxc: String,
+        shortcode: String,
+    ) -> Arc<ComposerUpdate> {
+        let mxc = Utf16String::from_str(&mxc);
+        let shortcode = Utf16String::from_str(&shortcode);
+        Arc::new(ComposerUpdate::from(
+            self.inner
+                .lock()
+                .unwrap()
+                .insert_custom_emoji(mxc, shortcode),
+        ))
+    }
+
+    pub fn insert_custom_emoji_suggestion(
+        self: &Arc<Self>,
+        mxc: String,
+        shortcode: String,
+        suggestion: SuggestionPattern,
+    ) -> Arc<ComposerUpdate> {
+        let mxc = Utf16String::from_str(&mxc);
+        let shortcode = Utf16String::from_str(&shortcode);
+        let Ok(suggestion) = wysiwyg::SuggestionPattern::try_from(suggestion)
+        else {
+            return Arc::new(ComposerUpdate::from(
+                wysiwyg::ComposerUpdate::keep(),
+            ));
+        };
+        Arc::new(ComposerUpdate::from(
+            self.inner
+                .lock()
+                .unwrap()
+                .insert_custom_emoji_suggestion(mxc, shortcode, suggestion),
+        ))
+    }
+
     pub fn indent(self: &Arc<Self>) -> Arc<ComposerUpdate> {
         Arc::new(ComposerUpdate::from(self.inner.lock().unwrap().indent()))
     }
@@ -310,6 +798,11 @@ impl ComposerModel {
         self.inner.lock().unwrap().to_tree().to_string()
     }
 
+    pub fn get_dom_snapshot(self: &Arc<Self>) -> DomNode {
+        let model = self.inner.lock().unwrap();
+        DomNode::from_node(model.state.dom.document_node(), &[])
+    }
+
     pub fn get_current_dom_state(self: &Arc<Self>) -> ComposerState {
         self.inner
             .lock()
@@ -329,6 +822,10 @@ impl ComposerModel {
         self.inner.lock().unwrap().get_link_action().into()
     }
 
+    pub fn get_context_state(self: &Arc<Self>) -> ContextState {
+        self.inner.lock().unwrap().get_context_state().into()
+    }
+
     #[cfg(not(debug_assertions))]
     pub fn debug_panic(self: &Arc<Self>) {
         // No-op
@@ -341,6 +838,7 @@ impl ComposerModel {
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct Attribute {
     pub key: String,
     pub value: String,
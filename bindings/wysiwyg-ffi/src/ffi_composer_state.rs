@@ -5,16 +5,25 @@ pub struct ComposerState {
     pub html: Vec<u16>,
     pub start: u32,
     pub end: u32,
+    pub composition_start: Option<u32>,
+    pub composition_end: Option<u32>,
 }
 
 impl From<wysiwyg::ComposerState<Utf16String>> for ComposerState {
     fn from(state: wysiwyg::ComposerState<Utf16String>) -> Self {
         let start_utf16_codeunit: usize = state.start.into();
         let end_utf16_codeunit: usize = state.end.into();
+        let composition = state.composition.map(|(start, end)| {
+            let start: usize = start.into();
+            let end: usize = end.into();
+            (u32::try_from(start).unwrap(), u32::try_from(end).unwrap())
+        });
         Self {
             html: state.dom.to_html().into_vec(),
             start: u32::try_from(start_utf16_codeunit).unwrap(),
             end: u32::try_from(end_utf16_codeunit).unwrap(),
+            composition_start: composition.map(|(start, _)| start),
+            composition_end: composition.map(|(_, end)| end),
         }
     }
 }
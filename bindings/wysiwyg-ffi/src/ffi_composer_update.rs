@@ -2,7 +2,7 @@ use widestring::Utf16String;
 
 use crate::ffi_menu_state::MenuState;
 use crate::ffi_text_update::TextUpdate;
-use crate::MenuAction;
+use crate::{LimitResult, MenuAction};
 
 pub struct ComposerUpdate {
     inner: wysiwyg::ComposerUpdate<Utf16String>,
@@ -24,6 +24,10 @@ impl ComposerUpdate {
     pub fn menu_action(&self) -> MenuAction {
         MenuAction::from(self.inner.menu_action.clone())
     }
+
+    pub fn limit_result(&self) -> LimitResult {
+        LimitResult::from(self.inner.limit_result)
+    }
 }
 
 #[cfg(test)]
@@ -31,8 +35,8 @@ mod test {
     use std::{collections::HashMap, sync::Arc};
 
     use crate::{
-        ActionState, Attribute, ComposerAction, ComposerModel, MenuAction,
-        MenuState, SuggestionPattern,
+        ActionState, Attribute, ComposerAction, ComposerModel, ContextState,
+        MenuAction, MenuState, SuggestionPattern,
     };
 
     #[test]
@@ -44,7 +48,8 @@ mod test {
         assert_eq!(
             update.menu_state(),
             MenuState::Update {
-                action_states: redo_indent_unindent_disabled()
+                action_states: redo_indent_unindent_disabled(),
+                context_state: Some(ContextState::default()),
             }
         );
     }
@@ -58,7 +63,8 @@ mod test {
         assert_eq!(
             update.menu_state(),
             MenuState::Update {
-                action_states: undo_redo_indent_unindent_disabled()
+                action_states: undo_redo_indent_unindent_disabled(),
+                context_state: Some(ContextState::default()),
             }
         );
     }
@@ -75,7 +81,8 @@ mod test {
         assert_eq!(
             update.menu_state(),
             MenuState::Update {
-                action_states: undo_redo_indent_unindent_disabled()
+                action_states: undo_redo_indent_unindent_disabled(),
+                context_state: Some(ContextState::default()),
             }
         );
     }
@@ -89,7 +96,8 @@ mod test {
         assert_eq!(
             update.menu_state(),
             MenuState::Update {
-                action_states: undo_redo_indent_unindent_disabled()
+                action_states: undo_redo_indent_unindent_disabled(),
+                context_state: Some(ContextState::default()),
             }
         );
     }
@@ -157,6 +165,8 @@ mod test {
             (ComposerAction::Bold, ActionState::Enabled),
             (ComposerAction::Indent, ActionState::Disabled),
             (ComposerAction::InlineCode, ActionState::Enabled),
+            (ComposerAction::Superscript, ActionState::Enabled),
+            (ComposerAction::Subscript, ActionState::Enabled),
             (ComposerAction::Italic, ActionState::Enabled),
             (ComposerAction::Link, ActionState::Enabled),
             (ComposerAction::OrderedList, ActionState::Enabled),
@@ -168,6 +178,9 @@ mod test {
             (ComposerAction::UnorderedList, ActionState::Enabled),
             (ComposerAction::CodeBlock, ActionState::Enabled),
             (ComposerAction::Quote, ActionState::Enabled),
+            (ComposerAction::ClearFormatting, ActionState::Disabled),
+            (ComposerAction::TextColor, ActionState::Enabled),
+            (ComposerAction::BackgroundColor, ActionState::Enabled),
         ])
     }
 
@@ -177,6 +190,8 @@ mod test {
             (ComposerAction::Bold, ActionState::Enabled),
             (ComposerAction::Indent, ActionState::Disabled),
             (ComposerAction::InlineCode, ActionState::Enabled),
+            (ComposerAction::Superscript, ActionState::Enabled),
+            (ComposerAction::Subscript, ActionState::Enabled),
             (ComposerAction::Italic, ActionState::Enabled),
             (ComposerAction::Link, ActionState::Enabled),
             (ComposerAction::OrderedList, ActionState::Enabled),
@@ -188,6 +203,9 @@ mod test {
             (ComposerAction::UnorderedList, ActionState::Enabled),
             (ComposerAction::CodeBlock, ActionState::Enabled),
             (ComposerAction::Quote, ActionState::Enabled),
+            (ComposerAction::ClearFormatting, ActionState::Disabled),
+            (ComposerAction::TextColor, ActionState::Enabled),
+            (ComposerAction::BackgroundColor, ActionState::Enabled),
         ])
     }
 }
@@ -0,0 +1,115 @@
+pub struct ContentMetrics {
+    pub graphemes: u32,
+    pub code_units: u32,
+    pub message_html_bytes: u32,
+    pub plain_text_bytes: u32,
+}
+
+impl From<wysiwyg::ContentMetrics> for ContentMetrics {
+    fn from(inner: wysiwyg::ContentMetrics) -> Self {
+        Self {
+            graphemes: u32::try_from(inner.graphemes).unwrap(),
+            code_units: u32::try_from(inner.code_units).unwrap(),
+            message_html_bytes: u32::try_from(inner.message_html_bytes)
+                .unwrap(),
+            plain_text_bytes: u32::try_from(inner.plain_text_bytes).unwrap(),
+        }
+    }
+}
+
+pub struct ContentLimit {
+    pub unit: LimitUnit,
+    pub max: u32,
+    pub behaviour: LimitBehaviour,
+}
+
+impl From<wysiwyg::ContentLimit> for ContentLimit {
+    fn from(inner: wysiwyg::ContentLimit) -> Self {
+        Self {
+            unit: inner.unit.into(),
+            max: u32::try_from(inner.max).unwrap(),
+            behaviour: inner.behaviour.into(),
+        }
+    }
+}
+
+impl From<ContentLimit> for wysiwyg::ContentLimit {
+    fn from(limit: ContentLimit) -> Self {
+        Self {
+            unit: limit.unit.into(),
+            max: usize::try_from(limit.max).unwrap(),
+            behaviour: limit.behaviour.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitUnit {
+    Graphemes,
+    CodeUnits,
+    MessageHtmlBytes,
+    PlainTextBytes,
+}
+
+impl From<wysiwyg::LimitUnit> for LimitUnit {
+    fn from(inner: wysiwyg::LimitUnit) -> Self {
+        match inner {
+            wysiwyg::LimitUnit::Graphemes => Self::Graphemes,
+            wysiwyg::LimitUnit::CodeUnits => Self::CodeUnits,
+            wysiwyg::LimitUnit::MessageHtmlBytes => Self::MessageHtmlBytes,
+            wysiwyg::LimitUnit::PlainTextBytes => Self::PlainTextBytes,
+        }
+    }
+}
+
+impl From<LimitUnit> for wysiwyg::LimitUnit {
+    fn from(unit: LimitUnit) -> Self {
+        match unit {
+            LimitUnit::Graphemes => Self::Graphemes,
+            LimitUnit::CodeUnits => Self::CodeUnits,
+            LimitUnit::MessageHtmlBytes => Self::MessageHtmlBytes,
+            LimitUnit::PlainTextBytes => Self::PlainTextBytes,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitBehaviour {
+    Truncate,
+    Reject,
+}
+
+impl From<wysiwyg::LimitBehaviour> for LimitBehaviour {
+    fn from(inner: wysiwyg::LimitBehaviour) -> Self {
+        match inner {
+            wysiwyg::LimitBehaviour::Truncate => Self::Truncate,
+            wysiwyg::LimitBehaviour::Reject => Self::Reject,
+        }
+    }
+}
+
+impl From<LimitBehaviour> for wysiwyg::LimitBehaviour {
+    fn from(behaviour: LimitBehaviour) -> Self {
+        match behaviour {
+            LimitBehaviour::Truncate => Self::Truncate,
+            LimitBehaviour::Reject => Self::Reject,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitResult {
+    Applied,
+    Truncated,
+    Rejected,
+}
+
+impl From<wysiwyg::LimitResult> for LimitResult {
+    fn from(inner: wysiwyg::LimitResult) -> Self {
+        match inner {
+            wysiwyg::LimitResult::Applied => Self::Applied,
+            wysiwyg::LimitResult::Truncated => Self::Truncated,
+            wysiwyg::LimitResult::Rejected => Self::Rejected,
+        }
+    }
+}
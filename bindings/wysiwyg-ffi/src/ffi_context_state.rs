@@ -0,0 +1,54 @@
+use widestring::Utf16String;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ContextState {
+    pub block_type: BlockType,
+    pub list_depth: u32,
+    pub link_url: Option<String>,
+    pub in_mention: bool,
+    pub text_color: Option<String>,
+    pub background_color: Option<String>,
+}
+
+impl From<wysiwyg::ContextState<Utf16String>> for ContextState {
+    fn from(inner: wysiwyg::ContextState<Utf16String>) -> Self {
+        Self {
+            block_type: inner.block_type.into(),
+            list_depth: u32::try_from(inner.list_depth).unwrap(),
+            link_url: inner.link_url.map(|url| url.to_string()),
+            in_mention: inner.in_mention,
+            text_color: inner.text_color.map(|color| color.to_string()),
+            background_color: inner
+                .background_color
+                .map(|color| color.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum BlockType {
+    #[default]
+    Paragraph,
+    Quote,
+    CodeBlock,
+    ListItem,
+    TableCell,
+    Heading {
+        level: u8,
+    },
+    Other,
+}
+
+impl From<wysiwyg::BlockType> for BlockType {
+    fn from(inner: wysiwyg::BlockType) -> Self {
+        match inner {
+            wysiwyg::BlockType::Paragraph => Self::Paragraph,
+            wysiwyg::BlockType::Quote => Self::Quote,
+            wysiwyg::BlockType::CodeBlock => Self::CodeBlock,
+            wysiwyg::BlockType::ListItem => Self::ListItem,
+            wysiwyg::BlockType::TableCell => Self::TableCell,
+            wysiwyg::BlockType::Heading { level } => Self::Heading { level },
+            wysiwyg::BlockType::Other => Self::Other,
+        }
+    }
+}
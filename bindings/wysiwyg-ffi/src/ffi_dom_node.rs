@@ -0,0 +1,320 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+use wysiwyg::{ContainerNodeKind, InlineFormatType, ListType, MentionNodeKind};
+
+use crate::Attribute;
+
+/// An immutable snapshot of a node in the composer's DOM, along with all of
+/// its descendants.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DomNode {
+    pub kind: DomNodeKind,
+    /// The tag name, or None for text nodes.
+    pub tag: Option<String>,
+    pub attributes: Vec<Attribute>,
+    /// The text of a text node, the display text of a mention, the alt text
+    /// of an image or the text of an opaque node. Empty for other nodes.
+    pub text: String,
+    pub children: Vec<DomNode>,
+    /// The inline formats applying to this node, outermost first, including
+    /// the node's own format if it is a formatting node.
+    pub formatting: Vec<InlineFormat>,
+    /// The target of a link or the source of an image.
+    pub url: Option<String>,
+    pub mention: Option<MentionData>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DomNodeKind {
+    Generic,
+    Formatting,
+    Link,
+    OrderedList,
+    UnorderedList,
+    ListItem,
+    CodeBlock,
+    Quote,
+    Paragraph,
+    Table,
+    TableRow,
+    TableCell,
+    Text,
+    LineBreak,
+    HorizontalRule,
+    Mention,
+    Image,
+    CustomEmoji,
+    Opaque,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InlineFormat {
+    Bold,
+    Italic,
+    StrikeThrough,
+    Underline,
+    InlineCode,
+    Superscript,
+    Subscript,
+    TextColor { color: String },
+    BackgroundColor { color: String },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MentionData {
+    /// The matrix.to URL of the mention, or None for @room.
+    pub url: Option<String>,
+    pub display_text: String,
+}
+
+impl From<&InlineFormatType> for InlineFormat {
+    fn from(format: &InlineFormatType) -> Self {
+        match format {
+            InlineFormatType::Bold => Self::Bold,
+            InlineFormatType::Italic => Self::Italic,
+            InlineFormatType::StrikeThrough => Self::StrikeThrough,
+            InlineFormatType::Underline => Self::Underline,
+            InlineFormatType::InlineCode => Self::InlineCode,
+            InlineFormatType::Superscript => Self::Superscript,
+            InlineFormatType::Subscript => Self::Subscript,
+            InlineFormatType::TextColor(color) => Self::TextColor {
+                color: color.clone(),
+            },
+            InlineFormatType::BackgroundColor(color) => Self::BackgroundColor {
+                color: color.clone(),
+            },
+        }
+    }
+}
+
+impl DomNode {
+    /// Build a snapshot of the supplied node, given the inline formats of
+    /// its ancestors.
+    pub(crate) fn from_node(
+        node: &wysiwyg::DomNode<Utf16String>,
+        formatting: &[InlineFormat],
+    ) -> Self {
+        let mut formatting = formatting.to_vec();
+        let snapshot = Self {
+            kind: DomNodeKind::Generic,
+            tag: None,
+            attributes: Vec::new(),
+            text: String::new(),
+            children: Vec::new(),
+            formatting: Vec::new(),
+            url: None,
+            mention: None,
+        };
+        let snapshot = match node {
+            wysiwyg::DomNode::Container(node) => {
+                let (kind, url) = match node.kind() {
+                    ContainerNodeKind::Generic => (DomNodeKind::Generic, None),
+                    ContainerNodeKind::Formatting(format) => {
+                        formatting.push(format.into());
+                        (DomNodeKind::Formatting, None)
+                    }
+                    ContainerNodeKind::Link(url) => {
+                        (DomNodeKind::Link, Some(url.to_string()))
+                    }
+                    ContainerNodeKind::List(ListType::Ordered) => {
+                        (DomNodeKind::OrderedList, None)
+                    }
+                    ContainerNodeKind::List(ListType::Unordered) => {
+                        (DomNodeKind::UnorderedList, None)
+                    }
+                    ContainerNodeKind::ListItem => {
+                        (DomNodeKind::ListItem, None)
+                    }
+                    ContainerNodeKind::CodeBlock => {
+                        (DomNodeKind::CodeBlock, None)
+                    }
+                    ContainerNodeKind::Quote => (DomNodeKind::Quote, None),
+                    ContainerNodeKind::Paragraph => {
+                        (DomNodeKind::Paragraph, None)
+                    }
+                    ContainerNodeKind::Table => (DomNodeKind::Table, None),
+                    ContainerNodeKind::TableRow => {
+                        (DomNodeKind::TableRow, None)
+                    }
+                    ContainerNodeKind::TableCell => {
+                        (DomNodeKind::TableCell, None)
+                    }
+                };
+                Self {
+                    kind,
+                    tag: Some(node.name().to_string()),
+                    attributes: node
+                        .attributes()
+                        .map(|attrs| attributes(attrs))
+                        .unwrap_or_default(),
+                    children: node
+                        .children()
+                        .iter()
+                        .map(|child| Self::from_node(child, &formatting))
+                        .collect(),
+                    url,
+                    ..snapshot
+                }
+            }
+            wysiwyg::DomNode::Text(node) => Self {
+                kind: DomNodeKind::Text,
+                text: node.data().to_string(),
+                ..snapshot
+            },
+            wysiwyg::DomNode::LineBreak(node) => Self {
+                kind: DomNodeKind::LineBreak,
+                tag: Some(node.name().to_string()),
+                ..snapshot
+            },
+            wysiwyg::DomNode::HorizontalRule(node) => Self {
+                kind: DomNodeKind::HorizontalRule,
+                tag: Some(node.name().to_string()),
+                ..snapshot
+            },
+            wysiwyg::DomNode::Mention(node) => Self {
+                kind: DomNodeKind::Mention,
+                tag: Some(node.name().to_string()),
+                attributes: attributes(node.attributes()),
+                text: node.display_text().to_string(),
+                mention: Some(MentionData {
+                    url: match node.kind() {
+                        MentionNodeKind::MatrixUrl { url, .. } => {
+                            Some(url.to_string())
+                        }
+                        MentionNodeKind::AtRoom => None,
+                    },
+                    display_text: node.display_text().to_string(),
+                }),
+                ..snapshot
+            },
+            wysiwyg::DomNode::Image(node) => Self {
+                kind: if node.is_custom_emoji() {
+                    DomNodeKind::CustomEmoji
+                } else {
+                    DomNodeKind::Image
+                },
+                tag: Some(node.name().to_string()),
+                attributes: attributes(node.attributes()),
+                text: node.alt().to_string(),
+                url: Some(node.src().to_string()),
+                ..snapshot
+            },
+            wysiwyg::DomNode::Opaque(node) => Self {
+                kind: DomNodeKind::Opaque,
+                tag: Some(node.name().to_string()),
+                attributes: attributes(node.attributes()),
+                text: node.text().to_string(),
+                ..snapshot
+            },
+        };
+        Self {
+            formatting,
+            ..snapshot
+        }
+    }
+}
+
+fn attributes(attrs: &[(Utf16String, Utf16String)]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .map(|(key, value)| Attribute {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        ComposerModel, DomNode, DomNodeKind, InlineFormat, MentionData,
+    };
+
+    #[test]
+    fn snapshot_of_empty_model_is_an_empty_document() {
+        let model = Arc::new(ComposerModel::new());
+        let snapshot = model.get_dom_snapshot();
+        assert_eq!(snapshot.kind, DomNodeKind::Generic);
+        assert!(snapshot.children.is_empty());
+    }
+
+    #[test]
+    fn snapshot_includes_inherited_formatting() {
+        let model = Arc::new(ComposerModel::new());
+        model
+            .set_content_from_html(String::from(
+                "<strong>a<em>b</em></strong><ol><li>c</li></ol>",
+            ))
+            .unwrap();
+        let snapshot = model.get_dom_snapshot();
+        let paragraph = &snapshot.children[0];
+        assert_eq!(paragraph.kind, DomNodeKind::Paragraph);
+        let bold = &paragraph.children[0];
+        assert_eq!(bold.kind, DomNodeKind::Formatting);
+        assert_eq!(bold.tag.as_deref(), Some("strong"));
+        assert_eq!(bold.children[0].text, "a");
+        assert_eq!(bold.children[0].formatting, vec![InlineFormat::Bold]);
+        let italic_text = &bold.children[1].children[0];
+        assert_eq!(italic_text.kind, DomNodeKind::Text);
+        assert_eq!(italic_text.tag, None);
+        assert_eq!(
+            italic_text.formatting,
+            vec![InlineFormat::Bold, InlineFormat::Italic]
+        );
+        let list = &snapshot.children[1];
+        assert_eq!(list.kind, DomNodeKind::OrderedList);
+        assert_eq!(list.children[0].kind, DomNodeKind::ListItem);
+        assert!(list.formatting.is_empty());
+    }
+
+    #[test]
+    fn snapshot_includes_links_and_mentions() {
+        let model = Arc::new(ComposerModel::new());
+        model
+            .set_content_from_html(String::from(
+                "<a href=\"https://matrix.org\">link</a> @room",
+            ))
+            .unwrap();
+        let snapshot = model.get_dom_snapshot();
+        let link = &snapshot.children[0];
+        assert_eq!(link.kind, DomNodeKind::Link);
+        assert_eq!(link.url.as_deref(), Some("https://matrix.org"));
+        assert_eq!(link.children[0].text, "link");
+
+        let mention = &snapshot.children[2];
+        assert_eq!(mention.kind, DomNodeKind::Mention);
+        assert_eq!(mention.text, "@room");
+        assert_eq!(
+            mention.mention,
+            Some(MentionData {
+                url: None,
+                display_text: String::from("@room"),
+            })
+        );
+    }
+
+    #[test]
+    fn snapshot_is_not_affected_by_later_edits() {
+        let model = Arc::new(ComposerModel::new());
+        model.replace_text(String::from("abc"));
+        let snapshot: DomNode = model.get_dom_snapshot();
+        model.replace_text(String::from("def"));
+        assert_eq!(snapshot.children[0].text, "abc");
+        assert_eq!(model.get_dom_snapshot().children[0].text, "abcdef");
+    }
+}
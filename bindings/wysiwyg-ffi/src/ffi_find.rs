@@ -0,0 +1,27 @@
+pub struct FindOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+impl From<FindOptions> for wysiwyg::FindOptions {
+    fn from(options: FindOptions) -> Self {
+        Self {
+            case_sensitive: options.case_sensitive,
+            whole_word: options.whole_word,
+        }
+    }
+}
+
+pub struct FindMatch {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl From<wysiwyg::FindMatch> for FindMatch {
+    fn from(inner: wysiwyg::FindMatch) -> Self {
+        Self {
+            start: u32::try_from(inner.start).unwrap(),
+            end: u32::try_from(inner.end).unwrap(),
+        }
+    }
+}
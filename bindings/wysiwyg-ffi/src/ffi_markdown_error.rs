@@ -0,0 +1,40 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+
+use widestring::Utf16String;
+
+#[derive(Debug)]
+pub enum MarkdownError {
+    InvalidListItem,
+}
+
+impl Display for MarkdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MarkdownError::InvalidListItem => {
+                "could not convert list to markdown"
+            }
+        })
+    }
+}
+
+impl From<wysiwyg::MarkdownError<Utf16String>> for MarkdownError {
+    fn from(error: wysiwyg::MarkdownError<Utf16String>) -> Self {
+        match error {
+            wysiwyg::MarkdownError::InvalidListItem(_) => Self::InvalidListItem,
+        }
+    }
+}
@@ -1,22 +1,26 @@
 use std::collections::HashMap;
 
+use widestring::Utf16String;
+
 use crate::into_ffi::IntoFfi;
-use crate::{ActionState, ComposerAction};
+use crate::{ActionState, ComposerAction, ContextState};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum MenuState {
     Keep,
     Update {
         action_states: HashMap<ComposerAction, ActionState>,
+        context_state: Option<ContextState>,
     },
 }
 
 impl MenuState {
-    pub fn from(inner: wysiwyg::MenuState) -> Self {
+    pub fn from(inner: wysiwyg::MenuState<Utf16String>) -> Self {
         match inner {
             wysiwyg::MenuState::Keep => Self::Keep,
             wysiwyg::MenuState::Update(menu_update) => Self::Update {
                 action_states: menu_update.action_states.into_ffi(),
+                context_state: menu_update.context_state.map(Into::into),
             },
         }
     }
@@ -0,0 +1,97 @@
+use widestring::Utf16String;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageType {
+    Text,
+    Emote,
+    Notice,
+}
+
+impl From<MessageType> for wysiwyg::MessageType {
+    fn from(msgtype: MessageType) -> Self {
+        match msgtype {
+            MessageType::Text => Self::Text,
+            MessageType::Emote => Self::Emote,
+            MessageType::Notice => Self::Notice,
+        }
+    }
+}
+
+pub struct MessageContentOptions {
+    pub msgtype: MessageType,
+    pub markdown_body: bool,
+}
+
+impl From<MessageContentOptions> for wysiwyg::MessageContentOptions {
+    fn from(options: MessageContentOptions) -> Self {
+        Self {
+            msgtype: options.msgtype.into(),
+            markdown_body: options.markdown_body,
+        }
+    }
+}
+
+pub struct MessageContent {
+    pub msgtype: String,
+    pub body: String,
+    pub format: Option<String>,
+    pub formatted_body: Option<String>,
+    pub mentions: Mentions,
+    pub in_reply_to: Option<String>,
+    /// The whole content, serialised as JSON.
+    pub json: String,
+}
+
+impl From<wysiwyg::MessageContent<Utf16String>> for MessageContent {
+    fn from(inner: wysiwyg::MessageContent<Utf16String>) -> Self {
+        Self {
+            msgtype: inner.msgtype.as_str().to_owned(),
+            body: inner.body.to_string(),
+            format: inner.format.as_ref().map(|f| f.to_string()),
+            formatted_body: inner
+                .formatted_body
+                .as_ref()
+                .map(|b| b.to_string()),
+            in_reply_to: inner.in_reply_to.as_ref().map(|id| id.to_string()),
+            json: inner.to_json(),
+            mentions: Mentions {
+                user_ids: inner
+                    .mentions
+                    .user_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect(),
+                room: inner.mentions.room,
+            },
+        }
+    }
+}
+
+pub struct ReplyParent {
+    pub event_id: String,
+    pub room_id: String,
+    pub sender: String,
+    pub msgtype: MessageType,
+    pub body: String,
+    pub formatted_body: Option<String>,
+}
+
+impl From<ReplyParent> for wysiwyg::ReplyParent<Utf16String> {
+    fn from(parent: ReplyParent) -> Self {
+        Self {
+            event_id: Utf16String::from_str(&parent.event_id),
+            room_id: Utf16String::from_str(&parent.room_id),
+            sender: Utf16String::from_str(&parent.sender),
+            msgtype: parent.msgtype.into(),
+            body: Utf16String::from_str(&parent.body),
+            formatted_body: parent
+                .formatted_body
+                .map(|html| Utf16String::from_str(&html)),
+        }
+    }
+}
+
+pub struct Mentions {
+    pub user_ids: Vec<String>,
+    pub room: bool,
+}
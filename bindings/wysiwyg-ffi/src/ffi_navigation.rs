@@ -0,0 +1,35 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Forwards,
+    Backwards,
+}
+
+impl From<Direction> for wysiwyg::Direction {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Forwards => Self::Forwards,
+            Direction::Backwards => Self::Backwards,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Granularity {
+    Character,
+    Word,
+    Line,
+    Paragraph,
+    Document,
+}
+
+impl From<Granularity> for wysiwyg::Granularity {
+    fn from(granularity: Granularity) -> Self {
+        match granularity {
+            Granularity::Character => Self::Character,
+            Granularity::Word => Self::Word,
+            Granularity::Line => Self::Line,
+            Granularity::Paragraph => Self::Paragraph,
+            Granularity::Document => Self::Document,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub name: Option<String>,
+    pub line: Option<u64>,
+}
+
+impl From<wysiwyg::ParseWarning> for ParseWarning {
+    fn from(inner: wysiwyg::ParseWarning) -> Self {
+        Self {
+            kind: ParseWarningKind::from(inner.kind),
+            name: inner.name,
+            line: inner.line,
+        }
+    }
+}
+
+pub enum ParseWarningKind {
+    UnknownElement,
+    ElementUnwrapped,
+    AttributeDropped,
+    NodeDropped,
+    NbspRemoved,
+    CodeBlockRestructured,
+    ReplyFallbackRemoved,
+}
+
+impl From<wysiwyg::ParseWarningKind> for ParseWarningKind {
+    fn from(inner: wysiwyg::ParseWarningKind) -> Self {
+        match inner {
+            wysiwyg::ParseWarningKind::UnknownElement => Self::UnknownElement,
+            wysiwyg::ParseWarningKind::ElementUnwrapped => {
+                Self::ElementUnwrapped
+            }
+            wysiwyg::ParseWarningKind::AttributeDropped => {
+                Self::AttributeDropped
+            }
+            wysiwyg::ParseWarningKind::NodeDropped => Self::NodeDropped,
+            wysiwyg::ParseWarningKind::NbspRemoved => Self::NbspRemoved,
+            wysiwyg::ParseWarningKind::CodeBlockRestructured => {
+                Self::CodeBlockRestructured
+            }
+            wysiwyg::ParseWarningKind::ReplyFallbackRemoved => {
+                Self::ReplyFallbackRemoved
+            }
+        }
+    }
+}
@@ -12,11 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt::Display;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum PatternKey {
     At,
     Hash,
     Slash,
+    Colon,
+    Custom { key: String },
 }
 
 impl From<wysiwyg::PatternKey> for PatternKey {
@@ -25,16 +29,132 @@ impl From<wysiwyg::PatternKey> for PatternKey {
             wysiwyg::PatternKey::At => Self::At,
             wysiwyg::PatternKey::Hash => Self::Hash,
             wysiwyg::PatternKey::Slash => Self::Slash,
+            wysiwyg::PatternKey::Colon => Self::Colon,
+            wysiwyg::PatternKey::Custom(key) => Self::Custom {
+                key: key.to_string(),
+            },
         }
     }
 }
 
-impl From<PatternKey> for wysiwyg::PatternKey {
-    fn from(key: PatternKey) -> Self {
-        match key {
+impl TryFrom<PatternKey> for wysiwyg::PatternKey {
+    type Error = CustomKeyError;
+
+    fn try_from(key: PatternKey) -> Result<Self, Self::Error> {
+        Ok(match key {
             PatternKey::At => Self::At,
             PatternKey::Hash => Self::Hash,
             PatternKey::Slash => Self::Slash,
+            PatternKey::Colon => Self::Colon,
+            PatternKey::Custom { key } => Self::Custom(custom_key_char(&key)?),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CustomKeyError {
+    /// The key is empty or longer than one character.
+    NotOneCharacter,
+}
+
+impl Display for CustomKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CustomKeyError::NotOneCharacter => {
+                "custom suggestion keys must be a single character"
+            }
+        })
+    }
+}
+
+/// The character of a custom key, which must be exactly one character
+/// long.
+fn custom_key_char(key: &str) -> Result<char, CustomKeyError> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(CustomKeyError::NotOneCharacter),
+    }
+}
+
+pub struct SuggestionTrigger {
+    pub key: String,
+    pub only_at_start: bool,
+    pub allow_whitespace: bool,
+    pub min_length: u32,
+}
+
+impl From<wysiwyg::SuggestionTrigger> for SuggestionTrigger {
+    fn from(inner: wysiwyg::SuggestionTrigger) -> Self {
+        Self {
+            key: inner.key.to_string(),
+            only_at_start: inner.only_at_start,
+            allow_whitespace: inner.allow_whitespace,
+            min_length: u32::try_from(inner.min_length).unwrap_or(u32::MAX),
+        }
+    }
+}
+
+impl TryFrom<SuggestionTrigger> for wysiwyg::SuggestionTrigger {
+    type Error = CustomKeyError;
+
+    fn try_from(trigger: SuggestionTrigger) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: custom_key_char(&trigger.key)?,
+            only_at_start: trigger.only_at_start,
+            allow_whitespace: trigger.allow_whitespace,
+            min_length: usize::try_from(trigger.min_length)
+                .unwrap_or(usize::MAX),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{ComposerModel, CustomKeyError, PatternKey, SuggestionTrigger};
+
+    #[test]
+    fn custom_keys_must_be_a_single_character() {
+        let convert = |key: &str| {
+            wysiwyg::PatternKey::try_from(PatternKey::Custom {
+                key: key.into(),
+            })
+        };
+        assert_eq!(convert("!"), Ok(wysiwyg::PatternKey::Custom('!')));
+        assert_eq!(convert(""), Err(CustomKeyError::NotOneCharacter));
+        assert_eq!(convert("!!"), Err(CustomKeyError::NotOneCharacter));
+    }
+
+    #[test]
+    fn triggers_with_invalid_keys_are_rejected() {
+        let model = Arc::new(ComposerModel::new());
+        for key in ["", "!!"] {
+            assert_eq!(
+                model.add_suggestion_trigger(trigger(key)),
+                Err(CustomKeyError::NotOneCharacter)
+            );
+        }
+        assert_eq!(model.add_suggestion_trigger(trigger("!")), Ok(()));
+        assert_eq!(
+            model.set_suggestion_triggers(vec![trigger(""), trigger("+")]),
+            Err(CustomKeyError::NotOneCharacter)
+        );
+        let keys: Vec<String> = model
+            .suggestion_triggers()
+            .into_iter()
+            .map(|t| t.key)
+            .collect();
+        assert_eq!(keys, ["!"]);
+    }
+
+    fn trigger(key: &str) -> SuggestionTrigger {
+        SuggestionTrigger {
+            key: key.into(),
+            only_at_start: false,
+            allow_whitespace: false,
+            min_length: 0,
         }
     }
 }
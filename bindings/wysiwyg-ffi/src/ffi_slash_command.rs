@@ -0,0 +1,44 @@
+pub struct SlashCommand {
+    pub name: String,
+    pub args: Vec<String>,
+    pub takes_remainder: bool,
+    pub description: String,
+}
+
+impl From<SlashCommand> for wysiwyg::SlashCommand {
+    fn from(command: SlashCommand) -> Self {
+        Self {
+            name: command.name,
+            args: command.args,
+            takes_remainder: command.takes_remainder,
+            description: command.description,
+        }
+    }
+}
+
+impl From<wysiwyg::SlashCommand> for SlashCommand {
+    fn from(inner: wysiwyg::SlashCommand) -> Self {
+        Self {
+            name: inner.name,
+            args: inner.args,
+            takes_remainder: inner.takes_remainder,
+            description: inner.description,
+        }
+    }
+}
+
+pub struct ParsedCommand {
+    pub name: String,
+    pub args: Vec<String>,
+    pub remainder_html: Option<String>,
+}
+
+impl From<wysiwyg::ParsedCommand> for ParsedCommand {
+    fn from(inner: wysiwyg::ParsedCommand) -> Self {
+        Self {
+            name: inner.name,
+            args: inner.args,
+            remainder_html: inner.remainder_html,
+        }
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::PatternKey;
+use crate::{CustomKeyError, PatternKey};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct SuggestionPattern {
@@ -33,13 +33,15 @@ impl From<wysiwyg::SuggestionPattern> for SuggestionPattern {
     }
 }
 
-impl From<SuggestionPattern> for wysiwyg::SuggestionPattern {
-    fn from(pattern: SuggestionPattern) -> Self {
-        Self {
-            key: wysiwyg::PatternKey::from(pattern.key),
+impl TryFrom<SuggestionPattern> for wysiwyg::SuggestionPattern {
+    type Error = CustomKeyError;
+
+    fn try_from(pattern: SuggestionPattern) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: wysiwyg::PatternKey::try_from(pattern.key)?,
             text: pattern.text,
             start: usize::try_from(pattern.start).unwrap(),
             end: usize::try_from(pattern.end).unwrap(),
-        }
+        })
     }
 }
@@ -19,31 +19,89 @@ mod ffi_composer_action;
 mod ffi_composer_model;
 mod ffi_composer_state;
 mod ffi_composer_update;
+mod ffi_content_limit;
+mod ffi_context_state;
 mod ffi_dom_creation_error;
+mod ffi_dom_node;
+mod ffi_find;
 mod ffi_link_actions;
+mod ffi_markdown_error;
 mod ffi_menu_action;
 mod ffi_menu_state;
+mod ffi_message_content;
+mod ffi_navigation;
+mod ffi_parse_warning;
 mod ffi_pattern_key;
+mod ffi_slash_command;
 mod ffi_suggestion_pattern;
 mod ffi_text_update;
 mod into_ffi;
 
 use std::sync::Arc;
 
+use widestring::Utf16String;
+
 pub use crate::ffi_action_state::ActionState;
 pub use crate::ffi_composer_action::ComposerAction;
 pub use crate::ffi_composer_model::Attribute;
 pub use crate::ffi_composer_model::ComposerModel;
 pub use crate::ffi_composer_state::ComposerState;
 pub use crate::ffi_composer_update::ComposerUpdate;
+pub use crate::ffi_content_limit::ContentLimit;
+pub use crate::ffi_content_limit::ContentMetrics;
+pub use crate::ffi_content_limit::LimitBehaviour;
+pub use crate::ffi_content_limit::LimitResult;
+pub use crate::ffi_content_limit::LimitUnit;
+pub use crate::ffi_context_state::BlockType;
+pub use crate::ffi_context_state::ContextState;
 pub use crate::ffi_dom_creation_error::DomCreationError;
+pub use crate::ffi_dom_node::DomNode;
+pub use crate::ffi_dom_node::DomNodeKind;
+pub use crate::ffi_dom_node::InlineFormat;
+pub use crate::ffi_dom_node::MentionData;
+pub use crate::ffi_find::FindMatch;
+pub use crate::ffi_find::FindOptions;
 pub use crate::ffi_link_actions::LinkAction;
+pub use crate::ffi_markdown_error::MarkdownError;
 pub use crate::ffi_menu_action::MenuAction;
 pub use crate::ffi_menu_state::MenuState;
+pub use crate::ffi_message_content::Mentions;
+pub use crate::ffi_message_content::MessageContent;
+pub use crate::ffi_message_content::MessageContentOptions;
+pub use crate::ffi_message_content::MessageType;
+pub use crate::ffi_message_content::ReplyParent;
+pub use crate::ffi_navigation::Direction;
+pub use crate::ffi_navigation::Granularity;
+pub use crate::ffi_parse_warning::ParseWarning;
+pub use crate::ffi_parse_warning::ParseWarningKind;
+pub use crate::ffi_pattern_key::CustomKeyError;
 pub use crate::ffi_pattern_key::PatternKey;
+pub use crate::ffi_pattern_key::SuggestionTrigger;
+pub use crate::ffi_slash_command::ParsedCommand;
+pub use crate::ffi_slash_command::SlashCommand;
 pub use crate::ffi_suggestion_pattern::SuggestionPattern;
 pub use crate::ffi_text_update::TextUpdate;
 
 pub fn new_composer_model() -> Arc<ComposerModel> {
     Arc::new(ComposerModel::new())
 }
+
+pub fn new_composer_model_from_html(
+    html: String,
+    start_utf16_codeunit: u32,
+    end_utf16_codeunit: u32,
+) -> Result<Arc<ComposerModel>, DomCreationError> {
+    Ok(Arc::new(ComposerModel::from_html(
+        &html,
+        start_utf16_codeunit,
+        end_utf16_codeunit,
+    )?))
+}
+
+pub fn parse_html_diagnostics(
+    html: String,
+) -> Result<Vec<ParseWarning>, DomCreationError> {
+    let (_, warnings) = wysiwyg::parse_with_diagnostics::<Utf16String>(&html)
+        .map_err(wysiwyg::DomCreationError::HtmlParseError)?;
+    Ok(warnings.into_iter().map(ParseWarning::from).collect())
+}
@@ -19,6 +19,7 @@ use std::{
 
 use wasm_bindgen::prelude::*;
 use widestring::Utf16String;
+use wysiwyg::ToHtml;
 
 #[wasm_bindgen(start)]
 pub fn start() {
@@ -37,14 +38,28 @@ pub fn new_composer_model_from_html(
     html: &str,
     start_utf16_codeunit: u32,
     end_utf16_codeunit: u32,
-) -> ComposerModel {
-    ComposerModel {
-        inner: wysiwyg::ComposerModel::<Utf16String>::from_html(
+) -> Result<ComposerModel, DomCreationError> {
+    Ok(ComposerModel {
+        inner: wysiwyg::ComposerModel::<Utf16String>::try_from_html(
             html,
             usize::try_from(start_utf16_codeunit).unwrap(),
             usize::try_from(end_utf16_codeunit).unwrap(),
-        ),
-    }
+        )?,
+    })
+}
+
+/// Parse [html] and return an array of ParseWarning, one for each part of
+/// it that could not be kept exactly as it was.
+#[wasm_bindgen]
+pub fn parse_html_diagnostics(
+    html: &str,
+) -> Result<js_sys::Array, DomCreationError> {
+    let (_, warnings) = wysiwyg::parse_with_diagnostics::<Utf16String>(html)
+        .map_err(wysiwyg::DomCreationError::HtmlParseError)?;
+    Ok(warnings
+        .into_iter()
+        .map(|warning| JsValue::from(ParseWarning::from(warning)))
+        .collect())
 }
 
 #[wasm_bindgen]
@@ -59,6 +74,10 @@ pub enum ActionState {
 
     /// The button cannot be clicked
     Disabled,
+
+    /// The button can be clicked, but only part of the selection has the
+    /// property e.g. half of the selected text is bold
+    Mixed,
 }
 
 trait IntoFfi {
@@ -124,8 +143,22 @@ impl ComposerModel {
         self.inner.get_content_as_message_html().to_string()
     }
 
-    pub fn get_content_as_markdown(&self) -> String {
-        self.inner.get_content_as_markdown().to_string()
+    pub fn get_content_as_markdown(&self) -> Result<String, MarkdownError> {
+        Ok(self.inner.try_get_content_as_markdown()?.to_string())
+    }
+
+    pub fn switch_to_markdown_mode(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.switch_to_markdown_mode())
+    }
+
+    pub fn switch_to_rich_mode(
+        &mut self,
+    ) -> Result<ComposerUpdate, DomCreationError> {
+        Ok(ComposerUpdate::from(self.inner.switch_to_rich_mode()?))
+    }
+
+    pub fn is_markdown_mode(&self) -> bool {
+        self.inner.is_markdown_mode()
     }
 
     pub fn get_content_as_plain_text(&self) -> String {
@@ -157,6 +190,231 @@ impl ComposerModel {
         ))
     }
 
+    pub fn move_cursor(
+        &mut self,
+        direction: Direction,
+        granularity: Granularity,
+        extend_selection: bool,
+    ) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.move_cursor(
+            direction.into(),
+            granularity.into(),
+            extend_selection,
+        ))
+    }
+
+    pub fn select_word_at_cursor(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.select_word_at_cursor())
+    }
+
+    pub fn select_block_at_cursor(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.select_block_at_cursor())
+    }
+
+    pub fn select_all(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.select_all())
+    }
+
+    pub fn to_message_content(
+        &self,
+        msgtype: MessageType,
+        markdown_body: bool,
+    ) -> MessageContent {
+        MessageContent::from(self.inner.to_message_content(
+            wysiwyg::MessageContentOptions {
+                msgtype: msgtype.into(),
+                markdown_body,
+            },
+        ))
+    }
+
+    pub fn to_reply_message_content(
+        &self,
+        msgtype: MessageType,
+        markdown_body: bool,
+        parent: ReplyParent,
+    ) -> MessageContent {
+        MessageContent::from(self.inner.to_reply_message_content(
+            wysiwyg::MessageContentOptions {
+                msgtype: msgtype.into(),
+                markdown_body,
+            },
+            &parent.into(),
+        ))
+    }
+
+    pub fn content_metrics(&self) -> ContentMetrics {
+        ContentMetrics::from(self.inner.content_metrics())
+    }
+
+    pub fn set_content_limit(
+        &mut self,
+        unit: LimitUnit,
+        max: u32,
+        behaviour: LimitBehaviour,
+    ) {
+        self.inner.set_content_limit(Some(wysiwyg::ContentLimit {
+            unit: unit.into(),
+            max: usize::try_from(max).unwrap(),
+            behaviour: behaviour.into(),
+        }));
+    }
+
+    pub fn remove_content_limit(&mut self) {
+        self.inner.set_content_limit(None);
+    }
+
+    pub fn content_limit(&self) -> Option<ContentLimit> {
+        self.inner.content_limit().map(ContentLimit::from)
+    }
+
+    pub fn set_emoji_suggestion_min_length(&mut self, min_length: u32) {
+        self.inner.set_emoji_suggestion_min_length(
+            usize::try_from(min_length).unwrap(),
+        );
+    }
+
+    /// Add a custom character that starts a suggestion, replacing any
+    /// existing trigger for the same character. Fails if [key] isn't a
+    /// single character.
+    pub fn add_suggestion_trigger(
+        &mut self,
+        key: &str,
+        only_at_start: bool,
+        allow_whitespace: bool,
+        min_length: u32,
+    ) -> Result<(), CustomKeyError> {
+        let key = custom_key_char(key)?;
+        self.inner
+            .add_suggestion_trigger(wysiwyg::SuggestionTrigger {
+                key,
+                only_at_start,
+                allow_whitespace,
+                min_length: usize::try_from(min_length).unwrap_or(usize::MAX),
+            });
+        Ok(())
+    }
+
+    pub fn clear_suggestion_triggers(&mut self) {
+        self.inner.set_suggestion_triggers(Vec::new());
+    }
+
+    /// Returns an array of SuggestionTrigger
+    pub fn suggestion_triggers(&self) -> js_sys::Array {
+        self.inner
+            .suggestion_triggers()
+            .iter()
+            .cloned()
+            .map(|trigger| JsValue::from(SuggestionTrigger::from(trigger)))
+            .collect()
+    }
+
+    pub fn set_mention_suggestion_max_words(&mut self, max_words: u32) {
+        self.inner.set_mention_suggestion_max_words(
+            usize::try_from(max_words).unwrap(),
+        );
+    }
+
+    /// Add a command to the ones known to `commands_for_suggestion` and
+    /// `parse_command`, replacing any existing command with the same name.
+    /// [args] is an array of the names of the arguments, as strings.
+    pub fn add_slash_command(
+        &mut self,
+        name: &str,
+        args: js_sys::Array,
+        takes_remainder: bool,
+        description: &str,
+    ) {
+        self.inner.add_slash_command(wysiwyg::SlashCommand {
+            name: name.to_owned(),
+            args: args.iter().filter_map(|a| a.as_string()).collect(),
+            takes_remainder,
+            description: description.to_owned(),
+        });
+    }
+
+    pub fn clear_slash_commands(&mut self) {
+        self.inner.set_slash_commands(Vec::new());
+    }
+
+    /// Returns an array of SlashCommand
+    pub fn slash_commands(&self) -> js_sys::Array {
+        self.inner
+            .slash_commands()
+            .iter()
+            .cloned()
+            .map(|command| JsValue::from(SlashCommand::from(command)))
+            .collect()
+    }
+
+    /// Returns an array of SlashCommand
+    pub fn commands_for_suggestion(
+        &self,
+        suggestion: &SuggestionPattern,
+    ) -> js_sys::Array {
+        let Ok(suggestion) =
+            wysiwyg::SuggestionPattern::try_from(suggestion.clone())
+        else {
+            return js_sys::Array::new();
+        };
+        self.inner
+            .commands_for_suggestion(&suggestion)
+            .into_iter()
+            .map(|command| JsValue::from(SlashCommand::from(command)))
+            .collect()
+    }
+
+    pub fn parse_command(&self) -> Option<ParsedCommand> {
+        self.inner.parse_command().map(ParsedCommand::from)
+    }
+
+    /// Returns an array of FindMatch
+    pub fn find(
+        &mut self,
+        query: &str,
+        case_sensitive: bool,
+        whole_word: bool,
+    ) -> js_sys::Array {
+        let options = wysiwyg::FindOptions {
+            case_sensitive,
+            whole_word,
+        };
+        self.inner
+            .find(Utf16String::from_str(query), options)
+            .into_iter()
+            .map(|found| JsValue::from(FindMatch::from(found)))
+            .collect()
+    }
+
+    pub fn replace_all(
+        &mut self,
+        query: &str,
+        replacement: &str,
+        case_sensitive: bool,
+        whole_word: bool,
+    ) -> ComposerUpdate {
+        let options = wysiwyg::FindOptions {
+            case_sensitive,
+            whole_word,
+        };
+        ComposerUpdate::from(self.inner.replace_all(
+            Utf16String::from_str(query),
+            Utf16String::from_str(replacement),
+            options,
+        ))
+    }
+
+    pub fn replace_match(
+        &mut self,
+        index: u32,
+        replacement: &str,
+    ) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.replace_match(
+            usize::try_from(index).unwrap(),
+            Utf16String::from_str(replacement),
+        ))
+    }
+
     pub fn selection_start(&self) -> u32 {
         let ret: usize = self.inner.state.start.into();
         ret as u32
@@ -167,6 +425,28 @@ impl ComposerModel {
         ret as u32
     }
 
+    pub fn composition_start(&self) -> Option<u32> {
+        self.inner.state.composition.map(|(start, _)| {
+            let ret: usize = start.into();
+            ret as u32
+        })
+    }
+
+    pub fn composition_end(&self) -> Option<u32> {
+        self.inner.state.composition.map(|(_, end)| {
+            let ret: usize = end.into();
+            ret as u32
+        })
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.inner.has_selection()
+    }
+
+    pub fn has_cursor(&self) -> bool {
+        self.inner.has_cursor()
+    }
+
     pub fn replace_text(&mut self, new_text: &str) -> ComposerUpdate {
         // Conversion here to UTF-16, which has presumably just been
         // converted to UTF-8 in the FFI bindings layer.
@@ -177,17 +457,49 @@ impl ComposerModel {
         )
     }
 
+    pub fn replace_text_in(
+        &mut self,
+        new_text: &str,
+        start: u32,
+        end: u32,
+    ) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.replace_text_in(
+            Utf16String::from_str(new_text),
+            usize::try_from(start).unwrap(),
+            usize::try_from(end).unwrap(),
+        ))
+    }
+
     pub fn replace_text_suggestion(
         &mut self,
         new_text: &str,
         suggestion: &SuggestionPattern,
     ) -> ComposerUpdate {
+        let Ok(suggestion) =
+            wysiwyg::SuggestionPattern::try_from(suggestion.clone())
+        else {
+            return ComposerUpdate::from(wysiwyg::ComposerUpdate::keep());
+        };
         ComposerUpdate::from(self.inner.replace_text_suggestion(
             Utf16String::from_str(new_text),
-            wysiwyg::SuggestionPattern::from(suggestion.clone()),
+            suggestion,
         ))
     }
 
+    pub fn set_composing_text(&mut self, text: &str) -> ComposerUpdate {
+        ComposerUpdate::from(
+            self.inner.set_composing_text(Utf16String::from_str(text)),
+        )
+    }
+
+    pub fn commit_composition(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.commit_composition())
+    }
+
+    pub fn cancel_composition(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.cancel_composition())
+    }
+
     pub fn set_content_from_html(
         &mut self,
         text: &str,
@@ -232,6 +544,13 @@ impl ComposerModel {
         ComposerUpdate::from(self.inner.delete_word())
     }
 
+    pub fn delete_in(&mut self, start: u32, end: u32) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.delete_in(
+            usize::try_from(start).unwrap(),
+            usize::try_from(end).unwrap(),
+        ))
+    }
+
     pub fn bold(&mut self) -> ComposerUpdate {
         ComposerUpdate::from(self.inner.bold())
     }
@@ -256,10 +575,80 @@ impl ComposerModel {
         ComposerUpdate::from(self.inner.inline_code())
     }
 
+    pub fn superscript(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.superscript())
+    }
+
+    pub fn subscript(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.subscript())
+    }
+
+    pub fn clear_formatting(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.clear_formatting())
+    }
+
+    pub fn clear_formatting_and_links(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.clear_formatting_and_links())
+    }
+
+    pub fn set_text_color(&mut self, color: Option<String>) -> ComposerUpdate {
+        ComposerUpdate::from(
+            self.inner.set_text_color(
+                color.map(|color| Utf16String::from_str(&color)),
+            ),
+        )
+    }
+
+    pub fn set_background_color(
+        &mut self,
+        color: Option<String>,
+    ) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.set_background_color(
+            color.map(|color| Utf16String::from_str(&color)),
+        ))
+    }
+
     pub fn code_block(&mut self) -> ComposerUpdate {
         ComposerUpdate::from(self.inner.code_block())
     }
 
+    pub fn insert_horizontal_rule(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.insert_horizontal_rule())
+    }
+
+    pub fn insert_table(&mut self, rows: u32, columns: u32) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.insert_table(
+            usize::try_from(rows).unwrap(),
+            usize::try_from(columns).unwrap(),
+        ))
+    }
+
+    pub fn add_table_row(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.add_table_row())
+    }
+
+    pub fn remove_table_row(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.remove_table_row())
+    }
+
+    pub fn add_table_column(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.add_table_column())
+    }
+
+    pub fn remove_table_column(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.remove_table_column())
+    }
+
+    /// Returns a keep update outside of tables, so that the Tab key can be
+    /// handled by the browser instead.
+    pub fn next_table_cell(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.next_table_cell())
+    }
+
+    pub fn previous_table_cell(&mut self) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.previous_table_cell())
+    }
+
     pub fn undo(&mut self) -> ComposerUpdate {
         ComposerUpdate::from(self.inner.undo())
     }
@@ -288,6 +677,10 @@ impl ComposerModel {
         self.inner.get_link_action().into()
     }
 
+    pub fn get_context_state(&self) -> ContextState {
+        self.inner.get_context_state().into()
+    }
+
     pub fn set_link(
         &mut self,
         url: &str,
@@ -322,10 +715,15 @@ impl ComposerModel {
         suggestion: &SuggestionPattern,
         attributes: js_sys::Map,
     ) -> ComposerUpdate {
+        let Ok(suggestion) =
+            wysiwyg::SuggestionPattern::try_from(suggestion.clone())
+        else {
+            return ComposerUpdate::from(wysiwyg::ComposerUpdate::keep());
+        };
         ComposerUpdate::from(self.inner.set_link_suggestion(
             Utf16String::from_str(url),
             Utf16String::from_str(text),
-            wysiwyg::SuggestionPattern::from(suggestion.clone()),
+            suggestion,
             attributes.into_vec(),
         ))
     }
@@ -333,6 +731,82 @@ impl ComposerModel {
     pub fn remove_links(&mut self) -> ComposerUpdate {
         ComposerUpdate::from(self.inner.remove_links())
     }
+
+    pub fn insert_custom_emoji(
+        &mut self,
+        mxc: &str,
+        shortcode: &str,
+    ) -> ComposerUpdate {
+        ComposerUpdate::from(self.inner.insert_custom_emoji(
+            Utf16String::from_str(mxc),
+            Utf16String::from_str(shortcode),
+        ))
+    }
+
+    pub fn insert_custom_emoji_suggestion(
+        &mut self,
+        mxc: &str,
+        shortcode: &str,
+        suggestion: &SuggestionPattern,
+    ) -> ComposerUpdate {
+        let Ok(suggestion) =
+            wysiwyg::SuggestionPattern::try_from(suggestion.clone())
+        else {
+            return ComposerUpdate::from(wysiwyg::ComposerUpdate::keep());
+        };
+        ComposerUpdate::from(self.inner.insert_custom_emoji_suggestion(
+            Utf16String::from_str(mxc),
+            Utf16String::from_str(shortcode),
+            suggestion,
+        ))
+    }
+
+    pub fn to_tree(&self) -> String {
+        self.inner.to_tree().to_string()
+    }
+
+    pub fn get_current_dom_state(&self) -> ComposerState {
+        ComposerState::from(self.inner.get_current_state())
+    }
+
+    #[cfg(not(debug_assertions))]
+    pub fn debug_panic(&self) {
+        // No-op
+    }
+
+    /// Force a panic for test purposes
+    #[cfg(debug_assertions)]
+    pub fn debug_panic(&self) {
+        panic!("This should only happen in tests.");
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct ComposerState {
+    pub html: String,
+    pub start: u32,
+    pub end: u32,
+    pub composition_start: Option<u32>,
+    pub composition_end: Option<u32>,
+}
+
+impl From<&wysiwyg::ComposerState<Utf16String>> for ComposerState {
+    fn from(state: &wysiwyg::ComposerState<Utf16String>) -> Self {
+        let start: usize = state.start.into();
+        let end: usize = state.end.into();
+        let composition = state.composition.map(|(start, end)| {
+            let start: usize = start.into();
+            let end: usize = end.into();
+            (u32::try_from(start).unwrap(), u32::try_from(end).unwrap())
+        });
+        Self {
+            html: state.dom.to_html().to_string(),
+            start: u32::try_from(start).unwrap(),
+            end: u32::try_from(end).unwrap(),
+            composition_start: composition.map(|(start, _)| start),
+            composition_end: composition.map(|(_, end)| end),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -359,6 +833,10 @@ impl ComposerUpdate {
     pub fn menu_action(&self) -> MenuAction {
         MenuAction::from(self.inner.menu_action.clone())
     }
+
+    pub fn limit_result(&self) -> LimitResult {
+        LimitResult::from(self.inner.limit_result)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -406,6 +884,60 @@ impl From<DomCreationError> for JsValue {
     }
 }
 
+#[derive(Clone, Debug)]
+#[wasm_bindgen]
+pub enum MarkdownError {
+    InvalidListItem,
+}
+
+impl Display for MarkdownError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MarkdownError::InvalidListItem => {
+                "could not convert list to markdown"
+            }
+        })
+    }
+}
+
+impl From<wysiwyg::MarkdownError<Utf16String>> for MarkdownError {
+    fn from(error: wysiwyg::MarkdownError<Utf16String>) -> Self {
+        match error {
+            wysiwyg::MarkdownError::InvalidListItem(_) => Self::InvalidListItem,
+        }
+    }
+}
+
+impl From<MarkdownError> for JsValue {
+    fn from(error: MarkdownError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+#[wasm_bindgen]
+pub enum DomHandleError {
+    /// The handle doesn't refer to a node, because the model has changed
+    /// since it was created, or because it came from a different model.
+    InvalidHandle,
+}
+
+impl Display for DomHandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DomHandleError::InvalidHandle => {
+                "dom handle does not refer to a node in this model"
+            }
+        })
+    }
+}
+
+impl From<DomHandleError> for JsValue {
+    fn from(error: DomHandleError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
 #[wasm_bindgen(getter_with_clone)]
 pub struct TextUpdate {
     pub keep: Option<Keep>,
@@ -479,11 +1011,11 @@ pub struct Selection {
 
 #[wasm_bindgen]
 pub struct MenuState {
-    inner: wysiwyg::MenuState,
+    inner: wysiwyg::MenuState<Utf16String>,
 }
 
 impl MenuState {
-    pub fn from(inner: wysiwyg::MenuState) -> Self {
+    pub fn from(inner: wysiwyg::MenuState<Utf16String>) -> Self {
         Self { inner }
     }
 }
@@ -508,12 +1040,58 @@ impl MenuState {
 #[derive(Debug)]
 pub struct MenuStateUpdate {
     pub action_states: js_sys::Map,
+    pub context_state: Option<ContextState>,
 }
 
 impl MenuStateUpdate {
-    pub fn from(inner: &wysiwyg::MenuStateUpdate) -> Self {
+    pub fn from(inner: &wysiwyg::MenuStateUpdate<Utf16String>) -> Self {
         Self {
             action_states: inner.action_states.into_ffi(),
+            context_state: inner.context_state.clone().map(Into::into),
+        }
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ContextState {
+    /// One of "paragraph", "quote", "codeblock", "listitem", "tablecell",
+    /// "heading" or "other"
+    pub block_type: String,
+    /// The level from 1 to 6 if [Self::block_type] is "heading"
+    pub heading_level: Option<u8>,
+    pub list_depth: u32,
+    pub link_url: Option<String>,
+    pub in_mention: bool,
+    pub text_color: Option<String>,
+    pub background_color: Option<String>,
+}
+
+impl From<wysiwyg::ContextState<Utf16String>> for ContextState {
+    fn from(inner: wysiwyg::ContextState<Utf16String>) -> Self {
+        let block_type = match inner.block_type {
+            wysiwyg::BlockType::Paragraph => "paragraph",
+            wysiwyg::BlockType::Quote => "quote",
+            wysiwyg::BlockType::CodeBlock => "codeblock",
+            wysiwyg::BlockType::ListItem => "listitem",
+            wysiwyg::BlockType::TableCell => "tablecell",
+            wysiwyg::BlockType::Heading { .. } => "heading",
+            wysiwyg::BlockType::Other => "other",
+        };
+        let heading_level = match inner.block_type {
+            wysiwyg::BlockType::Heading { level } => Some(level),
+            _ => None,
+        };
+        Self {
+            block_type: String::from(block_type),
+            heading_level,
+            list_depth: u32::try_from(inner.list_depth).unwrap(),
+            link_url: inner.link_url.map(|url| url.to_string()),
+            in_mention: inner.in_mention,
+            text_color: inner.text_color.map(|color| color.to_string()),
+            background_color: inner
+                .background_color
+                .map(|color| color.to_string()),
         }
     }
 }
@@ -567,6 +1145,8 @@ pub enum ComposerAction {
     StrikeThrough,
     Underline,
     InlineCode,
+    Superscript,
+    Subscript,
     Link,
     Undo,
     Redo,
@@ -576,6 +1156,9 @@ pub enum ComposerAction {
     Unindent,
     CodeBlock,
     Quote,
+    ClearFormatting,
+    TextColor,
+    BackgroundColor,
 }
 
 impl ComposerAction {
@@ -586,6 +1169,8 @@ impl ComposerAction {
             wysiwyg::ComposerAction::StrikeThrough => Self::StrikeThrough,
             wysiwyg::ComposerAction::Underline => Self::Underline,
             wysiwyg::ComposerAction::InlineCode => Self::InlineCode,
+            wysiwyg::ComposerAction::Superscript => Self::Superscript,
+            wysiwyg::ComposerAction::Subscript => Self::Subscript,
             wysiwyg::ComposerAction::Link => Self::Link,
             wysiwyg::ComposerAction::Undo => Self::Undo,
             wysiwyg::ComposerAction::Redo => Self::Redo,
@@ -595,6 +1180,9 @@ impl ComposerAction {
             wysiwyg::ComposerAction::Unindent => Self::Unindent,
             wysiwyg::ComposerAction::CodeBlock => Self::CodeBlock,
             wysiwyg::ComposerAction::Quote => Self::Quote,
+            wysiwyg::ComposerAction::ClearFormatting => Self::ClearFormatting,
+            wysiwyg::ComposerAction::TextColor => Self::TextColor,
+            wysiwyg::ComposerAction::BackgroundColor => Self::BackgroundColor,
         }
     }
 }
@@ -607,6 +1195,8 @@ impl From<&ComposerAction> for wysiwyg::ComposerAction {
             ComposerAction::StrikeThrough => Self::StrikeThrough,
             ComposerAction::Underline => Self::Underline,
             ComposerAction::InlineCode => Self::InlineCode,
+            ComposerAction::Superscript => Self::Superscript,
+            ComposerAction::Subscript => Self::Subscript,
             ComposerAction::Link => Self::Link,
             ComposerAction::Undo => Self::Undo,
             ComposerAction::Redo => Self::Redo,
@@ -616,6 +1206,281 @@ impl From<&ComposerAction> for wysiwyg::ComposerAction {
             ComposerAction::Unindent => Self::Unindent,
             ComposerAction::CodeBlock => Self::CodeBlock,
             ComposerAction::Quote => Self::Quote,
+            ComposerAction::ClearFormatting => Self::ClearFormatting,
+            ComposerAction::TextColor => Self::TextColor,
+            ComposerAction::BackgroundColor => Self::BackgroundColor,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct FindMatch {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl From<wysiwyg::FindMatch> for FindMatch {
+    fn from(inner: wysiwyg::FindMatch) -> Self {
+        Self {
+            start: u32::try_from(inner.start).unwrap(),
+            end: u32::try_from(inner.end).unwrap(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum MessageType {
+    Text,
+    Emote,
+    Notice,
+}
+
+impl From<MessageType> for wysiwyg::MessageType {
+    fn from(msgtype: MessageType) -> Self {
+        match msgtype {
+            MessageType::Text => Self::Text,
+            MessageType::Emote => Self::Emote,
+            MessageType::Notice => Self::Notice,
+        }
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct MessageContent {
+    pub msgtype: String,
+    pub body: String,
+    pub format: Option<String>,
+    pub formatted_body: Option<String>,
+    /// Array of the mentioned user IDs, as strings
+    pub mentioned_user_ids: js_sys::Array,
+    pub mentions_room: bool,
+    pub in_reply_to: Option<String>,
+    /// The whole content, serialised as JSON
+    pub json: String,
+}
+
+impl From<wysiwyg::MessageContent<Utf16String>> for MessageContent {
+    fn from(inner: wysiwyg::MessageContent<Utf16String>) -> Self {
+        Self {
+            msgtype: inner.msgtype.as_str().to_owned(),
+            body: inner.body.to_string(),
+            format: inner.format.as_ref().map(|f| f.to_string()),
+            formatted_body: inner
+                .formatted_body
+                .as_ref()
+                .map(|b| b.to_string()),
+            mentioned_user_ids: inner
+                .mentions
+                .user_ids
+                .iter()
+                .map(|id| JsValue::from(id.to_string()))
+                .collect(),
+            mentions_room: inner.mentions.room,
+            in_reply_to: inner.in_reply_to.as_ref().map(|id| id.to_string()),
+            json: inner.to_json(),
+        }
+    }
+}
+
+/// The event being replied to, for [ComposerModel::to_reply_message_content]
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct ReplyParent {
+    pub event_id: String,
+    pub room_id: String,
+    pub sender: String,
+    pub msgtype: MessageType,
+    pub body: String,
+    pub formatted_body: Option<String>,
+}
+
+#[wasm_bindgen]
+impl ReplyParent {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        event_id: String,
+        room_id: String,
+        sender: String,
+        msgtype: MessageType,
+        body: String,
+        formatted_body: Option<String>,
+    ) -> Self {
+        Self {
+            event_id,
+            room_id,
+            sender,
+            msgtype,
+            body,
+            formatted_body,
+        }
+    }
+}
+
+impl From<ReplyParent> for wysiwyg::ReplyParent<Utf16String> {
+    fn from(parent: ReplyParent) -> Self {
+        Self {
+            event_id: Utf16String::from_str(&parent.event_id),
+            room_id: Utf16String::from_str(&parent.room_id),
+            sender: Utf16String::from_str(&parent.sender),
+            msgtype: parent.msgtype.into(),
+            body: Utf16String::from_str(&parent.body),
+            formatted_body: parent
+                .formatted_body
+                .map(|html| Utf16String::from_str(&html)),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct ContentMetrics {
+    pub graphemes: u32,
+    pub code_units: u32,
+    pub message_html_bytes: u32,
+    pub plain_text_bytes: u32,
+}
+
+impl From<wysiwyg::ContentMetrics> for ContentMetrics {
+    fn from(inner: wysiwyg::ContentMetrics) -> Self {
+        Self {
+            graphemes: u32::try_from(inner.graphemes).unwrap(),
+            code_units: u32::try_from(inner.code_units).unwrap(),
+            message_html_bytes: u32::try_from(inner.message_html_bytes)
+                .unwrap(),
+            plain_text_bytes: u32::try_from(inner.plain_text_bytes).unwrap(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct ContentLimit {
+    pub unit: LimitUnit,
+    pub max: u32,
+    pub behaviour: LimitBehaviour,
+}
+
+impl From<wysiwyg::ContentLimit> for ContentLimit {
+    fn from(inner: wysiwyg::ContentLimit) -> Self {
+        Self {
+            unit: inner.unit.into(),
+            max: u32::try_from(inner.max).unwrap(),
+            behaviour: inner.behaviour.into(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum LimitUnit {
+    Graphemes,
+    CodeUnits,
+    MessageHtmlBytes,
+    PlainTextBytes,
+}
+
+impl From<wysiwyg::LimitUnit> for LimitUnit {
+    fn from(inner: wysiwyg::LimitUnit) -> Self {
+        match inner {
+            wysiwyg::LimitUnit::Graphemes => Self::Graphemes,
+            wysiwyg::LimitUnit::CodeUnits => Self::CodeUnits,
+            wysiwyg::LimitUnit::MessageHtmlBytes => Self::MessageHtmlBytes,
+            wysiwyg::LimitUnit::PlainTextBytes => Self::PlainTextBytes,
+        }
+    }
+}
+
+impl From<LimitUnit> for wysiwyg::LimitUnit {
+    fn from(unit: LimitUnit) -> Self {
+        match unit {
+            LimitUnit::Graphemes => Self::Graphemes,
+            LimitUnit::CodeUnits => Self::CodeUnits,
+            LimitUnit::MessageHtmlBytes => Self::MessageHtmlBytes,
+            LimitUnit::PlainTextBytes => Self::PlainTextBytes,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum LimitBehaviour {
+    Truncate,
+    Reject,
+}
+
+impl From<wysiwyg::LimitBehaviour> for LimitBehaviour {
+    fn from(inner: wysiwyg::LimitBehaviour) -> Self {
+        match inner {
+            wysiwyg::LimitBehaviour::Truncate => Self::Truncate,
+            wysiwyg::LimitBehaviour::Reject => Self::Reject,
+        }
+    }
+}
+
+impl From<LimitBehaviour> for wysiwyg::LimitBehaviour {
+    fn from(behaviour: LimitBehaviour) -> Self {
+        match behaviour {
+            LimitBehaviour::Truncate => Self::Truncate,
+            LimitBehaviour::Reject => Self::Reject,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum LimitResult {
+    Applied,
+    Truncated,
+    Rejected,
+}
+
+impl From<wysiwyg::LimitResult> for LimitResult {
+    fn from(inner: wysiwyg::LimitResult) -> Self {
+        match inner {
+            wysiwyg::LimitResult::Applied => Self::Applied,
+            wysiwyg::LimitResult::Truncated => Self::Truncated,
+            wysiwyg::LimitResult::Rejected => Self::Rejected,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Forwards,
+    Backwards,
+}
+
+impl From<Direction> for wysiwyg::Direction {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Forwards => Self::Forwards,
+            Direction::Backwards => Self::Backwards,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum Granularity {
+    Character,
+    Word,
+    Line,
+    Paragraph,
+    Document,
+}
+
+impl From<Granularity> for wysiwyg::Granularity {
+    fn from(granularity: Granularity) -> Self {
+        match granularity {
+            Granularity::Character => Self::Character,
+            Granularity::Word => Self::Word,
+            Granularity::Line => Self::Line,
+            Granularity::Paragraph => Self::Paragraph,
+            Granularity::Document => Self::Document,
         }
     }
 }
@@ -624,6 +1489,8 @@ impl From<&ComposerAction> for wysiwyg::ComposerAction {
 #[derive(Clone)]
 pub struct SuggestionPattern {
     pub key: PatternKey,
+    /// The trigger character, if [key] is [PatternKey::Custom].
+    pub custom_key: Option<String>,
     pub text: String,
     pub start: u32,
     pub end: u32,
@@ -631,8 +1498,13 @@ pub struct SuggestionPattern {
 
 impl From<wysiwyg::SuggestionPattern> for SuggestionPattern {
     fn from(inner: wysiwyg::SuggestionPattern) -> Self {
+        let custom_key = match inner.key {
+            wysiwyg::PatternKey::Custom(key) => Some(key.to_string()),
+            _ => None,
+        };
         Self {
             key: PatternKey::from(inner.key),
+            custom_key,
             text: inner.text,
             start: u32::try_from(inner.start).unwrap(),
             end: u32::try_from(inner.end).unwrap(),
@@ -640,13 +1512,169 @@ impl From<wysiwyg::SuggestionPattern> for SuggestionPattern {
     }
 }
 
-impl From<SuggestionPattern> for wysiwyg::SuggestionPattern {
-    fn from(pattern: SuggestionPattern) -> Self {
-        Self {
-            key: wysiwyg::PatternKey::from(pattern.key),
+impl TryFrom<SuggestionPattern> for wysiwyg::SuggestionPattern {
+    type Error = CustomKeyError;
+
+    fn try_from(pattern: SuggestionPattern) -> Result<Self, Self::Error> {
+        let key = match pattern.key {
+            PatternKey::At => wysiwyg::PatternKey::At,
+            PatternKey::Hash => wysiwyg::PatternKey::Hash,
+            PatternKey::Slash => wysiwyg::PatternKey::Slash,
+            PatternKey::Colon => wysiwyg::PatternKey::Colon,
+            PatternKey::Custom => wysiwyg::PatternKey::Custom(custom_key_char(
+                pattern.custom_key.as_deref().unwrap_or_default(),
+            )?),
+        };
+        Ok(Self {
+            key,
             text: pattern.text,
             start: usize::try_from(pattern.start).unwrap(),
             end: usize::try_from(pattern.end).unwrap(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[wasm_bindgen]
+pub enum CustomKeyError {
+    /// The key is empty or longer than one character.
+    NotOneCharacter,
+}
+
+impl Display for CustomKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CustomKeyError::NotOneCharacter => {
+                "custom suggestion keys must be a single character"
+            }
+        })
+    }
+}
+
+impl From<CustomKeyError> for JsValue {
+    fn from(error: CustomKeyError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// The character of a custom key, which must be exactly one character
+/// long.
+fn custom_key_char(key: &str) -> Result<char, CustomKeyError> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(CustomKeyError::NotOneCharacter),
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct SuggestionTrigger {
+    pub key: String,
+    pub only_at_start: bool,
+    pub allow_whitespace: bool,
+    pub min_length: u32,
+}
+
+impl From<wysiwyg::SuggestionTrigger> for SuggestionTrigger {
+    fn from(inner: wysiwyg::SuggestionTrigger) -> Self {
+        Self {
+            key: inner.key.to_string(),
+            only_at_start: inner.only_at_start,
+            allow_whitespace: inner.allow_whitespace,
+            min_length: u32::try_from(inner.min_length).unwrap_or(u32::MAX),
+        }
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct SlashCommand {
+    pub name: String,
+    /// Array of the names of the arguments, as strings
+    pub args: js_sys::Array,
+    pub takes_remainder: bool,
+    pub description: String,
+}
+
+impl From<wysiwyg::SlashCommand> for SlashCommand {
+    fn from(inner: wysiwyg::SlashCommand) -> Self {
+        Self {
+            name: inner.name,
+            args: inner.args.into_iter().map(JsValue::from).collect(),
+            takes_remainder: inner.takes_remainder,
+            description: inner.description,
+        }
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub name: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl From<wysiwyg::ParseWarning> for ParseWarning {
+    fn from(inner: wysiwyg::ParseWarning) -> Self {
+        Self {
+            kind: ParseWarningKind::from(inner.kind),
+            name: inner.name,
+            line: inner.line.map(|line| line as u32),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum ParseWarningKind {
+    UnknownElement,
+    ElementUnwrapped,
+    AttributeDropped,
+    NodeDropped,
+    NbspRemoved,
+    CodeBlockRestructured,
+    ReplyFallbackRemoved,
+}
+
+impl From<wysiwyg::ParseWarningKind> for ParseWarningKind {
+    fn from(inner: wysiwyg::ParseWarningKind) -> Self {
+        match inner {
+            wysiwyg::ParseWarningKind::UnknownElement => Self::UnknownElement,
+            wysiwyg::ParseWarningKind::ElementUnwrapped => {
+                Self::ElementUnwrapped
+            }
+            wysiwyg::ParseWarningKind::AttributeDropped => {
+                Self::AttributeDropped
+            }
+            wysiwyg::ParseWarningKind::NodeDropped => Self::NodeDropped,
+            wysiwyg::ParseWarningKind::NbspRemoved => Self::NbspRemoved,
+            wysiwyg::ParseWarningKind::CodeBlockRestructured => {
+                Self::CodeBlockRestructured
+            }
+            wysiwyg::ParseWarningKind::ReplyFallbackRemoved => {
+                Self::ReplyFallbackRemoved
+            }
+        }
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct ParsedCommand {
+    pub name: String,
+    /// Array of the values of the arguments, as strings
+    pub args: js_sys::Array,
+    pub remainder_html: Option<String>,
+}
+
+impl From<wysiwyg::ParsedCommand> for ParsedCommand {
+    fn from(inner: wysiwyg::ParsedCommand) -> Self {
+        Self {
+            name: inner.name,
+            args: inner.args.into_iter().map(JsValue::from).collect(),
+            remainder_html: inner.remainder_html,
         }
     }
 }
@@ -657,6 +1685,8 @@ pub enum PatternKey {
     At,
     Hash,
     Slash,
+    Colon,
+    Custom,
 }
 
 impl From<wysiwyg::PatternKey> for PatternKey {
@@ -665,16 +1695,8 @@ impl From<wysiwyg::PatternKey> for PatternKey {
             wysiwyg::PatternKey::At => Self::At,
             wysiwyg::PatternKey::Hash => Self::Hash,
             wysiwyg::PatternKey::Slash => Self::Slash,
-        }
-    }
-}
-
-impl From<PatternKey> for wysiwyg::PatternKey {
-    fn from(key: PatternKey) -> Self {
-        match key {
-            PatternKey::At => Self::At,
-            PatternKey::Hash => Self::Hash,
-            PatternKey::Slash => Self::Slash,
+            wysiwyg::PatternKey::Colon => Self::Colon,
+            wysiwyg::PatternKey::Custom(_) => Self::Custom,
         }
     }
 }
@@ -717,27 +1739,39 @@ pub struct DomHandle {
 impl DomHandle {
     /// Returns "container", "line_break", "text" or "zwsp" depending on the type of
     /// node we refer to.
-    /// Panics if we are not a valid reference (because the model has changed
+    /// Fails if we are not a valid reference (because the model has changed
     /// since we were created, or because you passed in a different model
     /// from the one that created us.)
-    pub fn node_type(&self, model: &ComposerModel) -> String {
-        let node = model.inner.state.dom.lookup_node(&self.inner);
-        String::from(match node {
+    pub fn node_type(
+        &self,
+        model: &ComposerModel,
+    ) -> Result<String, DomHandleError> {
+        let node = self.lookup_node(model)?;
+        Ok(String::from(match node {
             wysiwyg::DomNode::Container(_) => "container",
             wysiwyg::DomNode::LineBreak(_) => "line_break",
+            wysiwyg::DomNode::HorizontalRule(_) => "horizontal_rule",
             wysiwyg::DomNode::Mention(_) => "mention",
+            wysiwyg::DomNode::Image(node) if node.is_custom_emoji() => {
+                "custom_emoji"
+            }
+            wysiwyg::DomNode::Image(_) => "image",
+            wysiwyg::DomNode::Opaque(_) => "opaque",
             wysiwyg::DomNode::Text(_) => "text",
-        })
+        }))
     }
 
     /// Returns a list of our children nodes, or an empty list if we refer
     /// to a text or line break node.
-    /// Panics if we are not a valid reference (because the model has changed
+    /// Fails if we are not a valid reference (because the model has changed
     /// since we were created, or because you passed in a different model
     /// from the one that created us.)
-    pub fn children(&self, model: &ComposerModel) -> DomChildren {
-        let node = model.inner.state.dom.lookup_node(&self.inner);
-        match node {
+    pub fn children(
+        &self,
+        model: &ComposerModel,
+    ) -> Result<DomChildren, DomHandleError> {
+        let node = self.lookup_node(model)?;
+        Ok(match node {
             wysiwyg::DomNode::Container(node) => node
                 .children()
                 .iter()
@@ -746,36 +1780,59 @@ impl DomHandle {
                 })
                 .collect(),
             _ => DomChildren::new(),
-        }
+        })
     }
 
     /// Returns the text of this node, or an empty string if this is a
     /// container or line break.
-    /// Panics if we are not a valid reference (because the model has changed
+    /// Fails if we are not a valid reference (because the model has changed
     /// since we were created, or because you passed in a different model
     /// from the one that created us.)
-    pub fn text(&self, model: &ComposerModel) -> String {
-        let node = model.inner.state.dom.lookup_node(&self.inner);
-        match node {
+    pub fn text(
+        &self,
+        model: &ComposerModel,
+    ) -> Result<String, DomHandleError> {
+        let node = self.lookup_node(model)?;
+        Ok(match node {
             wysiwyg::DomNode::Container(_) => String::from(""),
             wysiwyg::DomNode::LineBreak(_) => String::from(""),
+            wysiwyg::DomNode::HorizontalRule(_) => String::from(""),
             wysiwyg::DomNode::Mention(node) => node.display_text().to_string(),
+            wysiwyg::DomNode::Image(node) => node.alt().to_string(),
+            wysiwyg::DomNode::Opaque(node) => node.text().to_string(),
             wysiwyg::DomNode::Text(node) => node.data().to_string(),
-        }
+        })
     }
 
     /// Returns our tagname, or "-text-"/"-zwsp-" if we are a text/zwsp node.
-    /// Panics if we are not a valid reference (because the model has changed
+    /// Fails if we are not a valid reference (because the model has changed
     /// since we were created, or because you passed in a different model
     /// from the one that created us.)
-    pub fn tag(&self, model: &ComposerModel) -> String {
-        let node = model.inner.state.dom.lookup_node(&self.inner);
-        match node {
+    pub fn tag(&self, model: &ComposerModel) -> Result<String, DomHandleError> {
+        let node = self.lookup_node(model)?;
+        Ok(match node {
             wysiwyg::DomNode::Container(node) => node.name().to_string(),
             wysiwyg::DomNode::LineBreak(node) => node.name().to_string(),
+            wysiwyg::DomNode::HorizontalRule(node) => node.name().to_string(),
             wysiwyg::DomNode::Mention(node) => node.name().to_string(),
+            wysiwyg::DomNode::Image(node) => node.name().to_string(),
+            wysiwyg::DomNode::Opaque(node) => node.name().to_string(),
             wysiwyg::DomNode::Text(_) => String::from("-text-"),
-        }
+        })
+    }
+}
+
+impl DomHandle {
+    fn lookup_node<'a>(
+        &self,
+        model: &'a ComposerModel,
+    ) -> Result<&'a wysiwyg::DomNode<Utf16String>, DomHandleError> {
+        model
+            .inner
+            .state
+            .dom
+            .try_lookup_node(&self.inner)
+            .ok_or(DomHandleError::InvalidHandle)
     }
 }
 
@@ -841,16 +1898,18 @@ impl From<wysiwyg::LinkAction<Utf16String>> for LinkAction {
 
 #[cfg(test)]
 mod test {
-    use super::ComposerModel;
+    use super::{ComposerModel, CustomKeyError, DomHandleError};
 
     #[test]
     fn can_find_types_of_nodes() {
         let mut model = ComposerModel::new();
         model.replace_text("foo");
 
-        assert_eq!(model.document().node_type(&model), "container");
+        assert_eq!(model.document().node_type(&model).unwrap(), "container");
         assert_eq!(
-            model.document().children(&model).inner[0].node_type(&model),
+            model.document().children(&model).unwrap().inner[0]
+                .node_type(&model)
+                .unwrap(),
             "text"
         );
     }
@@ -864,23 +1923,65 @@ mod test {
         model.select(2, 3);
         model.italic();
 
-        let children = model.document().children(&model).inner;
-        let grandchildren = children[1].children(&model).inner;
-        let great_grandchildren = grandchildren[1].children(&model).inner;
-
-        assert_eq!(children[0].node_type(&model), "text");
-        assert_eq!(children[0].text(&model), "0");
-        assert_eq!(children[1].node_type(&model), "container");
-        assert_eq!(children[1].tag(&model), "strong");
-        assert_eq!(grandchildren[0].node_type(&model), "text");
-        assert_eq!(grandchildren[0].text(&model), "1");
-        assert_eq!(grandchildren[1].node_type(&model), "container");
-        assert_eq!(grandchildren[1].tag(&model), "em");
-        assert_eq!(great_grandchildren[0].node_type(&model), "text");
-        assert_eq!(great_grandchildren[0].text(&model), "2");
-        assert_eq!(grandchildren[2].node_type(&model), "text");
-        assert_eq!(grandchildren[2].text(&model), "3");
-        assert_eq!(children[2].node_type(&model), "text");
-        assert_eq!(children[2].text(&model), "4");
+        let children = model.document().children(&model).unwrap().inner;
+        let grandchildren = children[1].children(&model).unwrap().inner;
+        let great_grandchildren =
+            grandchildren[1].children(&model).unwrap().inner;
+
+        assert_eq!(children[0].node_type(&model).unwrap(), "text");
+        assert_eq!(children[0].text(&model).unwrap(), "0");
+        assert_eq!(children[1].node_type(&model).unwrap(), "container");
+        assert_eq!(children[1].tag(&model).unwrap(), "strong");
+        assert_eq!(grandchildren[0].node_type(&model).unwrap(), "text");
+        assert_eq!(grandchildren[0].text(&model).unwrap(), "1");
+        assert_eq!(grandchildren[1].node_type(&model).unwrap(), "container");
+        assert_eq!(grandchildren[1].tag(&model).unwrap(), "em");
+        assert_eq!(great_grandchildren[0].node_type(&model).unwrap(), "text");
+        assert_eq!(great_grandchildren[0].text(&model).unwrap(), "2");
+        assert_eq!(grandchildren[2].node_type(&model).unwrap(), "text");
+        assert_eq!(grandchildren[2].text(&model).unwrap(), "3");
+        assert_eq!(children[2].node_type(&model).unwrap(), "text");
+        assert_eq!(children[2].text(&model).unwrap(), "4");
+    }
+
+    #[test]
+    fn stale_handles_report_an_error() {
+        let mut model = ComposerModel::new();
+        model.replace_text("foo");
+        model.bold();
+        model.replace_text("bar");
+        let children = model.document().children(&model).unwrap().inner;
+        let handle = &children[1];
+
+        model.select(0, 6);
+        model.replace_text("baz");
+
+        assert!(matches!(
+            handle.node_type(&model),
+            Err(DomHandleError::InvalidHandle)
+        ));
+        assert!(matches!(
+            handle.children(&model),
+            Err(DomHandleError::InvalidHandle)
+        ));
+    }
+
+    #[test]
+    fn suggestion_triggers_need_a_single_character_key() {
+        let mut model = ComposerModel::new();
+        for key in ["", "!!"] {
+            assert_eq!(
+                model.add_suggestion_trigger(key, false, false, 0),
+                Err(CustomKeyError::NotOneCharacter)
+            );
+        }
+        assert_eq!(model.add_suggestion_trigger("!", false, false, 0), Ok(()));
+        let keys: Vec<char> = model
+            .inner
+            .suggestion_triggers()
+            .iter()
+            .map(|t| t.key)
+            .collect();
+        assert_eq!(keys, ['!']);
     }
 }
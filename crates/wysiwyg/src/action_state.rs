@@ -19,4 +19,7 @@ pub enum ActionState {
     Enabled,
     Reversed,
     Disabled,
+    /// Only part of the selection has the property controlled by the action
+    /// e.g. half of the selected text is bold.
+    Mixed,
 }
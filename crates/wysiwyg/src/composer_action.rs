@@ -21,6 +21,8 @@ pub enum ComposerAction {
     StrikeThrough,
     Underline,
     InlineCode,
+    Superscript,
+    Subscript,
     Link,
     Undo,
     Redo,
@@ -30,4 +32,7 @@ pub enum ComposerAction {
     Unindent,
     CodeBlock,
     Quote,
+    ClearFormatting,
+    TextColor,
+    BackgroundColor,
 }
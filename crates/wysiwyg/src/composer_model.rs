@@ -14,18 +14,29 @@
 
 pub mod base;
 pub mod code_block;
+pub mod composition;
+pub mod content_limit;
+pub mod context_state;
+pub mod custom_emoji;
 pub mod delete_text;
 pub mod example_format;
+pub mod find_replace;
 pub mod format;
 mod format_inline_code;
+pub mod horizontal_rule;
 pub mod hyperlinks;
 pub mod lists;
+pub mod markdown_mode;
 pub mod menu_action;
 pub mod menu_state;
+pub mod message_content;
+pub mod navigation;
 pub mod new_lines;
 pub mod quotes;
 pub mod replace_text;
 pub mod selection;
+pub mod slash_commands;
+pub mod tables;
 pub mod undo_redo;
 
 pub use base::ComposerModel;
@@ -18,13 +18,21 @@ use crate::composer_state::ComposerState;
 use crate::dom::parser::markdown::markdown_html_parser::MarkdownHTMLParser;
 use crate::dom::parser::parse;
 use crate::dom::to_plain_text::ToPlainText;
-use crate::dom::{Dom, DomCreationError, UnicodeString};
+use crate::dom::{Dom, DomCreationError, MarkdownError, UnicodeString};
 use crate::{
-    ComposerAction, ComposerUpdate, DomHandle, Location, ToHtml, ToMarkdown,
-    ToTree,
+    ComposerAction, ComposerUpdate, ContentLimit, ContextState, FindOptions,
+    Location, SlashCommand, SuggestionTrigger, ToHtml, ToMarkdown, ToTree,
 };
 use std::collections::HashMap;
 
+/// Emoji are suggested once at least this many characters follow the `:`,
+/// so that typing e.g. `:D` doesn't bring up a menu.
+const DEFAULT_EMOJI_SUGGESTION_MIN_LENGTH: usize = 2;
+
+/// Mention suggestions end at the first whitespace unless more words are
+/// allowed with `set_mention_suggestion_max_words`.
+const DEFAULT_MENTION_SUGGESTION_MAX_WORDS: usize = 1;
+
 #[derive(Clone, Default)]
 pub struct ComposerModel<S>
 where
@@ -41,6 +49,35 @@ where
 
     /// The states of the buttons for each action e.g. bold, undo
     pub(crate) action_states: HashMap<ComposerAction, ActionState>,
+
+    /// Where the selection was the last time the menu state was computed
+    pub(crate) context_state: ContextState<S>,
+
+    /// The query of the last search, used by replace_match()
+    pub(crate) search_query: Option<S>,
+
+    /// The options of the last search, used by replace_match()
+    pub(crate) search_options: FindOptions,
+
+    /// The state before the current composition started, restored if the
+    /// composition is cancelled
+    pub(crate) state_before_composition: Option<ComposerState<S>>,
+
+    /// The maximum size of the content when typing or pasting, if any
+    pub(crate) content_limit: Option<ContentLimit>,
+
+    /// How many characters must follow a `:` before emoji are suggested
+    pub(crate) emoji_suggestion_min_length: usize,
+
+    /// Extra characters that start a suggestion, on top of the built in ones
+    pub(crate) suggestion_triggers: Vec<SuggestionTrigger>,
+
+    /// How many words a `@` or `#` suggestion may span, e.g. 2 to search for
+    /// `@John Sm`
+    pub(crate) mention_suggestion_max_words: usize,
+
+    /// The commands that can be typed at the start of the content
+    pub(crate) slash_commands: Vec<SlashCommand>,
 }
 
 impl<S> ComposerModel<S>
@@ -53,6 +90,15 @@ where
             previous_states: Vec::new(),
             next_states: Vec::new(),
             action_states: HashMap::new(), // TODO: Calculate state based on ComposerState
+            context_state: ContextState::default(),
+            search_query: None,
+            search_options: FindOptions::default(),
+            state_before_composition: None,
+            content_limit: None,
+            emoji_suggestion_min_length: DEFAULT_EMOJI_SUGGESTION_MIN_LENGTH,
+            suggestion_triggers: Vec::new(),
+            mention_suggestion_max_words: DEFAULT_MENTION_SUGGESTION_MAX_WORDS,
+            slash_commands: Vec::new(),
         };
         instance.compute_menu_state(MenuStateComputeType::AlwaysUpdate);
         instance
@@ -64,30 +110,61 @@ where
             previous_states: Vec::new(),
             next_states: Vec::new(),
             action_states: HashMap::new(), // TODO: Calculate state based on ComposerState
+            context_state: ContextState::default(),
+            search_query: None,
+            search_options: FindOptions::default(),
+            state_before_composition: None,
+            content_limit: None,
+            emoji_suggestion_min_length: DEFAULT_EMOJI_SUGGESTION_MIN_LENGTH,
+            suggestion_triggers: Vec::new(),
+            mention_suggestion_max_words: DEFAULT_MENTION_SUGGESTION_MAX_WORDS,
+            slash_commands: Vec::new(),
         }
     }
 
     /// Create a UTF-16 model from an HTML string, or panic if HTML parsing
-    /// fails.
+    /// fails. See [Self::try_from_html] for untrusted input.
     pub fn from_html(
         html: &str,
         start_codeunit: usize,
         end_codeunit: usize,
     ) -> Self {
+        Self::try_from_html(html, start_codeunit, end_codeunit)
+            .expect("HTML parsing failed")
+    }
+
+    /// Create a UTF-16 model from an HTML string, or return an error if
+    /// HTML parsing fails.
+    pub fn try_from_html(
+        html: &str,
+        start_codeunit: usize,
+        end_codeunit: usize,
+    ) -> Result<Self, DomCreationError> {
         let mut model = Self {
             state: ComposerState {
-                dom: parse(html).expect("HTML parsing failed"),
+                dom: parse(html).map_err(DomCreationError::HtmlParseError)?,
                 start: Location::from(start_codeunit),
                 end: Location::from(end_codeunit),
                 toggled_format_types: Vec::new(),
+                composition: None,
+                markdown_mode: false,
             },
             previous_states: Vec::new(),
             next_states: Vec::new(),
             action_states: HashMap::new(), // TODO: Calculate state based on ComposerState
+            context_state: ContextState::default(),
+            search_query: None,
+            search_options: FindOptions::default(),
+            state_before_composition: None,
+            content_limit: None,
+            emoji_suggestion_min_length: DEFAULT_EMOJI_SUGGESTION_MIN_LENGTH,
+            suggestion_triggers: Vec::new(),
+            mention_suggestion_max_words: DEFAULT_MENTION_SUGGESTION_MAX_WORDS,
+            slash_commands: Vec::new(),
         };
         model.compute_menu_state(MenuStateComputeType::AlwaysUpdate);
         Self::post_process_dom(&mut model.state.dom);
-        model
+        Ok(model)
     }
 
     /// Replace the entire content of the model with given HTML string.
@@ -101,6 +178,8 @@ where
             .map_err(DomCreationError::HtmlParseError)?;
 
         self.state.dom = dom;
+        self.state.composition = None;
+        self.state_before_composition = None;
         self.previous_states.clear();
         self.next_states.clear();
         Self::post_process_dom(&mut self.state.dom);
@@ -109,11 +188,25 @@ where
         Ok(self.create_update_replace_all_with_menu_state())
     }
 
-    fn post_process_dom(dom: &mut Dom<S>) {
-        dom.wrap_inline_nodes_into_paragraphs_if_needed(&DomHandle::root());
+    pub(crate) fn post_process_dom(dom: &mut Dom<S>) {
+        dom.normalize();
         dom.explicitly_assert_invariants();
     }
 
+    /// Repair the DOM if the last operation left it inconsistent, keeping
+    /// the selection inside it.
+    fn normalize_dom_if_invalid(&mut self) {
+        if self.state.dom.validate().is_empty() {
+            return;
+        }
+        let dom = &mut self.state.dom;
+        let start = dom.text_anchor(self.state.start.into());
+        let end = dom.text_anchor(self.state.end.into());
+        dom.normalize();
+        self.state.start = Location::from(dom.location_of_text_anchor(start));
+        self.state.end = Location::from(dom.location_of_text_anchor(end));
+    }
+
     pub fn set_content_from_markdown(
         &mut self,
         markdown: &S,
@@ -137,6 +230,11 @@ where
         self.action_states.get(&action) == Some(&ActionState::Reversed)
     }
 
+    #[cfg(test)]
+    pub(crate) fn action_is_mixed(&self, action: ComposerAction) -> bool {
+        self.action_states.get(&action) == Some(&ActionState::Mixed)
+    }
+
     #[cfg(test)]
     pub(crate) fn action_is_disabled(&self, action: ComposerAction) -> bool {
         self.action_states.get(&action) == Some(&ActionState::Disabled)
@@ -160,6 +258,15 @@ where
     }
 
     pub(crate) fn create_update_replace_all(&mut self) -> ComposerUpdate<S> {
+        self.normalize_dom_if_invalid();
+        self.create_update_replace_all_unchanged()
+    }
+
+    /// Like [Self::create_update_replace_all], but sends the DOM exactly as
+    /// it is, e.g. when undo or redo restores a saved state.
+    pub(crate) fn create_update_replace_all_unchanged(
+        &mut self,
+    ) -> ComposerUpdate<S> {
         #[cfg(any(test, feature = "assert-invariants"))]
         self.state.dom.assert_transaction_not_in_progress();
 
@@ -177,6 +284,7 @@ where
     ) -> ComposerUpdate<S> {
         #[cfg(any(test, feature = "assert-invariants"))]
         self.state.dom.assert_transaction_not_in_progress();
+        self.normalize_dom_if_invalid();
 
         ComposerUpdate::replace_all(
             self.state.dom.to_html(),
@@ -199,8 +307,14 @@ where
         self.state.dom.to_message_html()
     }
 
+    /// Panics if the content can't be written as Markdown. See
+    /// [Self::try_get_content_as_markdown].
     pub fn get_content_as_markdown(&self) -> S {
-        self.state.dom.to_markdown().unwrap()
+        self.try_get_content_as_markdown().unwrap()
+    }
+
+    pub fn try_get_content_as_markdown(&self) -> Result<S, MarkdownError<S>> {
+        self.state.dom.to_markdown()
     }
 
     pub fn get_content_as_plain_text(&self) -> S {
@@ -241,6 +355,20 @@ mod test {
         assert_eq!(model.state.dom.to_string(), "foo <b>bar</b>")
     }
 
+    #[test]
+    fn try_from_html_creates_a_model() {
+        let model =
+            ComposerModel::<Utf16String>::try_from_html("a<b>b</b>", 1, 2)
+                .unwrap();
+        assert_eq!(tx(&model), "a<b>{b}|</b>");
+    }
+
+    #[test]
+    fn try_from_html_reports_invalid_html() {
+        let result = ComposerModel::<Utf16String>::try_from_html("</b>", 0, 0);
+        assert!(matches!(result, Err(DomCreationError::HtmlParseError(_))));
+    }
+
     #[test]
     fn action_states_are_reported() {
         let mut model = ComposerModel::new();
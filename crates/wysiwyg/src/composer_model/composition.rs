@@ -0,0 +1,205 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::composer_model::menu_state::MenuStateComputeType;
+use crate::dom::nodes::dom_node::DomNodeKind;
+use crate::dom::nodes::{ContainerNode, ContainerNodeKind, DomNode};
+use crate::dom::unicode_string::UnicodeStrExt;
+use crate::{
+    ComposerModel, ComposerUpdate, ContentLimit, DomHandle, LimitResult,
+    Location, MenuAction, ToHtml, UnicodeString,
+};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Replace the text being composed by the keyboard (e.g. an IME) with
+    /// [text]. If no composition is in progress, one starts at the current
+    /// selection.
+    ///
+    /// Composing text doesn't create undo steps or suggestions until it is
+    /// committed with [Self::commit_composition]. The HTML of the returned
+    /// update wraps the composing text in a `<span data-composing="true">`.
+    ///
+    /// If a content limit is set, the text may be truncated or rejected,
+    /// as reported in the limit_result of the returned update.
+    pub fn set_composing_text(&mut self, text: S) -> ComposerUpdate<S> {
+        let Some(limit) = self.content_limit else {
+            return self.do_set_composing_text(text);
+        };
+        let (start, end) = self.composing_range();
+        let edit = |model: &mut Self, text: S| {
+            model.do_set_composing_text(text);
+        };
+        match self.text_within_limit(text, start, end, limit, edit) {
+            Some((text, limit_result)) => self
+                .do_set_composing_text(text)
+                .with_limit_result(limit_result),
+            None => {
+                ComposerUpdate::keep().with_limit_result(LimitResult::Rejected)
+            }
+        }
+    }
+
+    fn do_set_composing_text(&mut self, text: S) -> ComposerUpdate<S> {
+        if self.state.composition.is_none() {
+            self.state_before_composition = Some(self.state.clone());
+        }
+        let (start, end) = self.composing_range();
+
+        let len = text.len();
+        self.state.dom.replace_text_in(text, start, end);
+        self.apply_pending_formats(start, start + len);
+        self.state.start = Location::from(start + len);
+        self.state.end = self.state.start;
+        self.state.composition =
+            Some((Location::from(start), Location::from(start + len)));
+
+        ComposerUpdate::replace_all(
+            self.html_with_composition_marker(),
+            self.state.start,
+            self.state.end,
+            self.compute_menu_state(MenuStateComputeType::KeepIfUnchanged),
+            MenuAction::Keep,
+        )
+    }
+
+    /// Accept the composing text as it is, making the whole composition a
+    /// single undo step.
+    ///
+    /// If the content limit was set or lowered during the composition, the
+    /// composing text is truncated or removed to respect it.
+    pub fn commit_composition(&mut self) -> ComposerUpdate<S> {
+        if self.state.composition.is_none() {
+            return ComposerUpdate::keep();
+        }
+        let limit_result = match self.content_limit {
+            Some(limit) => self.limit_composition(limit),
+            None => LimitResult::Applied,
+        };
+        self.commit_pending_composition();
+        self.create_update_replace_all()
+            .with_limit_result(limit_result)
+    }
+
+    /// Compose the current composing text again from the start of the
+    /// composition, if the content has grown past [limit] since then.
+    fn limit_composition(&mut self, limit: ContentLimit) -> LimitResult {
+        let Some(before) = &self.state_before_composition else {
+            return LimitResult::Applied;
+        };
+        let size = self.content_metrics().get(limit.unit);
+        let size_before = Self::from_state(before.clone())
+            .content_metrics()
+            .get(limit.unit);
+        if size <= limit.max || size <= size_before {
+            return LimitResult::Applied;
+        }
+        let Some((handle, s, e)) = self.composing_text_location() else {
+            return LimitResult::Applied;
+        };
+        let DomNode::Text(text_node) = self.state.dom.lookup_node(&handle)
+        else {
+            return LimitResult::Applied;
+        };
+        let text = text_node.data()[s..e].to_owned();
+
+        self.state = self.state_before_composition.take().unwrap();
+        self.set_composing_text(text).limit_result
+    }
+
+    /// Remove the composing text, restoring the content and selection from
+    /// before the composition started.
+    pub fn cancel_composition(&mut self) -> ComposerUpdate<S> {
+        let Some(before) = self.state_before_composition.take() else {
+            return ComposerUpdate::keep();
+        };
+        self.state = before;
+        self.create_update_replace_all()
+    }
+
+    /// Commit any composition in progress. Called before anything that
+    /// would make the composition range meaningless, like another edit.
+    pub(crate) fn commit_pending_composition(&mut self) {
+        let Some(before) = self.state_before_composition.take() else {
+            return;
+        };
+        self.state.composition = None;
+        if before.dom != self.state.dom {
+            self.next_states.clear();
+            self.previous_states.push(before);
+        }
+    }
+
+    /// The range that composing text replaces: the text already being
+    /// composed, or the selection if no composition is in progress.
+    fn composing_range(&self) -> (usize, usize) {
+        match self.state.composition {
+            Some((s, e)) => self.safe_locations_from(s, e),
+            None => self.safe_selection(),
+        }
+    }
+
+    /// The text node holding the composing text, and the offsets of the
+    /// composing text inside it.
+    fn composing_text_location(&self) -> Option<(DomHandle, usize, usize)> {
+        let (start, end) = self.state.composition?;
+        let (start, end) = self.safe_locations_from(start, end);
+        let range = self.state.dom.find_range(start, end);
+        // Composed text is always inserted in a single text node
+        let loc = range.leaves().find(|loc| {
+            loc.kind == DomNodeKind::Text
+                && loc.position <= start
+                && end <= loc.position + loc.length
+        })?;
+        Some((
+            loc.node_handle.clone(),
+            start - loc.position,
+            end - loc.position,
+        ))
+    }
+
+    fn html_with_composition_marker(&self) -> S {
+        let Some((handle, s, e)) = self.composing_text_location() else {
+            return self.state.dom.to_html();
+        };
+        let DomNode::Text(text_node) = self.state.dom.lookup_node(&handle)
+        else {
+            return self.state.dom.to_html();
+        };
+
+        let data = text_node.data();
+        if s == e {
+            return self.state.dom.to_html();
+        }
+        let mut nodes = Vec::new();
+        if s > 0 {
+            nodes.push(DomNode::new_text(data[..s].to_owned()));
+        }
+        nodes.push(DomNode::Container(ContainerNode::new(
+            "span".into(),
+            ContainerNodeKind::Generic,
+            Some(vec![("data-composing".into(), "true".into())]),
+            vec![DomNode::new_text(data[s..e].to_owned())],
+        )));
+        if e < data.len() {
+            nodes.push(DomNode::new_text(data[e..].to_owned()));
+        }
+
+        let mut dom = self.state.dom.clone();
+        dom.replace(&handle, nodes);
+        dom.to_html()
+    }
+}
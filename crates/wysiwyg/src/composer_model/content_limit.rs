@@ -0,0 +1,219 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::dom::nodes::DomNode;
+use crate::dom::to_plain_text::ToPlainText;
+use crate::dom::unicode_string::{UnicodeStrExt, UnicodeStringExt};
+use crate::{
+    ComposerModel, ComposerUpdate, ContentLimit, ContentMetrics,
+    LimitBehaviour, LimitResult, LimitUnit, ToHtml, UnicodeString,
+};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Measure the content as it would be sent in a message.
+    pub fn content_metrics(&self) -> ContentMetrics {
+        let plain_text = self.state.dom.to_plain_text();
+        let plain_text_utf8 = plain_text.to_string();
+        ContentMetrics {
+            graphemes: plain_text_utf8.graphemes(true).count(),
+            code_units: plain_text.len(),
+            message_html_bytes: self
+                .state
+                .dom
+                .to_message_html()
+                .to_string()
+                .len(),
+            plain_text_bytes: plain_text_utf8.len(),
+        }
+    }
+
+    /// Set or remove the limit checked by [Self::replace_text],
+    /// [Self::replace_text_in] and [Self::set_composing_text]. Content that
+    /// is already over the limit is not changed, and can still be shortened.
+    pub fn set_content_limit(&mut self, limit: Option<ContentLimit>) {
+        self.content_limit = limit;
+    }
+
+    pub fn content_limit(&self) -> Option<ContentLimit> {
+        self.content_limit
+    }
+
+    /// Replace the text between [start] and [end], truncating or rejecting
+    /// [new_text] if it would make the content exceed [limit].
+    pub(crate) fn replace_text_in_within_limit(
+        &mut self,
+        new_text: S,
+        start: usize,
+        end: usize,
+        limit: ContentLimit,
+    ) -> ComposerUpdate<S> {
+        let edit = |model: &mut Self, text: S| {
+            model.do_replace_text_in(text, start, end);
+        };
+        match self.text_within_limit(new_text, start, end, limit, edit) {
+            Some((text, limit_result)) => {
+                self.push_state_to_history();
+                self.do_replace_text_in(text, start, end)
+                    .with_limit_result(limit_result)
+            }
+            None => {
+                ComposerUpdate::keep().with_limit_result(LimitResult::Rejected)
+            }
+        }
+    }
+
+    /// The longest run of whole graphemes from the start of [new_text] that
+    /// [edit] can insert in place of the text between [start] and [end]
+    /// without making the content exceed [limit], and whether it had to be
+    /// truncated. None if none of it fits.
+    ///
+    /// Where possible the new text is measured against what is left of the
+    /// limit. Otherwise, e.g. for limits on the message HTML, each attempt
+    /// is made on a copy of the model, so this model's content, history and
+    /// menu state are left as they are.
+    pub(crate) fn text_within_limit(
+        &self,
+        new_text: S,
+        start: usize,
+        end: usize,
+        limit: ContentLimit,
+        edit: impl Fn(&mut Self, S),
+    ) -> Option<(S, LimitResult)> {
+        let size_before = self.content_metrics().get(limit.unit);
+        let size_without_range = self.plain_text_size_without(
+            size_before,
+            &new_text,
+            start,
+            end,
+            limit.unit,
+        );
+        let fits = |text: S| {
+            let size = match size_without_range {
+                Some(size_without_range) => {
+                    size_without_range + plain_text_size(&text, limit.unit)
+                }
+                None => {
+                    let mut model = self.copy_without_history();
+                    edit(&mut model, text);
+                    model.content_metrics().get(limit.unit)
+                }
+            };
+            size <= limit.max || size <= size_before
+        };
+
+        if fits(new_text.clone()) {
+            return Some((new_text, LimitResult::Applied));
+        }
+        if limit.behaviour == LimitBehaviour::Reject {
+            return None;
+        }
+
+        // Find the longest run of whole graphemes that fits
+        let new_text = new_text.to_string();
+        let graphemes: Vec<&str> = new_text.graphemes(true).collect();
+        let mut fitting = 0;
+        let (mut low, mut high) = (1, graphemes.len().saturating_sub(1));
+        while low <= high {
+            let mid = (low + high) / 2;
+            if fits(S::from(graphemes[..mid].concat())) {
+                fitting = mid;
+                low = mid + 1;
+            } else {
+                high = mid - 1;
+            }
+        }
+        (fitting > 0).then(|| {
+            (
+                S::from(graphemes[..fitting].concat()),
+                LimitResult::Truncated,
+            )
+        })
+    }
+
+    /// [size], the size of the content in [unit], without the text between
+    /// [start] and [end], so that the size after replacing that text is
+    /// this plus the size of the new text.
+    ///
+    /// None if [unit] isn't measured on the plain text, or if replacing the
+    /// range with [new_text] could change more than the text itself, e.g.
+    /// by adding paragraphs or joining blocks.
+    fn plain_text_size_without(
+        &self,
+        size: usize,
+        new_text: &S,
+        start: usize,
+        end: usize,
+        unit: LimitUnit,
+    ) -> Option<usize> {
+        if unit == LimitUnit::MessageHtmlBytes
+            || new_text.to_string().contains('\n')
+        {
+            return None;
+        }
+        let range = self.state.dom.find_range(start, end);
+        let mut replaced = S::default();
+        for location in range.leaves() {
+            let DomNode::Text(text) =
+                self.state.dom.lookup_node(&location.node_handle)
+            else {
+                return None;
+            };
+            replaced.push(
+                text.data()[location.start_offset..location.end_offset]
+                    .to_owned(),
+            );
+        }
+        if replaced.len() != end - start {
+            return None;
+        }
+        Some(size.saturating_sub(plain_text_size(&replaced, unit)))
+    }
+
+    /// A copy of this model to try edits on. The undo history is left out
+    /// as the copy is thrown away.
+    fn copy_without_history(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            previous_states: Vec::new(),
+            next_states: Vec::new(),
+            action_states: self.action_states.clone(),
+            context_state: self.context_state.clone(),
+            search_query: self.search_query.clone(),
+            search_options: self.search_options,
+            state_before_composition: self.state_before_composition.clone(),
+            content_limit: None,
+            emoji_suggestion_min_length: self.emoji_suggestion_min_length,
+            suggestion_triggers: self.suggestion_triggers.clone(),
+            mention_suggestion_max_words: self.mention_suggestion_max_words,
+            slash_commands: self.slash_commands.clone(),
+        }
+    }
+}
+
+/// The size of some plain text in [unit], taking its UTF-8 bytes for
+/// either kind of byte limit.
+fn plain_text_size<S: UnicodeString>(text: &S, unit: LimitUnit) -> usize {
+    match unit {
+        LimitUnit::Graphemes => text.to_string().graphemes(true).count(),
+        LimitUnit::CodeUnits => text.len(),
+        LimitUnit::MessageHtmlBytes | LimitUnit::PlainTextBytes => {
+            text.to_string().len()
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::dom_node::DomNodeKind;
+use crate::dom::nodes::{ContainerNodeKind, DomNode};
+use crate::dom::range::DomLocationPosition::{After, Inside};
+use crate::dom::Range;
+use crate::{
+    BlockType, ComposerModel, ContextState, InlineFormatType, UnicodeString,
+};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Describe where the current selection is in the document: the block
+    /// it starts in, how deeply nested in lists it is, and the link,
+    /// mention and colours under it.
+    pub fn get_context_state(&self) -> ContextState<S> {
+        let (s, e) = self.safe_selection();
+        let range = self.state.dom.find_range(s, e);
+        self.compute_context_state(&range)
+    }
+
+    pub(crate) fn compute_context_state(
+        &self,
+        range: &Range,
+    ) -> ContextState<S> {
+        // Like for reversed actions, a leaf ending at the cursor counts as
+        // being under it, but a leaf starting at the cursor does not.
+        let location = range
+            .leaves()
+            .find(|loc| loc.relative_position() != After)
+            .or_else(|| range.leaves().next())
+            .or_else(|| range.deepest_container_node(None));
+        let Some(location) = location else {
+            return ContextState::default();
+        };
+
+        let mut context = ContextState::default();
+        let mut block_type = None;
+        for handle in location.node_handle.with_ancestors().iter().rev() {
+            let container = match self.state.dom.lookup_node(handle) {
+                DomNode::Container(container) => container,
+                DomNode::Opaque(opaque) if opaque.is_block() => {
+                    block_type.get_or_insert(opaque_block_type(
+                        &opaque.name().to_string(),
+                    ));
+                    continue;
+                }
+                _ => continue,
+            };
+            match container.kind() {
+                ContainerNodeKind::List(_) => context.list_depth += 1,
+                ContainerNodeKind::ListItem => {
+                    block_type.get_or_insert(BlockType::ListItem);
+                }
+                ContainerNodeKind::Quote => {
+                    block_type.get_or_insert(BlockType::Quote);
+                }
+                ContainerNodeKind::CodeBlock => {
+                    block_type.get_or_insert(BlockType::CodeBlock);
+                }
+                ContainerNodeKind::TableCell => {
+                    block_type.get_or_insert(BlockType::TableCell);
+                }
+                ContainerNodeKind::Formatting(InlineFormatType::TextColor(
+                    color,
+                )) => {
+                    context.text_color.get_or_insert(color.as_str().into());
+                }
+                ContainerNodeKind::Formatting(
+                    InlineFormatType::BackgroundColor(color),
+                ) => {
+                    context
+                        .background_color
+                        .get_or_insert(color.as_str().into());
+                }
+                ContainerNodeKind::Link(url) => {
+                    context.link_url.get_or_insert(url.clone());
+                    if container.is_immutable() {
+                        context.in_mention = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        context.block_type = block_type.unwrap_or_default();
+        // Colours set at the cursor apply to the text typed next
+        for format in &self.state.toggled_format_types {
+            let (pending, color) = match format {
+                InlineFormatType::TextColor(color) => {
+                    (&mut context.text_color, color)
+                }
+                InlineFormatType::BackgroundColor(color) => {
+                    (&mut context.background_color, color)
+                }
+                _ => continue,
+            };
+            *pending = (!color.is_empty()).then(|| color.as_str().into());
+        }
+        context.in_mention |= range.leaves().any(|loc| {
+            loc.kind == DomNodeKind::Mention
+                && loc.relative_position() == Inside
+        });
+        context
+    }
+}
+
+/// The block type of an opaque block element called [name].
+fn opaque_block_type(name: &str) -> BlockType {
+    match name.strip_prefix('h').and_then(|level| level.parse().ok()) {
+        Some(level @ 1..=6) => BlockType::Heading { level },
+        _ => BlockType::Other,
+    }
+}
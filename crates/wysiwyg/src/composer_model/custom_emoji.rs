@@ -0,0 +1,99 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::image_node::is_mxc_url;
+use crate::dom::nodes::DomNode;
+use crate::{
+    ComposerAction, ComposerModel, ComposerUpdate, Location, SuggestionPattern,
+    UnicodeString,
+};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Replace the selection with the custom emoji at [mxc], known by
+    /// [shortcode] (with or without the surrounding colons). Nothing
+    /// happens if [mxc] is not an mxc URL, or inside code, which can only
+    /// hold text.
+    pub fn insert_custom_emoji(
+        &mut self,
+        mxc: S,
+        shortcode: S,
+    ) -> ComposerUpdate<S> {
+        if !is_mxc_url(&mxc.to_string())
+            || self.action_is_reversed(ComposerAction::InlineCode)
+            || self.action_is_reversed(ComposerAction::CodeBlock)
+        {
+            return ComposerUpdate::keep();
+        }
+
+        self.push_state_to_history();
+        let (s, e) = self.safe_selection();
+        if s != e {
+            self.do_replace_text_in(S::default(), s, e);
+        }
+        if let Some(position) = self.insert_paragraph_at_rule(s) {
+            self.state.start = Location::from(position);
+            self.state.end = self.state.start;
+        }
+        self.do_insert_leaf(DomNode::new_custom_emoji(mxc, shortcode))
+    }
+
+    /// Replace the text of an emoji [suggestion] (e.g. `:par`) with the
+    /// custom emoji at [mxc], known by [shortcode], followed by a space.
+    pub fn insert_custom_emoji_suggestion(
+        &mut self,
+        mxc: S,
+        shortcode: S,
+        suggestion: SuggestionPattern,
+    ) -> ComposerUpdate<S> {
+        if !is_mxc_url(&mxc.to_string()) {
+            return ComposerUpdate::keep();
+        }
+
+        self.push_state_to_history();
+        self.do_replace_text_in(S::default(), suggestion.start, suggestion.end);
+        self.state.start = Location::from(suggestion.start);
+        self.state.end = self.state.start;
+        self.do_insert_leaf(DomNode::new_custom_emoji(mxc, shortcode));
+        self.do_replace_text(" ".into())
+    }
+
+    /// Insert [leaf] at the cursor, splitting the text it is in if needed,
+    /// and move the cursor after it.
+    fn do_insert_leaf(&mut self, leaf: DomNode<S>) -> ComposerUpdate<S> {
+        let (s, _) = self.safe_selection();
+        let range = self.state.dom.find_range(s, s);
+        let leaf_at_cursor = range
+            .leaves()
+            .find(|loc| loc.position <= s && s <= loc.position + loc.length);
+
+        if let Some(loc) = leaf_at_cursor {
+            let handle = loc.node_handle.clone();
+            self.state
+                .dom
+                .insert_into_text(&handle, loc.start_offset, leaf);
+        } else if let Some(block) = range.deepest_block_node(None) {
+            // The cursor is in an empty block
+            let handle = block.node_handle.clone();
+            self.state.dom.append(&handle, leaf);
+        } else {
+            self.state.dom.append_at_end_of_document(leaf);
+        }
+        self.state.start = Location::from(s + 1);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+}
@@ -19,7 +19,7 @@ use crate::dom::unicode_string::UnicodeStrExt;
 use crate::dom::{DomHandle, DomLocation, Range};
 use crate::{ComposerModel, ComposerUpdate, Location, UnicodeString};
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Direction {
     Forwards,
     Backwards,
@@ -45,6 +45,15 @@ where
     S: UnicodeString,
 {
     pub fn backspace(&mut self) -> ComposerUpdate<S> {
+        let (s, e) = self.safe_selection();
+        if s == e && s > 0 && self.range_crosses_table_cells(s - 1, s) {
+            return ComposerUpdate::keep();
+        }
+        if s == e {
+            if let Some(update) = self.backspace_at_rule(s) {
+                return update;
+            }
+        }
         self.push_state_to_history();
         self.handle_non_editable_selection(&Direction::Backwards);
 
@@ -145,6 +154,12 @@ where
 
     /// Deletes the character after the current cursor position.
     pub fn delete(&mut self) -> ComposerUpdate<S> {
+        let (s, e) = self.safe_selection();
+        if s == e {
+            if let Some(update) = self.delete_at_rule(s) {
+                return update;
+            }
+        }
         self.push_state_to_history();
         self.do_delete()
     }
@@ -154,6 +169,9 @@ where
 
         if self.state.start == self.state.end {
             let (s, _) = self.safe_selection();
+            if self.range_crosses_table_cells(s, s + 1) {
+                return ComposerUpdate::keep();
+            }
             // If we're dealing with complex graphemes, this value might not be 1
             let next_char_len =
                 if let Some((text_node, loc)) = self.get_selected_text_node() {
@@ -193,6 +211,16 @@ where
             return self.delete_selection();
         }
 
+        // a horizontal rule is removed on its own, like a single character
+        let (s, _) = self.safe_selection();
+        let rule_update = match direction {
+            Direction::Forwards => self.delete_at_rule(s),
+            Direction::Backwards => self.backspace_at_rule(s),
+        };
+        if let Some(update) = rule_update {
+            return update;
+        }
+
         let args = self.get_remove_word_arguments(&direction);
         match args {
             None => ComposerUpdate::keep(),
@@ -236,7 +264,10 @@ where
                     _ => ComposerUpdate::keep(),
                 }
             }
-            DomNode::Mention(_) => self
+            DomNode::HorizontalRule(_)
+            | DomNode::Mention(_)
+            | DomNode::Image(_)
+            | DomNode::Opaque(_) => self
                 .delete_to_cursor(direction.increment(location.index_in_dom())),
             DomNode::Text(node) => {
                 // we are guaranteed to get valid chars here, so can use unwrap
@@ -359,7 +390,10 @@ where
                 // we have to treat linebreaks as chars, this type fits best
                 Some(CharType::Whitespace)
             }
-            DomNode::Mention(_) => Some(CharType::Other),
+            DomNode::HorizontalRule(_)
+            | DomNode::Mention(_)
+            | DomNode::Image(_)
+            | DomNode::Opaque(_) => Some(CharType::Other),
             DomNode::Text(text_node) => {
                 text_node.char_type_at_offset(location.start_offset, direction)
             }
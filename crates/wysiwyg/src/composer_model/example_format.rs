@@ -19,7 +19,10 @@ use widestring::{Utf16Str, Utf16String};
 
 use crate::char::CharExt;
 use crate::composer_model::menu_state::MenuStateComputeType;
-use crate::dom::nodes::{ContainerNode, LineBreakNode, MentionNode, TextNode};
+use crate::dom::nodes::{
+    ContainerNode, HorizontalRuleNode, ImageNode, LineBreakNode, MentionNode,
+    OpaqueNode, TextNode,
+};
 use crate::dom::parser::parse;
 use crate::dom::to_html::ToHtmlState;
 use crate::dom::unicode_string::{UnicodeStr, UnicodeStrExt};
@@ -346,6 +349,26 @@ impl SelectionWriter {
         }
     }
 
+    /// Write special selection (`{` and `}`) and cursor (`|`) characters
+    /// before or after a horizontal rule node
+    ///
+    /// * `buf` - the output buffer up to and including the given node
+    /// * `start_pos` - the buffer position immediately before the node
+    pub fn write_selection_horizontal_rule_node<S: UnicodeString>(
+        &mut self,
+        buf: &mut S,
+        start_pos: usize,
+        node: &HorizontalRuleNode<S>,
+    ) {
+        if let Some(loc) = self.locations.get(&node.handle()) {
+            let strings_to_add = self.state.advance(loc, 1);
+            for (string, i) in strings_to_add.into_iter().rev() {
+                let length = if i == 0 { 0 } else { "<hr />".len() };
+                buf.insert(start_pos + length, &S::from(string));
+            }
+        }
+    }
+
     /// Write special selection (`{` and `}`) and cursor (`|`) characters
     /// after a mention node
     ///
@@ -366,6 +389,46 @@ impl SelectionWriter {
         }
     }
 
+    /// Write special selection (`{` and `}`) and cursor (`|`) characters
+    /// after an image node
+    ///
+    /// * `buf` - the output buffer up to and including the given node
+    /// * `pos` - the buffer position immediately after the node
+    pub fn write_selection_image_node<S: UnicodeString>(
+        &mut self,
+        buf: &mut S,
+        pos: usize,
+        node: &ImageNode<S>,
+    ) {
+        if let Some(loc) = self.locations.get(&node.handle()) {
+            let strings_to_add = self.state.advance(loc, 1);
+            for (str, i) in strings_to_add.into_iter().rev() {
+                let i = if i == 0 { pos } else { buf.len() };
+                buf.insert(i, &S::from(str));
+            }
+        }
+    }
+
+    /// Write special selection (`{` and `}`) and cursor (`|`) characters
+    /// after an opaque node
+    ///
+    /// * `buf` - the output buffer up to and including the given node
+    /// * `pos` - the buffer position immediately after the node
+    pub fn write_selection_opaque_node<S: UnicodeString>(
+        &mut self,
+        buf: &mut S,
+        pos: usize,
+        node: &OpaqueNode<S>,
+    ) {
+        if let Some(loc) = self.locations.get(&node.handle()) {
+            let strings_to_add = self.state.advance(loc, 1);
+            for (str, i) in strings_to_add.into_iter().rev() {
+                let i = if i == 0 { pos } else { buf.len() };
+                buf.insert(i, &S::from(str));
+            }
+        }
+    }
+
     /// Write special selection (`{` and `}`) and cursor (`|`) characters
     /// after an empty container node
     ///
@@ -734,6 +797,8 @@ mod test {
                 start: Location::from(4),
                 end: Location::from(7),
                 toggled_format_types: Vec::new(),
+                composition: None,
+                markdown_mode: false,
             });
         assert_eq!(tx(&model), "AAA<b>B{BB</b>C}|CC");
     }
@@ -746,6 +811,8 @@ mod test {
                 start: Location::from(7),
                 end: Location::from(4),
                 toggled_format_types: Vec::new(),
+                composition: None,
+                markdown_mode: false,
             });
         assert_eq!(tx(&model), "AAA<b>B|{BB</b>C}CC");
     }
@@ -758,6 +825,8 @@ mod test {
                 start: Location::from(1),
                 end: Location::from(1),
                 toggled_format_types: Vec::new(),
+                composition: None,
+                markdown_mode: false,
             });
         assert_eq!(tx(&model), "|");
     }
@@ -0,0 +1,266 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::text_node::{get_char_type, CharType};
+use crate::dom::nodes::{ContainerNode, DomNode};
+use crate::dom::unicode_string::{UnicodeStr, UnicodeStrExt};
+use crate::{
+    ComposerModel, ComposerUpdate, FindMatch, FindOptions, Location,
+    UnicodeString,
+};
+
+/// A character of the document as seen by a search. Mentions, opaque
+/// nodes, line breaks and gaps between blocks have no character, so they
+/// can't be matched.
+struct SearchChar {
+    c: Option<char>,
+    start: usize,
+    len: usize,
+}
+
+impl SearchChar {
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    fn is_word_char(&self) -> bool {
+        self.c
+            .map_or(false, |c| get_char_type(c) == CharType::Other)
+    }
+}
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Find all the non-overlapping occurrences of [query] in the content,
+    /// including those spanning several text nodes e.g. partly formatted
+    /// text. Mentions are never matched.
+    ///
+    /// The query and options are remembered, to be used by
+    /// [Self::replace_match].
+    pub fn find(&mut self, query: S, options: FindOptions) -> Vec<FindMatch> {
+        let matches = self.find_matches(&query, &options);
+        self.search_options = options;
+        self.search_query = Some(query);
+        matches
+    }
+
+    /// Replace every occurrence of [query], matched using [options]. Each
+    /// replacement takes the formatting of the first character it replaces.
+    /// The whole operation is a single undo step.
+    pub fn replace_all(
+        &mut self,
+        query: S,
+        replacement: S,
+        options: FindOptions,
+    ) -> ComposerUpdate<S> {
+        let matches = self.find_matches(&query, &options);
+        self.replace_matches(&matches, replacement)
+    }
+
+    /// Replace the match at [index] in the results of the last call to
+    /// [Self::find], re-running the search on the current content.
+    pub fn replace_match(
+        &mut self,
+        index: usize,
+        replacement: S,
+    ) -> ComposerUpdate<S> {
+        let Some(query) = self.search_query.clone() else {
+            return ComposerUpdate::keep();
+        };
+        let matches = self.find_matches(&query, &self.search_options.clone());
+        match matches.get(index) {
+            Some(found) => self.replace_matches(&[*found], replacement),
+            None => ComposerUpdate::keep(),
+        }
+    }
+
+    fn find_matches(&self, query: &S, options: &FindOptions) -> Vec<FindMatch> {
+        let query: Vec<char> = query.chars().collect();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut chars = Vec::new();
+        collect_search_chars(
+            self.state.dom.document(),
+            &mut chars,
+            &mut 0,
+            false,
+        );
+
+        let chars_match = |c: Option<char>, q: char| match c {
+            Some(c) if options.case_sensitive => c == q,
+            Some(c) => c.to_lowercase().eq(q.to_lowercase()),
+            None => false,
+        };
+
+        let mut matches = Vec::new();
+        let mut i = 0;
+        while i + query.len() <= chars.len() {
+            let candidate = &chars[i..i + query.len()];
+            let is_match = candidate
+                .iter()
+                .zip(query.iter())
+                .all(|(sc, q)| chars_match(sc.c, *q));
+            let is_whole_word = !options.whole_word
+                || ((i == 0 || !chars[i - 1].is_word_char())
+                    && chars
+                        .get(i + query.len())
+                        .map_or(true, |next| !next.is_word_char()));
+            if is_match && is_whole_word {
+                matches.push(FindMatch {
+                    start: candidate[0].start,
+                    end: candidate[candidate.len() - 1].end(),
+                });
+                i += query.len();
+            } else {
+                i += 1;
+            }
+        }
+        matches
+    }
+
+    fn replace_matches(
+        &mut self,
+        matches: &[FindMatch],
+        replacement: S,
+    ) -> ComposerUpdate<S> {
+        let Some(last) = matches.last() else {
+            return ComposerUpdate::keep();
+        };
+        self.push_state_to_history();
+        self.state.toggled_format_types.clear();
+
+        // Replace from the end, so the earlier matches don't move
+        for found in matches.iter().rev() {
+            self.replace_keeping_formatting(found, replacement.clone());
+        }
+
+        let len_change: isize = matches
+            .iter()
+            .map(|m| replacement.len() as isize - (m.end - m.start) as isize)
+            .sum();
+        let cursor = last.end as isize + len_change;
+        self.state.start = Location::from(cursor as usize);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+
+    /// Put [replacement] in place of the matched text, inside the same
+    /// node as the first matched character. The replacement is inserted
+    /// before deleting anything, so that nodes like links are not removed
+    /// for being empty along the way.
+    fn replace_keeping_formatting(
+        &mut self,
+        found: &FindMatch,
+        replacement: S,
+    ) {
+        let first_char_end = self
+            .first_char_len_at(found.start)
+            .map_or(found.end, |len| found.start + len);
+        let len = replacement.len();
+        if len > 0 {
+            self.state.dom.replace_text_in(
+                replacement,
+                first_char_end,
+                first_char_end,
+            );
+        }
+        if first_char_end < found.end {
+            self.state.dom.replace_text_in(
+                S::default(),
+                first_char_end + len,
+                found.end + len,
+            );
+        }
+        self.state.dom.replace_text_in(
+            S::default(),
+            found.start,
+            first_char_end,
+        );
+    }
+
+    fn first_char_len_at(&self, pos: usize) -> Option<usize> {
+        let range = self.state.dom.find_range(pos, pos);
+        let len = range.leaves().find_map(|loc| {
+            if loc.start_offset == loc.length {
+                return None;
+            }
+            let DomNode::Text(text) =
+                self.state.dom.lookup_node(&loc.node_handle)
+            else {
+                return None;
+            };
+            let c = text.data()[loc.start_offset..].chars().next()?;
+            Some(text.data().char_len(&c))
+        });
+        len
+    }
+}
+
+fn collect_search_chars<S: UnicodeString>(
+    container: &ContainerNode<S>,
+    chars: &mut Vec<SearchChar>,
+    pos: &mut usize,
+    in_immutable: bool,
+) {
+    let last_block = container
+        .children()
+        .iter()
+        .rposition(|child| child.is_block_node());
+    for (i, child) in container.children().iter().enumerate() {
+        match child {
+            DomNode::Container(c) => collect_search_chars(
+                c,
+                chars,
+                pos,
+                in_immutable || c.is_immutable(),
+            ),
+            DomNode::Text(text) => {
+                let data = text.data();
+                for c in data.chars() {
+                    let len = data.char_len(&c);
+                    let c = if in_immutable { None } else { Some(c) };
+                    chars.push(SearchChar {
+                        c,
+                        start: *pos,
+                        len,
+                    });
+                    *pos += len;
+                }
+            }
+            DomNode::LineBreak(_)
+            | DomNode::HorizontalRule(_)
+            | DomNode::Mention(_)
+            | DomNode::Image(_)
+            | DomNode::Opaque(_) => {
+                chars.push(SearchChar {
+                    c: None,
+                    start: *pos,
+                    len: child.text_len(),
+                });
+                *pos += child.text_len();
+            }
+        }
+        if child.is_block_node() && Some(i) != last_block {
+            chars.push(SearchChar {
+                c: None,
+                start: *pos,
+                len: 1,
+            });
+            *pos += 1;
+        }
+    }
+}
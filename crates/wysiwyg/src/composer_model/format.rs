@@ -14,13 +14,18 @@
 
 use std::collections::HashMap;
 
+use strum::IntoEnumIterator;
+
 use crate::composer_model::menu_state::MenuStateComputeType;
 use crate::dom::action_list::DomActionList;
+use crate::dom::nodes::dom_node::DomNodeKind;
 use crate::dom::nodes::{ContainerNodeKind, DomNode};
+use crate::dom::range::DomLocationPosition;
 use crate::dom::unicode_string::UnicodeStrExt;
 use crate::dom::{Dom, DomHandle, DomLocation, Range};
 use crate::{
-    ComposerModel, ComposerUpdate, InlineFormatType, MenuAction, UnicodeString,
+    parse_hex_color, ComposerModel, ComposerUpdate, InlineFormatType,
+    MenuAction, UnicodeString,
 };
 
 #[derive(Eq, PartialEq, Debug)]
@@ -53,6 +58,39 @@ where
         self.format_or_unformat(InlineFormatType::Underline)
     }
 
+    /// Set the colour of the selected text, or of the text typed next if
+    /// the selection is a cursor. `None` removes the colour. The colour
+    /// must be a `#rgb` or `#rrggbb` hex string, otherwise nothing changes.
+    pub fn set_text_color(&mut self, color: Option<S>) -> ComposerUpdate<S> {
+        self.set_color(color, InlineFormatType::TextColor)
+    }
+
+    /// Same as [Self::set_text_color], for the background colour.
+    pub fn set_background_color(
+        &mut self,
+        color: Option<S>,
+    ) -> ComposerUpdate<S> {
+        self.set_color(color, InlineFormatType::BackgroundColor)
+    }
+
+    /// Toggle superscript, removing any subscript from the same text.
+    pub fn superscript(&mut self) -> ComposerUpdate<S> {
+        self.push_state_to_history();
+        self.format_or_unformat_exclusive(
+            InlineFormatType::Superscript,
+            InlineFormatType::Subscript,
+        )
+    }
+
+    /// Toggle subscript, removing any superscript from the same text.
+    pub fn subscript(&mut self) -> ComposerUpdate<S> {
+        self.push_state_to_history();
+        self.format_or_unformat_exclusive(
+            InlineFormatType::Subscript,
+            InlineFormatType::Superscript,
+        )
+    }
+
     pub fn inline_code(&mut self) -> ComposerUpdate<S> {
         self.push_state_to_history();
         let format_type = InlineFormatType::InlineCode;
@@ -63,6 +101,97 @@ where
         }
     }
 
+    /// Removes all the inline formatting intersecting the current selection,
+    /// splitting any formatting node that is only partially selected.
+    /// If the selection is a cursor, the active formats are toggled off
+    /// for the next text input instead.
+    pub fn clear_formatting(&mut self) -> ComposerUpdate<S> {
+        self.clear_formatting_and_maybe_links(false)
+    }
+
+    /// Same as [Self::clear_formatting], but links intersecting the
+    /// selection are also removed, keeping their text.
+    pub fn clear_formatting_and_links(&mut self) -> ComposerUpdate<S> {
+        self.clear_formatting_and_maybe_links(true)
+    }
+
+    fn clear_formatting_and_maybe_links(
+        &mut self,
+        remove_links: bool,
+    ) -> ComposerUpdate<S> {
+        let (s, e) = self.safe_selection();
+
+        if s == e {
+            let active_formats: Vec<InlineFormatType> =
+                InlineFormatType::iter()
+                    .chain([
+                        InlineFormatType::TextColor(String::new()),
+                        InlineFormatType::BackgroundColor(String::new()),
+                    ])
+                    .filter(|f| self.action_is_reversed(f.action()))
+                    .collect();
+            if active_formats.is_empty() {
+                return ComposerUpdate::keep();
+            }
+            self.push_state_to_history();
+            for format in active_formats {
+                self.toggle_zero_length_format(&format);
+            }
+            return ComposerUpdate::update_menu_state(
+                self.compute_menu_state(MenuStateComputeType::KeepIfUnchanged),
+                MenuAction::Keep,
+            );
+        }
+
+        let range = self.state.dom.find_range(s, e);
+        let formats = Self::formats_intersecting(&range);
+        let has_links = remove_links && Self::links_intersecting(&range);
+        if formats.is_empty() && !has_links {
+            return ComposerUpdate::keep();
+        }
+
+        self.push_state_to_history();
+        for format in formats {
+            self.unformat_range(s, e, &format);
+        }
+        if has_links {
+            self.remove_links_in(s, e);
+        }
+        self.create_update_replace_all()
+    }
+
+    /// Returns the formats of all the formatting nodes that are at least
+    /// partially inside the given range, in [InlineFormatType] order,
+    /// followed by each distinct colour.
+    pub(crate) fn formats_intersecting(range: &Range) -> Vec<InlineFormatType> {
+        let inside = range
+            .locations
+            .iter()
+            .filter(|l| l.relative_position() == DomLocationPosition::Inside);
+        let mut formats: Vec<InlineFormatType> = InlineFormatType::iter()
+            .filter(|format| {
+                inside
+                    .clone()
+                    .any(|l| l.kind == DomNodeKind::Formatting(format.clone()))
+            })
+            .collect();
+        for l in inside {
+            if let DomNodeKind::Formatting(format) = &l.kind {
+                if format.is_color() && !formats.contains(format) {
+                    formats.push(format.clone());
+                }
+            }
+        }
+        formats
+    }
+
+    fn links_intersecting(range: &Range) -> bool {
+        range.locations.iter().any(|l| {
+            l.kind == DomNodeKind::Link
+                && l.relative_position() == DomLocationPosition::Inside
+        })
+    }
+
     /// Finds the closest structure node ancestor for each leaf node handle and groups it with other
     /// leaves that share it as the common closest structure node ancestor. If none is found,
     /// the root/document node is used instead.
@@ -96,11 +225,36 @@ where
         }
     }
 
+    /// Like [Self::format_or_unformat], but [excluded] is removed from
+    /// the selection before [format_type] is applied to it.
+    fn format_or_unformat_exclusive(
+        &mut self,
+        format_type: InlineFormatType,
+        excluded: InlineFormatType,
+    ) -> ComposerUpdate<S> {
+        if !self.action_is_reversed(format_type.action()) {
+            let (s, e) = self.safe_selection();
+            if s == e {
+                if self.action_is_reversed(excluded.action()) {
+                    self.toggle_zero_length_format(&excluded);
+                }
+            } else {
+                let range = self.state.dom.find_range(s, e);
+                if Self::formats_intersecting(&range).contains(&excluded) {
+                    self.unformat_range(s, e, &excluded);
+                }
+            }
+        }
+        self.format_or_unformat(format_type)
+    }
+
     pub(crate) fn apply_pending_formats(&mut self, start: usize, end: usize) {
         // Reverse to pop and apply in expected order.
         self.state.toggled_format_types.reverse();
         while let Some(format) = self.state.toggled_format_types.pop() {
-            if self.action_is_reversed(format.action()) {
+            if format.is_color() {
+                self.set_color_in(start, end, &format);
+            } else if self.action_is_reversed(format.action()) {
                 self.format_range(start, end, &format);
             } else {
                 self.unformat_range(start, end, &format);
@@ -108,6 +262,54 @@ where
         }
     }
 
+    fn set_color(
+        &mut self,
+        color: Option<S>,
+        format: fn(String) -> InlineFormatType,
+    ) -> ComposerUpdate<S> {
+        let format = match color {
+            Some(color) => match parse_hex_color(&color.to_string()) {
+                Some(color) => format(color),
+                None => return ComposerUpdate::keep(),
+            },
+            None => format(String::new()),
+        };
+        self.push_state_to_history();
+        let (s, e) = self.safe_selection();
+
+        if s == e {
+            self.toggle_zero_length_format(&format);
+            ComposerUpdate::update_menu_state(
+                self.compute_menu_state(MenuStateComputeType::KeepIfUnchanged),
+                MenuAction::Keep,
+            )
+        } else {
+            self.set_color_in(s, e, &format);
+            self.create_update_replace_all()
+        }
+    }
+
+    /// Replace any colour of the same kind as [format] in the range with
+    /// [format], or just remove it if [format] has no colour.
+    fn set_color_in(
+        &mut self,
+        start: usize,
+        end: usize,
+        format: &InlineFormatType,
+    ) {
+        let range = self.state.dom.find_range(start, end);
+        for existing in Self::formats_intersecting(&range)
+            .into_iter()
+            .filter(|f| f.is_same_kind(format))
+        {
+            self.unformat_range(start, end, &existing);
+        }
+        if matches!(format.color_attribute(), Some((_, color)) if !color.is_empty())
+        {
+            self.format_range(start, end, format);
+        }
+    }
+
     fn format(&mut self, format: InlineFormatType) -> ComposerUpdate<S> {
         let (s, e) = self.safe_selection();
 
@@ -167,6 +369,14 @@ where
         &mut self,
         format: &InlineFormatType,
     ) {
+        if format.is_color() {
+            // Only the last colour set at the cursor is kept
+            self.state
+                .toggled_format_types
+                .retain(|f| !f.is_same_kind(format));
+            self.state.toggled_format_types.push(format.clone());
+            return;
+        }
         let index = self
             .state
             .toggled_format_types
@@ -196,6 +406,7 @@ where
             // Find text nodes inside the selection that are not formatted with this format
             let non_formatted_leaf_locations = locations.iter().filter(|l| {
                 l.is_leaf()
+                    && l.kind != DomNodeKind::HorizontalRule
                     && Self::path_contains_format_node(
                         &self.state.dom,
                         &l.node_handle,
@@ -285,7 +496,9 @@ where
         loc: &DomLocation,
         format: &InlineFormatType,
     ) -> bool {
-        Self::path_contains_format_node(dom, &loc.node_handle, format).is_none()
+        loc.kind != DomNodeKind::HorizontalRule
+            && Self::path_contains_format_node(dom, &loc.node_handle, format)
+                .is_none()
     }
 
     fn extend_format_in_multiple_nodes(
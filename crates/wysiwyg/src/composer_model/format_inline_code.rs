@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::composer_model::menu_state::MenuStateComputeType;
+use crate::dom::nodes::dom_node::DomNodeKind;
 use crate::dom::nodes::{DomNode, TextNode};
 use crate::dom::unicode_string::UnicodeStrExt;
 use crate::dom::{DomHandle, DomLocation};
@@ -41,7 +42,11 @@ where
 
     pub(crate) fn add_inline_code_in(&mut self, start: usize, end: usize) {
         let range = self.state.dom.find_range(start, end);
-        let leaves: Vec<&DomLocation> = range.leaves().collect();
+        // Horizontal rules are blocks, they can't be made code
+        let leaves: Vec<&DomLocation> = range
+            .leaves()
+            .filter(|l| l.kind != DomNodeKind::HorizontalRule)
+            .collect();
         // We'll iterate through the leaves finding their closest structural node ancestor and
         // grouping these leaves based on the handles of these ancestors.
         let structure_ancestors =
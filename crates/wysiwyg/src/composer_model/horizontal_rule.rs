@@ -0,0 +1,203 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::dom_node::DomNodeKind;
+use crate::dom::nodes::DomNode;
+use crate::dom::DomLocation;
+use crate::{ComposerModel, ComposerUpdate, Location, UnicodeString};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Insert a horizontal rule at the cursor, splitting the current
+    /// paragraph around it, and move the cursor to the start of the
+    /// paragraph after it. Rules can't be added inside lists, quotes, code
+    /// blocks or tables.
+    pub fn insert_horizontal_rule(&mut self) -> ComposerUpdate<S> {
+        if self.selection_is_in_nested_block() {
+            return ComposerUpdate::keep();
+        }
+
+        self.push_state_to_history();
+        let (s, e) = self.safe_selection();
+        if s == e {
+            if let Some(position) = self.insert_paragraph_at_rule(s) {
+                self.state.start = Location::from(position);
+                self.state.end = self.state.start;
+            }
+        }
+        // Split the text at the cursor, the rule goes between both halves
+        self.do_enter();
+        let (s, _) = self.safe_selection();
+        let range = self.state.dom.find_range(s, s);
+        let Some(paragraph) = range.deepest_block_node(None) else {
+            return self.create_update_replace_all();
+        };
+        let mut rule_handle = paragraph.node_handle.clone();
+        self.state
+            .dom
+            .insert_at(&rule_handle, DomNode::new_horizontal_rule());
+        if rule_handle.index_in_parent() > 0 {
+            let previous = rule_handle.prev_sibling();
+            let previous_node = self.state.dom.lookup_node(&previous);
+            if previous_node.kind() == DomNodeKind::Paragraph
+                && previous_node.has_no_text()
+            {
+                self.state.dom.remove(&previous);
+                rule_handle = previous;
+            }
+        }
+        let start = self.position_of(&rule_handle.next_sibling());
+        self.state.start = Location::from(start);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+
+    /// If [position] is right before or after a horizontal rule, add an
+    /// empty paragraph on that side of it, as nothing else can be placed
+    /// next to a rule. Returns the position of the new paragraph.
+    pub(crate) fn insert_paragraph_at_rule(
+        &mut self,
+        position: usize,
+    ) -> Option<usize> {
+        let rule = self.horizontal_rule_at(position)?;
+        let (handle, start) = if rule.start_offset == 0 {
+            (rule.node_handle, rule.position)
+        } else {
+            // Skip the rule and the gap after it
+            (rule.node_handle.next_sibling(), rule.position + 2)
+        };
+        self.state
+            .dom
+            .insert_at(&handle, DomNode::new_paragraph(Vec::new()));
+        Some(start)
+    }
+
+    /// Backspace with the cursor at [position] removes a horizontal rule
+    /// right before it, or right before the block it starts. With the
+    /// cursor right before a rule, it moves to the end of the previous
+    /// block. None if there is no rule there.
+    pub(crate) fn backspace_at_rule(
+        &mut self,
+        position: usize,
+    ) -> Option<ComposerUpdate<S>> {
+        if let Some(rule) = self.horizontal_rule_ending_at(position) {
+            return Some(self.remove_rule(&rule, position - 1));
+        }
+        if position > 1 {
+            if let Some(rule) = self.horizontal_rule_ending_at(position - 1) {
+                return Some(self.remove_rule(&rule, position - 2));
+            }
+        }
+        self.horizontal_rule_at(position)?;
+        if position == 0 {
+            return Some(ComposerUpdate::keep());
+        }
+        Some(
+            self.select(
+                Location::from(position - 1),
+                Location::from(position - 1),
+            ),
+        )
+    }
+
+    /// Delete with the cursor at [position] removes a horizontal rule right
+    /// after it, or right after the block it ends. With the cursor right
+    /// after a rule, it moves to the start of the next block. None if there
+    /// is no rule there.
+    pub(crate) fn delete_at_rule(
+        &mut self,
+        position: usize,
+    ) -> Option<ComposerUpdate<S>> {
+        if let Some(rule) = self.horizontal_rule_starting_at(position) {
+            return Some(self.remove_rule(&rule, position));
+        }
+        if let Some(rule) = self.horizontal_rule_starting_at(position + 1) {
+            return Some(self.remove_rule(&rule, position));
+        }
+        self.horizontal_rule_at(position)?;
+        if position == self.state.dom.text_len() {
+            return Some(ComposerUpdate::keep());
+        }
+        Some(
+            self.select(
+                Location::from(position + 1),
+                Location::from(position + 1),
+            ),
+        )
+    }
+
+    fn remove_rule(
+        &mut self,
+        rule: &DomLocation,
+        cursor: usize,
+    ) -> ComposerUpdate<S> {
+        self.push_state_to_history();
+        let handle = &rule.node_handle;
+        self.state.dom.remove(handle);
+        // A block can't be left without any children
+        let parent = handle.parent_handle();
+        if !parent.is_root()
+            && self
+                .state
+                .dom
+                .lookup_container(&parent)
+                .children()
+                .is_empty()
+        {
+            self.state
+                .dom
+                .insert_at(handle, DomNode::new_paragraph(Vec::new()));
+        }
+        self.state.start = Location::from(cursor);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+
+    /// True if a horizontal rule is selected between start and end.
+    pub(crate) fn range_contains_rule(&self, start: usize, end: usize) -> bool {
+        self.state
+            .dom
+            .find_range(start, end)
+            .leaves()
+            .any(|l| l.kind == DomNodeKind::HorizontalRule)
+    }
+
+    /// The horizontal rule that [position] is right before or after.
+    fn horizontal_rule_at(&self, position: usize) -> Option<DomLocation> {
+        self.state
+            .dom
+            .find_range(position, position)
+            .locations
+            .into_iter()
+            .find(|l| l.kind == DomNodeKind::HorizontalRule)
+    }
+
+    fn horizontal_rule_starting_at(
+        &self,
+        position: usize,
+    ) -> Option<DomLocation> {
+        self.horizontal_rule_at(position)
+            .filter(|rule| rule.start_offset == 0)
+    }
+
+    fn horizontal_rule_ending_at(
+        &self,
+        position: usize,
+    ) -> Option<DomLocation> {
+        self.horizontal_rule_at(position)
+            .filter(|rule| rule.start_offset == 1)
+    }
+}
@@ -97,7 +97,10 @@ where
                     }
                 }
                 DomNodeKind::LineBreak => continue,
-                DomNodeKind::Mention => return false,
+                DomNodeKind::HorizontalRule
+                | DomNodeKind::Mention
+                | DomNodeKind::Image
+                | DomNodeKind::Opaque => return false,
                 DomNodeKind::Formatting(_)
                 | DomNodeKind::Link
                 | DomNodeKind::ListItem
@@ -105,7 +108,10 @@ where
                 | DomNodeKind::CodeBlock
                 | DomNodeKind::Quote
                 | DomNodeKind::Generic
-                | DomNodeKind::Paragraph => {
+                | DomNodeKind::Paragraph
+                | DomNodeKind::Table
+                | DomNodeKind::TableRow
+                | DomNodeKind::TableCell => {
                     unreachable!("Inside leaf iterator and found a non-leaf")
                 }
             }
@@ -227,6 +233,13 @@ where
 
         for (_, s, e) in split_points.into_iter() {
             let range = self.state.dom.find_range(s, e);
+            // Horizontal rules are blocks, they can't be linked
+            if range
+                .leaves()
+                .all(|l| l.kind == DomNodeKind::HorizontalRule)
+            {
+                continue;
+            }
 
             // Create a new link node containing the passed range
             let inserted = self.state.dom.insert_parent(
@@ -324,24 +337,25 @@ where
     }
 
     pub fn remove_links(&mut self) -> ComposerUpdate<S> {
-        let mut has_found_link = false;
         let (s, e) = self.safe_selection();
         let range = self.state.dom.find_range(s, e);
-        let iter = range.locations.into_iter().rev();
-        for loc in iter {
+        if !range.locations.iter().any(|l| l.kind == DomNodeKind::Link) {
+            return ComposerUpdate::keep();
+        }
+        self.push_state_to_history();
+        self.remove_links_in(s, e);
+        self.create_update_replace_all()
+    }
+
+    /// Replaces every link found in the given range with its children.
+    pub(crate) fn remove_links_in(&mut self, start: usize, end: usize) {
+        let range = self.state.dom.find_range(start, end);
+        for loc in range.locations.into_iter().rev() {
             if loc.kind == DomNodeKind::Link {
-                if !has_found_link {
-                    has_found_link = true;
-                    self.push_state_to_history();
-                }
                 self.state
                     .dom
                     .replace_node_with_its_children(&loc.node_handle);
             }
         }
-        if !has_found_link {
-            return ComposerUpdate::keep();
-        }
-        self.create_update_replace_all()
     }
 }
@@ -0,0 +1,257 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::DomNode;
+use crate::dom::parser::markdown::markdown_html_parser::MarkdownHTMLParser;
+use crate::dom::parser::parse;
+use crate::dom::unicode_string::{UnicodeStr, UnicodeStrExt, UnicodeStringExt};
+use crate::dom::Dom;
+use crate::{
+    ComposerModel, ComposerUpdate, DomCreationError, Location, ToHtml,
+    ToMarkdown, ToRawText, UnicodeString,
+};
+
+/// Marks the selection while the content is converted, so that it can be
+/// found again afterwards. It is a noncharacter, reserved for internal use,
+/// so it can't be part of the content.
+const SELECTION_MARKER: char = '\u{FDD0}';
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Whether the content is Markdown typed as plain text.
+    pub fn is_markdown_mode(&self) -> bool {
+        self.state.markdown_mode
+    }
+
+    /// Replace the rich content with its Markdown, as plain text with one
+    /// line per paragraph, keeping the selection on the same text. Undoing
+    /// it brings the rich content back. If the content can't be written as
+    /// Markdown, nothing changes.
+    pub fn switch_to_markdown_mode(&mut self) -> ComposerUpdate<S> {
+        if self.state.markdown_mode {
+            return ComposerUpdate::keep();
+        }
+        let (s, e) = self.safe_selection();
+        let mut marked = Self::from_state(self.state.clone());
+        marked.state.toggled_format_types.clear();
+        marked.do_replace_text_in(SELECTION_MARKER.to_string().into(), e, e);
+        marked.do_replace_text_in(SELECTION_MARKER.to_string().into(), s, s);
+        let Ok(markdown) = marked.state.dom.to_markdown() else {
+            return ComposerUpdate::keep();
+        };
+        let (markdown, start, end) = remove_markers::<S>(markdown.to_string());
+
+        self.push_state_to_history();
+        self.state.dom = markdown_dom(&markdown);
+        self.state.markdown_mode = true;
+        self.state.composition = None;
+        self.state_before_composition = None;
+        let (start, end) = (start.unwrap_or(0), end.unwrap_or(0));
+        self.select_keeping_direction(start, end);
+        self.create_update_replace_all_with_menu_state()
+    }
+
+    /// Replace the Markdown typed in Markdown mode with the rich content it
+    /// describes, keeping the selection on the same text where possible.
+    /// Undoing it brings the Markdown back.
+    pub fn switch_to_rich_mode(
+        &mut self,
+    ) -> Result<ComposerUpdate<S>, DomCreationError> {
+        if !self.state.markdown_mode {
+            return Ok(ComposerUpdate::keep());
+        }
+        let (s, e) = self.safe_selection();
+        let source = self.markdown_source();
+        let html = MarkdownHTMLParser::to_html(&source)
+            .map_err(DomCreationError::MarkdownParseError)?;
+        let mut dom: Dom<S> = parse(&html.to_string())
+            .map_err(DomCreationError::HtmlParseError)?;
+        let start = rich_location(&source, s, &dom);
+        let end = if s == e {
+            start
+        } else {
+            rich_location(&source, e, &dom)
+        };
+        Self::post_process_dom(&mut dom);
+
+        self.push_state_to_history();
+        let len = dom.text_len();
+        self.state.dom = dom;
+        self.state.markdown_mode = false;
+        self.state.composition = None;
+        self.state_before_composition = None;
+        let (start, end) = (start.unwrap_or(len), end.unwrap_or(len));
+        self.select_keeping_direction(start.min(len), end.min(len));
+        Ok(self.create_update_replace_all_with_menu_state())
+    }
+
+    /// The Markdown typed in Markdown mode, with a `\n` for each line break
+    /// or paragraph boundary, so that offsets in it match locations.
+    fn markdown_source(&self) -> S {
+        let dom = &self.state.dom;
+        let len = dom.text_len();
+        let mut source = S::default();
+        let mut location = 0;
+        for leaf in dom.find_range(0, len).leaves() {
+            while location < leaf.position {
+                source.push('\n');
+                location += 1;
+            }
+            let node = dom.lookup_node(&leaf.node_handle);
+            match node.as_text() {
+                Some(text) => source.push(text.data()),
+                None if node.is_line_break() => source.push('\n'),
+                None => source.push(node.to_raw_text()),
+            }
+            location += leaf.length;
+        }
+        while location < len {
+            source.push('\n');
+            location += 1;
+        }
+        source
+    }
+
+    /// Select from [start] to [end], the other way round if the current
+    /// selection is backwards.
+    fn select_keeping_direction(&mut self, start: usize, end: usize) {
+        if self.state.start > self.state.end {
+            self.state.start = Location::from(end);
+            self.state.end = Location::from(start);
+        } else {
+            self.state.start = Location::from(start);
+            self.state.end = Location::from(end);
+        }
+    }
+}
+
+/// Remove both selection markers from [text], returning the remaining text
+/// and where the markers were, in code units of S.
+fn remove_markers<S: UnicodeString>(
+    text: String,
+) -> (S, Option<usize>, Option<usize>) {
+    let mut offsets = text.match_indices(SELECTION_MARKER).map(|(i, _)| {
+        let before = text[..i].replace(SELECTION_MARKER, "");
+        S::from(before.as_str()).len()
+    });
+    let start = offsets.next();
+    let end = offsets.next();
+    (S::from(text.replace(SELECTION_MARKER, "")), start, end)
+}
+
+/// Where the text at [offset] in the Markdown [source] is in [dom], the
+/// rich content it describes. A marker put inside Markdown syntax, e.g.
+/// between the `_` of `__`, would change the content, so either side of the
+/// syntax, the start of the word and the start of the line are tried too.
+fn rich_location<S: UnicodeString>(
+    source: &S,
+    offset: usize,
+    dom: &Dom<S>,
+) -> Option<usize> {
+    let (run_start, run_end) = punctuation_run(source, offset);
+    let word_start = offset - source.previous_whitespace_offset(offset);
+    let before = source[..offset].to_string();
+    let line_start = before
+        .rfind('\n')
+        .map_or(0, |i| S::from(&before[..=i]).len());
+    let html = dom.to_html().to_string();
+    [offset, run_start, run_end, word_start, line_start]
+        .into_iter()
+        .find_map(|offset| {
+            let mut marked = source[..offset].to_owned();
+            marked.push(SELECTION_MARKER);
+            marked.push(&source[offset..]);
+            let marked_html = MarkdownHTMLParser::to_html(&marked).ok()?;
+            let marked_dom: Dom<S> = parse(&marked_html.to_string()).ok()?;
+            let unmarked_html = marked_dom
+                .to_html()
+                .to_string()
+                .replace(SELECTION_MARKER, "");
+            if unmarked_html == html {
+                marker_location(&marked_dom)
+            } else {
+                None
+            }
+        })
+}
+
+/// The start and end of the ASCII punctuation around [offset] in [source],
+/// e.g. `__` or `](`.
+fn punctuation_run<S: UnicodeString>(
+    source: &S,
+    offset: usize,
+) -> (usize, usize) {
+    let mut chars = Vec::new();
+    let mut position = 0;
+    for c in source.chars() {
+        chars.push((position, c));
+        position += source.char_len(&c);
+    }
+    let start = chars
+        .iter()
+        .rev()
+        .filter(|(p, _)| *p < offset)
+        .take_while(|(_, c)| c.is_ascii_punctuation())
+        .last()
+        .map_or(offset, |(p, _)| *p);
+    let end = chars
+        .iter()
+        .filter(|(p, _)| *p >= offset)
+        .take_while(|(_, c)| c.is_ascii_punctuation())
+        .last()
+        .map_or(offset, |(p, c)| p + source.char_len(c));
+    (start, end)
+}
+
+/// The location of the selection marker in [dom], if it's in a text node.
+fn marker_location<S: UnicodeString>(dom: &Dom<S>) -> Option<usize> {
+    dom.find_range(0, dom.text_len()).leaves().find_map(|leaf| {
+        let text = dom.lookup_node(&leaf.node_handle).as_text()?;
+        let mut offset = 0;
+        for c in text.data().chars() {
+            if c == SELECTION_MARKER {
+                return Some(leaf.position + offset);
+            }
+            offset += text.data().char_len(&c);
+        }
+        None
+    })
+}
+
+/// A DOM for Markdown typed as plain text, with a paragraph for each line.
+fn markdown_dom<S: UnicodeString>(markdown: &S) -> Dom<S> {
+    let markdown = markdown.to_string();
+    if markdown.is_empty() {
+        return Dom::default();
+    }
+    let lines: Vec<&str> = markdown.split('\n').collect();
+    if lines.len() == 1 {
+        return Dom::new(vec![DomNode::new_text(S::from(lines[0]))]);
+    }
+    Dom::new(
+        lines
+            .into_iter()
+            .map(|line| {
+                let children = if line.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![DomNode::new_text(S::from(line))]
+                };
+                DomNode::new_paragraph(children)
+            })
+            .collect(),
+    )
+}
@@ -14,10 +14,11 @@
 
 use crate::{
     dom::{
-        unicode_string::{UnicodeStr, UnicodeStringExt},
+        unicode_string::{UnicodeStr, UnicodeStrExt, UnicodeStringExt},
         Range,
     },
-    ComposerModel, MenuAction, PatternKey, SuggestionPattern, UnicodeString,
+    ComposerModel, MenuAction, PatternKey, SuggestionPattern,
+    SuggestionTrigger, UnicodeString,
 };
 
 impl<S> ComposerModel<S>
@@ -32,7 +33,11 @@ where
             return MenuAction::None;
         }
         let (raw_text, start, end) = self.extended_text(range);
-        if let Some((key, text)) = Self::pattern_for_text(raw_text, start) {
+        let pattern = self
+            .pattern_for_text(raw_text, start)
+            .map(|(key, text)| (key, text, start, end))
+            .or_else(|| self.pattern_spanning_whitespace(s, e));
+        if let Some((key, text, start, end)) = pattern {
             MenuAction::Suggestion(SuggestionPattern {
                 key,
                 text,
@@ -69,27 +74,237 @@ where
             )
     }
 
-    /// Compute at/hash/slash pattern for a given text.
+    /// Set how many characters must follow a `:` before emoji are
+    /// suggested.
+    pub fn set_emoji_suggestion_min_length(&mut self, min_length: usize) {
+        self.emoji_suggestion_min_length = min_length;
+    }
+
+    /// Set the extra characters that start a suggestion. Triggers using
+    /// one of the built in keys (`@`, `#`, `/` and `:`) are ignored.
+    pub fn set_suggestion_triggers(
+        &mut self,
+        triggers: Vec<SuggestionTrigger>,
+    ) {
+        self.suggestion_triggers = triggers;
+    }
+
+    /// Add a trigger to [Self::suggestion_triggers], replacing any existing
+    /// trigger for the same key.
+    pub fn add_suggestion_trigger(&mut self, trigger: SuggestionTrigger) {
+        self.suggestion_triggers.retain(|t| t.key != trigger.key);
+        self.suggestion_triggers.push(trigger);
+    }
+
+    pub fn suggestion_triggers(&self) -> &[SuggestionTrigger] {
+        &self.suggestion_triggers
+    }
+
+    /// Set how many words a `@` or `#` suggestion may span, so that e.g.
+    /// `@John Sm` can search display names. Defaults to 1, which ends
+    /// suggestions at the first whitespace.
+    pub fn set_mention_suggestion_max_words(&mut self, max_words: usize) {
+        self.mention_suggestion_max_words = max_words;
+    }
+
+    /// Compute at/hash/slash/colon/custom pattern for a given text.
     /// Return pattern key and associated text, if it exists.
     fn pattern_for_text(
+        &self,
         mut text: S,
         start_location: usize,
     ) -> Option<(PatternKey, String)> {
         let Some(first_char) = text.pop_first() else {
             return None;
         };
-        let Some(key) = PatternKey::from_char(first_char) else {
+        let key = self.pattern_key_for_char(first_char)?;
+        let trigger = self.suggestion_trigger(&key);
+        let only_at_start = key == PatternKey::Slash
+            || trigger.map_or(false, |t| t.only_at_start);
+        let allow_whitespace = match key {
+            PatternKey::At | PatternKey::Hash => {
+                self.is_multi_word_mention(&text)
+            }
+            _ => trigger.map_or(false, |t| t.allow_whitespace),
+        };
+
+        // Exclude patterns that must be at the beginning of the document but
+        // aren't, and any selection that contains inner whitespaces unless
+        // the trigger allows them.
+        if (only_at_start && start_location > 0)
+            || (!allow_whitespace && text.chars().any(|c| c.is_whitespace()))
+        {
+            return None;
+        }
+        if key == PatternKey::Colon
+            && !self.is_emoji_shortcode(&text, start_location)
+        {
+            return None;
+        }
+        if trigger.map_or(false, |t| text.chars().count() < t.min_length) {
+            return None;
+        }
+        Some((key, text.to_string()))
+    }
+
+    /// The built in or custom key started by [c], if any.
+    fn pattern_key_for_char(&self, c: char) -> Option<PatternKey> {
+        PatternKey::from_char(c).or_else(|| {
+            self.suggestion_triggers
+                .iter()
+                .any(|t| t.key == c)
+                .then_some(PatternKey::Custom(c))
+        })
+    }
+
+    /// The custom trigger behind [key], if it is a custom one.
+    fn suggestion_trigger(
+        &self,
+        key: &PatternKey,
+    ) -> Option<&SuggestionTrigger> {
+        let PatternKey::Custom(c) = key else {
             return None;
         };
+        self.suggestion_triggers.iter().find(|t| t.key == *c)
+    }
 
-        // Exclude slash patterns that are not at the beginning of the document
-        // and any selection that contains inner whitespaces.
-        if (key == PatternKey::Slash && start_location > 0)
-            || text.chars().any(|c| c.is_whitespace())
+    /// Look for a pattern spanning whitespace, by searching back from the
+    /// selection for a trigger starting a word. Returns the pattern key and
+    /// text, and its start/end locations.
+    fn pattern_spanning_whitespace(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Option<(PatternKey, String, usize, usize)> {
+        if self.mention_suggestion_max_words <= 1
+            && !self.suggestion_triggers.iter().any(|t| t.allow_whitespace)
         {
-            None
-        } else {
-            Some((key, text.to_string()))
+            return None;
+        }
+        let (text, text_start) = self.text_around(start, end)?;
+        let start_offset = start - text_start;
+
+        let mut offset = 0;
+        let mut previous: Option<char> = None;
+        let mut trigger_offset = None;
+        for c in text.chars() {
+            if offset >= start_offset {
+                break;
+            }
+            let starts_word = previous.map_or(true, |p| p.is_whitespace());
+            if starts_word && self.key_can_span_whitespace(c) {
+                trigger_offset = Some(offset);
+            }
+            offset += text.char_len(&c);
+            previous = Some(c);
+        }
+        let trigger_offset = trigger_offset?;
+
+        let end_offset = end - text_start;
+        let end_offset = end_offset + text.next_whitespace_offset(end_offset);
+        let pattern_text = text[trigger_offset..end_offset].to_owned();
+        let start_location = text_start + trigger_offset;
+        let (key, pattern_text) =
+            self.pattern_for_text(pattern_text, start_location)?;
+        Some((key, pattern_text, start_location, text_start + end_offset))
+    }
+
+    /// Whether a pattern started by [c] may contain whitespace.
+    fn key_can_span_whitespace(&self, c: char) -> bool {
+        match self.pattern_key_for_char(c) {
+            Some(PatternKey::At | PatternKey::Hash) => {
+                self.mention_suggestion_max_words > 1
+            }
+            Some(key) => self
+                .suggestion_trigger(&key)
+                .map_or(false, |t| t.allow_whitespace),
+            None => false,
+        }
+    }
+
+    /// The text around the selection, across text nodes but not across
+    /// line breaks, mentions or any other leaves, and not outside of its
+    /// block. Returns the text and its start location.
+    pub(crate) fn text_around(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Option<(S, usize)> {
+        let dom = &self.state.dom;
+        let range = dom.find_range(start, end);
+        let leaf = range
+            .leaves()
+            .find(|l| dom.lookup_node(&l.node_handle).is_text_node())?;
+        let (block_handle, block_range) = match range
+            .deepest_block_node(Some(&leaf.node_handle))
+        {
+            Some(block) => (
+                block.node_handle.clone(),
+                dom.find_range(block.position, block.position + block.length),
+            ),
+            None => (dom.document_handle(), dom.find_range(0, dom.text_len())),
+        };
+
+        let mut text = S::default();
+        let mut text_start = None;
+        for loc in block_range
+            .leaves()
+            .filter(|l| block_handle.is_ancestor_of(&l.node_handle))
+        {
+            match dom.lookup_node(&loc.node_handle).as_text() {
+                Some(t) => {
+                    text_start.get_or_insert(loc.position);
+                    text.push(t.data());
+                }
+                None if loc.position >= end => break,
+                None => {
+                    text = S::default();
+                    text_start = None;
+                }
+            }
         }
+        let text_start = text_start?;
+        (text_start <= start && end <= text_start + text.len())
+            .then_some((text, text_start))
+    }
+
+    /// Whether [text], following a `@` or `#`, is a search for a name of
+    /// several words, e.g. `John Sm`. It must be within the word limit, and
+    /// typing punctuation ends it.
+    fn is_multi_word_mention(&self, text: &S) -> bool {
+        let words = text.chars().filter(|c| c.is_whitespace()).count() + 1;
+        words <= self.mention_suggestion_max_words
+            && !text.chars().any(|c| {
+                c.is_ascii_punctuation() && !matches!(c, '-' | '\'' | '_')
+            })
+    }
+
+    /// Whether [text], following a `:` at [colon_location], is the start of
+    /// an emoji shortcode. It must be long enough, and not already closed
+    /// by another `:`. A `:` right after a digit is part of a time or a
+    /// score (e.g. `10:30`) instead.
+    fn is_emoji_shortcode(&self, text: &S, colon_location: usize) -> bool {
+        text.chars().count() >= self.emoji_suggestion_min_length
+            && !text.chars().any(|c| c == ':')
+            && !self
+                .char_before(colon_location)
+                .map_or(false, |c| c.is_ascii_digit())
+    }
+
+    /// The character right before [location] in the text, if any.
+    fn char_before(&self, location: usize) -> Option<char> {
+        if location == 0 {
+            return None;
+        }
+        let range = self.state.dom.find_range(location - 1, location);
+        let first_char = range
+            .leaves()
+            .filter(|loc| loc.start_offset < loc.end_offset)
+            .find_map(|loc| {
+                let node = self.state.dom.lookup_node(&loc.node_handle);
+                let text = node.as_text()?;
+                text.data()[loc.start_offset..loc.end_offset].chars().next()
+            });
+        first_char
     }
 }
@@ -17,7 +17,7 @@ use strum::IntoEnumIterator;
 use crate::action_state::ActionState;
 use crate::dom::nodes::dom_node::DomNodeKind;
 use crate::dom::nodes::{ContainerNode, ContainerNodeKind};
-use crate::dom::range::DomLocationPosition::{After, Before};
+use crate::dom::range::DomLocationPosition::{After, Before, Inside};
 use crate::dom::{DomLocation, Range};
 use crate::menu_state::MenuStateUpdate;
 use crate::ComposerAction::{
@@ -41,19 +41,25 @@ where
     pub(crate) fn compute_menu_state(
         &mut self,
         compute_type: MenuStateComputeType,
-    ) -> MenuState {
+    ) -> MenuState<S> {
         let (s, e) = self.safe_selection();
         let range = self.state.dom.find_range(s, e);
 
         let action_states = self.compute_action_states(&range);
+        let context_state = self.compute_context_state(&range);
 
         if action_states == self.action_states
+            && context_state == self.context_state
             && matches!(compute_type, MenuStateComputeType::KeepIfUnchanged)
         {
             MenuState::Keep
         } else {
             self.action_states = action_states.clone();
-            MenuState::Update(MenuStateUpdate { action_states })
+            self.context_state = context_state.clone();
+            MenuState::Update(MenuStateUpdate {
+                action_states,
+                context_state: Some(context_state),
+            })
         }
     }
 
@@ -64,13 +70,20 @@ where
         let mut action_states = HashMap::new();
 
         let reversed = self.compute_reversed_actions_from_range(range);
-        let disabled = self.compute_disabled_actions();
+        let mut disabled = self.compute_disabled_actions();
+        if !self.can_clear_formatting(range, &reversed) {
+            disabled.insert(ComposerAction::ClearFormatting);
+        }
+
+        let mixed = self.compute_mixed_actions_from_range(range, &reversed);
 
         for action in ComposerAction::iter() {
             let state = if disabled.contains(&action) {
                 ActionState::Disabled
             } else if reversed.contains(&action) {
                 ActionState::Reversed
+            } else if mixed.contains(&action) {
+                ActionState::Mixed
             } else {
                 ActionState::Enabled
             };
@@ -80,6 +93,26 @@ where
         action_states
     }
 
+    /// Clearing formatting is possible if a cursor has any active inline
+    /// format, or if a selection intersects any formatting node.
+    fn can_clear_formatting(
+        &self,
+        range: &Range,
+        reversed: &HashSet<ComposerAction>,
+    ) -> bool {
+        if self.has_cursor() {
+            InlineFormatType::iter()
+                .map(|f| f.action())
+                .chain([
+                    ComposerAction::TextColor,
+                    ComposerAction::BackgroundColor,
+                ])
+                .any(|action| reversed.contains(&action))
+        } else {
+            !Self::formats_intersecting(range).is_empty()
+        }
+    }
+
     fn compute_reversed_actions_from_range(
         &self,
         range: &Range,
@@ -88,6 +121,7 @@ where
             .state
             .toggled_format_types
             .iter()
+            .filter(|format| !format.is_color())
             .map(|format| format.action())
             .collect();
 
@@ -115,9 +149,40 @@ where
             HashSet::new()
         };
 
-        reversed_actions
+        let mut reversed_actions: HashSet<ComposerAction> = reversed_actions
             .symmetric_difference(&toggled_format_actions)
             .cloned()
+            .collect();
+        // Pending colours replace the current one rather than toggling it
+        for format in &self.state.toggled_format_types {
+            if let Some((_, color)) = format.color_attribute() {
+                if color.is_empty() {
+                    reversed_actions.remove(&format.action());
+                } else {
+                    reversed_actions.insert(format.action());
+                }
+            }
+        }
+        reversed_actions
+    }
+
+    /// Finds the actions that would be reversed for some, but not all, of
+    /// the leaves inside a selection. Always empty for a cursor.
+    fn compute_mixed_actions_from_range(
+        &self,
+        range: &Range,
+        reversed: &HashSet<ComposerAction>,
+    ) -> HashSet<ComposerAction> {
+        if !self.has_selection() {
+            return HashSet::new();
+        }
+        range
+            .leaves()
+            .filter(|loc| loc.relative_position() == Inside)
+            .flat_map(|loc| self.compute_reversed_actions(&loc.node_handle))
+            .filter(|action| {
+                supports_mixed_state(action) && !reversed.contains(action)
+            })
             .collect()
     }
 
@@ -173,6 +238,16 @@ where
                 InlineFormatType::InlineCode => {
                     Some(ComposerAction::InlineCode)
                 }
+                InlineFormatType::Superscript => {
+                    Some(ComposerAction::Superscript)
+                }
+                InlineFormatType::Subscript => Some(ComposerAction::Subscript),
+                InlineFormatType::TextColor(_) => {
+                    Some(ComposerAction::TextColor)
+                }
+                InlineFormatType::BackgroundColor(_) => {
+                    Some(ComposerAction::BackgroundColor)
+                }
             },
             ContainerNodeKind::Link(_) => Some(ComposerAction::Link),
             ContainerNodeKind::List(list_type) => match list_type {
@@ -233,6 +308,8 @@ where
                 ComposerAction::Italic,
                 ComposerAction::Underline,
                 ComposerAction::StrikeThrough,
+                ComposerAction::Superscript,
+                ComposerAction::Subscript,
                 ComposerAction::Link,
             ])
         } else if contains_code_block(locations) {
@@ -244,10 +321,38 @@ where
                 ComposerAction::Link,
             ])
         }
+        if contains_table_cell(locations) {
+            disabled_actions.extend(vec![
+                ComposerAction::OrderedList,
+                ComposerAction::UnorderedList,
+                ComposerAction::Quote,
+                ComposerAction::CodeBlock,
+            ])
+        }
         disabled_actions
     }
 }
 
+/// Actions whose state can be reported as [ActionState::Mixed].
+fn supports_mixed_state(action: &ComposerAction) -> bool {
+    matches!(
+        action,
+        ComposerAction::Bold
+            | ComposerAction::Italic
+            | ComposerAction::StrikeThrough
+            | ComposerAction::Underline
+            | ComposerAction::InlineCode
+            | ComposerAction::Superscript
+            | ComposerAction::Subscript
+            | Link
+            | OrderedList
+            | UnorderedList
+            | ComposerAction::Quote
+            | ComposerAction::TextColor
+            | ComposerAction::BackgroundColor
+    )
+}
+
 fn contains_inline_code(locations: &[DomLocation]) -> bool {
     locations.iter().any(|l| {
         matches!(
@@ -257,6 +362,12 @@ fn contains_inline_code(locations: &[DomLocation]) -> bool {
     })
 }
 
+fn contains_table_cell(locations: &[DomLocation]) -> bool {
+    locations.iter().any(|l| {
+        l.relative_position() != Before && l.kind == DomNodeKind::TableCell
+    })
+}
+
 fn contains_code_block(locations: &[DomLocation]) -> bool {
     locations.iter().any(|l| {
         l.relative_position() != Before && l.kind == DomNodeKind::CodeBlock
@@ -0,0 +1,161 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::{ContainerNodeKind, DomNode, MentionNodeKind};
+use crate::dom::unicode_string::UnicodeStringExt;
+use crate::{
+    reply_fallback_body, reply_fallback_html, ComposerModel, Mentions,
+    MessageContent, MessageContentOptions, ReplyParent, UnicodeString,
+};
+
+const HTML_FORMAT: &str = "org.matrix.custom.html";
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Build the content of an `m.room.message` event from the composer.
+    /// The formatted body is omitted if the content is just plain text.
+    /// If a Markdown body was asked for but the content can't be written as
+    /// Markdown, the body is plain text instead.
+    pub fn to_message_content(
+        &self,
+        options: MessageContentOptions,
+    ) -> MessageContent<S> {
+        let body = if options.markdown_body {
+            self.try_get_content_as_markdown()
+                .unwrap_or_else(|_| self.get_content_as_plain_text())
+        } else {
+            self.get_content_as_plain_text()
+        };
+        // Paragraphs end with a new line, which isn't wanted in a message
+        let body = S::from(body.to_string().trim_end_matches('\n'));
+        let (format, formatted_body) = if self.has_formatting() {
+            (
+                Some(S::from(HTML_FORMAT)),
+                Some(self.get_content_as_message_html()),
+            )
+        } else {
+            (None, None)
+        };
+        MessageContent {
+            msgtype: options.msgtype,
+            body,
+            format,
+            formatted_body,
+            mentions: self.mentions(),
+            in_reply_to: None,
+        }
+    }
+
+    /// Build the content of a reply to [parent], with the reply fallbacks
+    /// the spec requires at the start of the body and formatted body. The
+    /// sender of [parent] is mentioned.
+    pub fn to_reply_message_content(
+        &self,
+        options: MessageContentOptions,
+        parent: &ReplyParent<S>,
+    ) -> MessageContent<S> {
+        let mut content = self.to_message_content(options);
+
+        let mut body = reply_fallback_body(parent);
+        body.push(content.body);
+        content.body = body;
+
+        let mut formatted_body = reply_fallback_html(parent);
+        formatted_body.push(self.get_content_as_message_html());
+        content.format = Some(S::from(HTML_FORMAT));
+        content.formatted_body = Some(formatted_body);
+
+        if !content.mentions.user_ids.contains(&parent.sender) {
+            content.mentions.user_ids.push(parent.sender.clone());
+        }
+        content.in_reply_to = Some(parent.event_id.clone());
+        content
+    }
+
+    fn has_formatting(&self) -> bool {
+        self.state.dom.iter().any(|node| match node {
+            DomNode::Container(c) => !matches!(
+                c.kind(),
+                ContainerNodeKind::Generic | ContainerNodeKind::Paragraph
+            ),
+            DomNode::Mention(_) | DomNode::Image(_) | DomNode::Opaque(_) => {
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Users mentioned with a mention or a link to their permalink, and
+    /// whether the whole room is mentioned.
+    fn mentions(&self) -> Mentions<S> {
+        let mut mentions = Mentions::default();
+        for node in self.state.dom.iter() {
+            let url = match node {
+                DomNode::Mention(mention) => match mention.kind() {
+                    MentionNodeKind::MatrixUrl { url, .. } => url,
+                    MentionNodeKind::AtRoom => {
+                        mentions.room = true;
+                        continue;
+                    }
+                },
+                DomNode::Container(container) => match container.kind() {
+                    ContainerNodeKind::Link(url) => url,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if let Some(user_id) = user_id_from_url(&url.to_string()) {
+                let user_id = S::from(user_id.as_str());
+                if !mentions.user_ids.contains(&user_id) {
+                    mentions.user_ids.push(user_id);
+                }
+            }
+        }
+        mentions
+    }
+}
+
+/// Extract the user ID from a permalink like
+/// `https://matrix.to/#/@alice:example.org`.
+fn user_id_from_url(url: &str) -> Option<String> {
+    let (_, path) = url.split_once("matrix.to/#/")?;
+    let id = path.split(['?', '/']).next()?;
+    let id = percent_decode(id);
+    id.starts_with('@').then_some(id)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,290 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::text_node::{get_char_type, CharType};
+use crate::dom::nodes::{ContainerNode, DomNode};
+use crate::dom::unicode_string::{UnicodeStr, UnicodeStrExt};
+use crate::{
+    ComposerModel, ComposerUpdate, Direction, Granularity, Location,
+    UnicodeString,
+};
+
+/// What a cursor moves over, from the point of view of navigation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unit {
+    Char(CharType),
+    Mention,
+    LineBreak,
+    BlockBoundary,
+}
+
+impl Unit {
+    fn is_word(&self) -> bool {
+        matches!(
+            self,
+            Unit::Char(CharType::Other)
+                | Unit::Char(CharType::Punctuation)
+                | Unit::Mention
+        )
+    }
+
+    fn is_space(&self) -> bool {
+        !self.is_word()
+    }
+
+    /// Mentions (and other uneditable nodes) are words on their own, even
+    /// next to other mentions.
+    fn continues_run_of(&self, other: &Unit) -> bool {
+        self == other && *self != Unit::Mention
+    }
+}
+
+/// A grapheme, mention, line break or gap between blocks, positioned in
+/// the code units of the model.
+#[derive(Debug)]
+struct Segment {
+    start: usize,
+    end: usize,
+    unit: Unit,
+}
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Move the cursor in [direction] by [granularity]. If
+    /// [extend_selection] is true, the start of the selection stays where
+    /// it is and only its end moves.
+    pub fn move_cursor(
+        &mut self,
+        direction: Direction,
+        granularity: Granularity,
+        extend_selection: bool,
+    ) -> ComposerUpdate<S> {
+        let segments = self.navigation_segments();
+        let focus = self.focus();
+        if extend_selection {
+            let end = move_from(&segments, focus, &direction, &granularity);
+            return self.select(self.state.start, Location::from(end));
+        }
+
+        let (s, e) = self.safe_selection();
+        let edge = match direction {
+            Direction::Forwards => e,
+            Direction::Backwards => s,
+        };
+        let new_pos = if s != e && granularity == Granularity::Character {
+            // Like in most text editors, the first move just collapses
+            // the selection.
+            edge
+        } else {
+            move_from(&segments, edge, &direction, &granularity)
+        };
+        self.select(Location::from(new_pos), Location::from(new_pos))
+    }
+
+    /// Select the word the cursor is in or next to. Whitespace is selected
+    /// if there is no word next to the cursor.
+    pub fn select_word_at_cursor(&mut self) -> ComposerUpdate<S> {
+        let segments = self.navigation_segments();
+        let focus = self.focus();
+        let after = segments.iter().position(|seg| seg.start == focus);
+        let before = segments.iter().position(|seg| seg.end == focus);
+        let is_char = |i: &usize| matches!(segments[*i].unit, Unit::Char(_));
+        let index = after
+            .filter(|i| segments[*i].unit.is_word())
+            .or_else(|| before.filter(|i| segments[*i].unit.is_word()))
+            .or_else(|| after.filter(is_char))
+            .or_else(|| before.filter(is_char));
+        let Some(index) = index else {
+            return ComposerUpdate::keep();
+        };
+
+        let unit = segments[index].unit;
+        let mut first = index;
+        while first > 0 && segments[first - 1].unit.continues_run_of(&unit) {
+            first -= 1;
+        }
+        let mut last = index;
+        while last + 1 < segments.len()
+            && segments[last + 1].unit.continues_run_of(&unit)
+        {
+            last += 1;
+        }
+        self.select(
+            Location::from(segments[first].start),
+            Location::from(segments[last].end),
+        )
+    }
+
+    /// Select the whole of the blocks (e.g. paragraphs, list items) the
+    /// selection is in.
+    pub fn select_block_at_cursor(&mut self) -> ComposerUpdate<S> {
+        let segments = self.navigation_segments();
+        let (s, e) = self.safe_selection();
+        let start = move_from(
+            &segments,
+            s,
+            &Direction::Backwards,
+            &Granularity::Paragraph,
+        );
+        let end = move_from(
+            &segments,
+            e,
+            &Direction::Forwards,
+            &Granularity::Paragraph,
+        );
+        self.select(Location::from(start), Location::from(end))
+    }
+
+    /// Select the whole content of the composer.
+    pub fn select_all(&mut self) -> ComposerUpdate<S> {
+        let len = self.state.dom.text_len();
+        self.select(Location::from(0), Location::from(len))
+    }
+
+    /// The end of the selection, where the cursor is drawn.
+    fn focus(&self) -> usize {
+        let end: usize = self.state.end.into();
+        end.min(self.state.dom.text_len())
+    }
+
+    fn navigation_segments(&self) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        collect_segments(self.state.dom.document(), &mut segments, &mut 0);
+        segments
+    }
+}
+
+fn collect_segments<S: UnicodeString>(
+    container: &ContainerNode<S>,
+    segments: &mut Vec<Segment>,
+    pos: &mut usize,
+) {
+    let last_block = container
+        .children()
+        .iter()
+        .rposition(|child| child.is_block_node());
+    for (i, child) in container.children().iter().enumerate() {
+        match child {
+            DomNode::Container(c) => collect_segments(c, segments, pos),
+            DomNode::Text(text) => {
+                let data = text.data();
+                let mut offset = 0;
+                while let Some(grapheme) = data.find_graphemes_at(offset).1 {
+                    let unit = Unit::Char(
+                        grapheme
+                            .chars()
+                            .next()
+                            .map_or(CharType::Other, get_char_type),
+                    );
+                    push_segment(segments, pos, grapheme.len(), unit);
+                    offset += grapheme.len();
+                }
+            }
+            DomNode::LineBreak(_) => {
+                push_segment(segments, pos, 1, Unit::LineBreak)
+            }
+            DomNode::HorizontalRule(_) => {
+                push_segment(segments, pos, 1, Unit::BlockBoundary)
+            }
+            DomNode::Mention(_) | DomNode::Image(_) | DomNode::Opaque(_) => {
+                push_segment(segments, pos, 1, Unit::Mention)
+            }
+        }
+        if child.is_block_node() && Some(i) != last_block {
+            push_segment(segments, pos, 1, Unit::BlockBoundary);
+        }
+    }
+}
+
+fn push_segment(
+    segments: &mut Vec<Segment>,
+    pos: &mut usize,
+    len: usize,
+    unit: Unit,
+) {
+    segments.push(Segment {
+        start: *pos,
+        end: *pos + len,
+        unit,
+    });
+    *pos += len;
+}
+
+/// Find where a cursor at [pos] ends up after moving it.
+fn move_from(
+    segments: &[Segment],
+    pos: usize,
+    direction: &Direction,
+    granularity: &Granularity,
+) -> usize {
+    // The segments we would cross, in the order we would cross them.
+    let crossed: Box<dyn Iterator<Item = &Segment>> = match direction {
+        Direction::Forwards => {
+            Box::new(segments.iter().filter(move |seg| seg.start >= pos))
+        }
+        Direction::Backwards => {
+            Box::new(segments.iter().rev().filter(move |seg| seg.end <= pos))
+        }
+    };
+    let far_edge = |seg: &Segment| match direction {
+        Direction::Forwards => seg.end,
+        Direction::Backwards => seg.start,
+    };
+    let near_edge = |seg: &Segment| match direction {
+        Direction::Forwards => seg.start,
+        Direction::Backwards => seg.end,
+    };
+
+    let mut crossed = crossed.peekable();
+    match granularity {
+        Granularity::Character => crossed.next().map_or(pos, far_edge),
+        Granularity::Word => {
+            let mut new_pos = pos;
+            while let Some(seg) = crossed.next_if(|seg| seg.unit.is_space()) {
+                new_pos = far_edge(seg);
+            }
+            if let Some(first) = crossed.next() {
+                new_pos = far_edge(first);
+                while let Some(seg) = crossed
+                    .next_if(|seg| seg.unit.continues_run_of(&first.unit))
+                {
+                    new_pos = far_edge(seg);
+                }
+            }
+            new_pos
+        }
+        Granularity::Line | Granularity::Paragraph => {
+            let ends_line = |unit: &Unit| match granularity {
+                Granularity::Line => {
+                    matches!(unit, Unit::LineBreak | Unit::BlockBoundary)
+                }
+                _ => *unit == Unit::BlockBoundary,
+            };
+            let mut new_pos = pos;
+            for seg in crossed {
+                if ends_line(&seg.unit) {
+                    return near_edge(seg);
+                }
+                new_pos = far_edge(seg);
+            }
+            new_pos
+        }
+        Granularity::Document => match direction {
+            Direction::Forwards => segments.last().map_or(pos, |seg| seg.end),
+            Direction::Backwards => 0,
+        },
+    }
+}
@@ -2,7 +2,7 @@ use crate::dom::nodes::dom_node::DomNodeKind::{
     Generic, Link, List, ListItem, Paragraph,
 };
 use crate::dom::{Dom, DomLocation};
-use crate::{ComposerModel, ComposerUpdate, DomNode, UnicodeString};
+use crate::{ComposerModel, ComposerUpdate, DomNode, Location, UnicodeString};
 
 impl<S> ComposerModel<S>
 where
@@ -16,6 +16,19 @@ where
 
     pub(crate) fn do_enter(&mut self) -> ComposerUpdate<S> {
         let (s, e) = self.safe_selection();
+        // Table cells hold a single line
+        if self.table_cell_at(s).is_some()
+            && !self.range_crosses_table_cells(s, e)
+        {
+            return ComposerUpdate::keep();
+        }
+        // Nothing can be split next to a horizontal rule, open a new
+        // paragraph on that side of it instead
+        if s == e && self.insert_paragraph_at_rule(s).is_some() {
+            self.state.start = Location::from(s + 1);
+            self.state.end = self.state.start;
+            return self.create_update_replace_all();
+        }
         let range = self.state.dom.find_range(s, e);
 
         // No selection, add two paragraphs to the Dom and exit:
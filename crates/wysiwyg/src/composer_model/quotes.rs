@@ -240,7 +240,7 @@ mod test {
         model.quote();
         assert_eq!(
             tx(&model),
-            "<blockquote><p><b>Some |text</b></p></blockquote><b><br />Next line</b>"
+            "<blockquote><p><b>Some |text</b></p></blockquote><p><b><br />Next line</b></p>"
         )
     }
 
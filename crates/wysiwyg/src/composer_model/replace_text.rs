@@ -29,18 +29,30 @@ where
     /// Replaces text in the current selection with new_text.
     /// Treats its input as plain text, so any HTML code will show up in
     /// the document (i.e. it will be escaped).
+    ///
+    /// If a content limit is set, the text may be truncated or rejected,
+    /// as reported in the limit_result of the returned update.
     pub fn replace_text(&mut self, new_text: S) -> ComposerUpdate<S> {
+        if let Some(limit) = self.content_limit {
+            let (s, e) = self.safe_selection();
+            return self.replace_text_in_within_limit(new_text, s, e, limit);
+        }
         self.push_state_to_history();
         self.do_replace_text(new_text)
     }
 
     /// Replaces text in the an arbitrary start..end range with new_text.
+    /// Like [Self::replace_text], respects any content limit.
     pub fn replace_text_in(
         &mut self,
         new_text: S,
         start: usize,
         end: usize,
     ) -> ComposerUpdate<S> {
+        if let Some(limit) = self.content_limit {
+            return self
+                .replace_text_in_within_limit(new_text, start, end, limit);
+        }
         self.push_state_to_history();
         self.do_replace_text_in(new_text, start, end)
     }
@@ -196,6 +208,23 @@ where
         start: usize,
         end: usize,
     ) -> ComposerUpdate<S> {
+        // Table cells can't be merged with each other or with the blocks
+        // around their table, so only what is inside the range is removed
+        // and the new text goes where it started
+        if self.range_crosses_table_cells(start, end) {
+            self.remove_across_table_cells(start, end);
+            return self.do_replace_text_in(new_text, start, start);
+        }
+        // Text can't be placed next to a horizontal rule, only in a new
+        // paragraph before or after it, or where a removed rule was
+        if start == end && !new_text.is_empty() {
+            if let Some(position) = self.insert_paragraph_at_rule(start) {
+                return self.do_replace_text_in(new_text, position, position);
+            }
+        } else if !new_text.is_empty() && self.range_contains_rule(start, end) {
+            self.state.dom.replace_text_in(S::default(), start, end);
+            return self.do_replace_text_in(new_text, start, start);
+        }
         let text_string = new_text.to_string();
         // If passed start, end don't match the model's state, we can't fix them
         let (s, e) = self.safe_selection();
@@ -245,7 +274,8 @@ mod test {
     use crate::tests::testutils_composer_model::cm;
     use crate::tests::testutils_conversion::utf16;
     use crate::{
-        ComposerAction, ComposerUpdate, Location, MenuAction, MenuState,
+        ComposerAction, ComposerUpdate, ContextState, Location, MenuAction,
+        MenuState,
     };
     use strum::IntoEnumIterator;
 
@@ -260,20 +290,24 @@ mod test {
                 Location::from(1),
                 Location::from(1),
                 MenuState::Update(MenuStateUpdate {
-                    action_states: indent_unindent_redo_disabled()
+                    action_states:
+                        indent_unindent_redo_clear_formatting_disabled(),
+                    context_state: Some(ContextState::default()),
                 }),
                 MenuAction::None,
             ),
         );
     }
 
-    fn indent_unindent_redo_disabled() -> HashMap<ComposerAction, ActionState> {
+    fn indent_unindent_redo_clear_formatting_disabled(
+    ) -> HashMap<ComposerAction, ActionState> {
         let actions = ComposerAction::iter().map(|action| {
             if matches!(
                 action,
                 ComposerAction::Redo
                     | ComposerAction::Indent
                     | ComposerAction::Unindent
+                    | ComposerAction::ClearFormatting
             ) {
                 (action, ActionState::Disabled)
             } else {
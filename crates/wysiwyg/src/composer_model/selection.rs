@@ -28,6 +28,7 @@ where
         if self.state.start == start && self.state.end == end {
             return ComposerUpdate::keep();
         }
+        self.commit_pending_composition();
         self.state.toggled_format_types.clear();
         self.state.start = start;
         self.state.end = end;
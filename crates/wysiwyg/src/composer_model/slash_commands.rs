@@ -0,0 +1,183 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::DomNode;
+use crate::dom::unicode_string::UnicodeStrExt;
+use crate::{
+    ComposerModel, MentionNodeKind, ParsedCommand, PatternKey, SlashCommand,
+    SuggestionPattern, UnicodeString,
+};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Set the commands known to [Self::commands_for_suggestion] and
+    /// [Self::parse_command].
+    pub fn set_slash_commands(&mut self, commands: Vec<SlashCommand>) {
+        self.slash_commands = commands;
+    }
+
+    /// Add a command to [Self::slash_commands], replacing any existing
+    /// command with the same name.
+    pub fn add_slash_command(&mut self, command: SlashCommand) {
+        self.slash_commands.retain(|c| c.name != command.name);
+        self.slash_commands.push(command);
+    }
+
+    pub fn slash_commands(&self) -> &[SlashCommand] {
+        &self.slash_commands
+    }
+
+    /// The registered commands whose name starts with the text of a `/`
+    /// suggestion. Other suggestions don't match any command.
+    pub fn commands_for_suggestion(
+        &self,
+        suggestion: &SuggestionPattern,
+    ) -> Vec<SlashCommand> {
+        if suggestion.key != PatternKey::Slash {
+            return Vec::new();
+        }
+        self.slash_commands
+            .iter()
+            .filter(|c| c.name.starts_with(&suggestion.text))
+            .cloned()
+            .collect()
+    }
+
+    /// If the content begins with a registered command, read its name and
+    /// arguments. The arguments are taken from the first line. A mention
+    /// counts as one argument, read as its URL, or as its display text if
+    /// it has none (e.g. `@room`).
+    pub fn parse_command(&self) -> Option<ParsedCommand> {
+        let range = self.state.dom.find_range(0, 0);
+        if range.locations.iter().any(|l| l.kind.is_code_kind()) {
+            return None;
+        }
+        let (words, line_end) = self.first_line_words()?;
+        let mut words = words.into_iter().peekable();
+        let name_word = words.next()?;
+        if name_word.start != 0 {
+            return None;
+        }
+        let name = name_word.text.strip_prefix('/')?;
+        let command = self.slash_commands.iter().find(|c| c.name == name)?;
+
+        let args = words
+            .by_ref()
+            .take(command.args.len())
+            .map(|word| word.text)
+            .collect();
+
+        let remainder_html = command.takes_remainder.then(|| {
+            let start = words.peek().map_or(line_end, |word| word.start);
+            self.message_html_after(start)
+        });
+        Some(ParsedCommand {
+            name: command.name.clone(),
+            args,
+            remainder_html,
+        })
+    }
+
+    /// The whitespace separated words of the first line of the content,
+    /// and the position where the line ends. None if the content doesn't
+    /// start with text.
+    fn first_line_words(&self) -> Option<(Vec<Word>, usize)> {
+        let dom = &self.state.dom;
+        let range = dom.find_range(0, dom.text_len());
+        let first = range.leaves().next()?;
+        if first.position != 0
+            || !dom.lookup_node(&first.node_handle).is_text_node()
+        {
+            return None;
+        }
+        let block = range
+            .deepest_block_node(Some(&first.node_handle))
+            .map_or_else(|| dom.document_handle(), |b| b.node_handle.clone());
+
+        let mut words = Vec::new();
+        let mut current: Option<Word> = None;
+        let mut line_end = 0;
+        for loc in range
+            .leaves()
+            .filter(|l| block.is_ancestor_of(&l.node_handle))
+        {
+            match dom.lookup_node(&loc.node_handle) {
+                DomNode::Text(text) => {
+                    let data = text.data().to_string();
+                    let mut position = loc.position;
+                    for c in data.chars() {
+                        let start = position;
+                        position += S::from(&*c.encode_utf8(&mut [0; 4])).len();
+                        if c.is_whitespace() {
+                            words.extend(current.take());
+                        } else {
+                            current
+                                .get_or_insert_with(|| Word::empty_at(start))
+                                .push(c, position);
+                        }
+                    }
+                }
+                DomNode::Mention(mention) => {
+                    words.extend(current.take());
+                    let text = match mention.kind() {
+                        MentionNodeKind::MatrixUrl { url, .. } => url.clone(),
+                        MentionNodeKind::AtRoom => mention.display_text(),
+                    };
+                    words.push(Word {
+                        text: text.to_string(),
+                        start: loc.position,
+                        end: loc.position + loc.length,
+                    });
+                }
+                // Anything else, like a line break, ends the line
+                _ => break,
+            }
+            line_end = loc.position + loc.length;
+        }
+        words.extend(current);
+        Some((words, line_end))
+    }
+
+    /// The message HTML of the content after [location].
+    fn message_html_after(&self, location: usize) -> String {
+        let mut model = Self::from_state(self.state.clone());
+        model.do_replace_text_in(S::default(), 0, location);
+        model.get_content_as_message_html().to_string()
+    }
+}
+
+/// A word of the first line of the content, see
+/// [ComposerModel::parse_command].
+struct Word {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+impl Word {
+    fn empty_at(position: usize) -> Self {
+        Self {
+            text: String::new(),
+            start: position,
+            end: position,
+        }
+    }
+
+    fn push(&mut self, c: char, end: usize) {
+        self.text.push(c);
+        self.end = end;
+    }
+}
@@ -0,0 +1,434 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::dom_node::DomNodeKind;
+use crate::dom::nodes::DomNode;
+use crate::{
+    ComposerModel, ComposerUpdate, DomHandle, Location, UnicodeString,
+};
+
+impl<S> ComposerModel<S>
+where
+    S: UnicodeString,
+{
+    /// Insert an empty table of `rows` by `columns` cells at the cursor,
+    /// splitting the current paragraph around it, and move the cursor to
+    /// its first cell. If there are several rows, the first one is made of
+    /// header cells. Tables can't be added inside lists, quotes, code
+    /// blocks or other tables.
+    pub fn insert_table(
+        &mut self,
+        rows: usize,
+        columns: usize,
+    ) -> ComposerUpdate<S> {
+        if rows == 0 || columns == 0 {
+            return ComposerUpdate::keep();
+        }
+        if self.selection_is_in_nested_block() {
+            return ComposerUpdate::keep();
+        }
+
+        self.push_state_to_history();
+        // Split the text at the cursor, the table goes between both halves
+        self.do_enter();
+        let (s, _) = self.safe_selection();
+        let range = self.state.dom.find_range(s, s);
+        let Some(paragraph) = range.deepest_block_node(None) else {
+            return self.create_update_replace_all();
+        };
+        let mut table_handle = paragraph.node_handle.clone();
+        let table = DomNode::new_table(
+            (0..rows)
+                .map(|i| Self::new_table_row(columns, i == 0 && rows > 1))
+                .collect(),
+        );
+        self.state.dom.insert_at(&table_handle, table);
+        if table_handle.index_in_parent() > 0 {
+            let previous = table_handle.prev_sibling();
+            let previous_node = self.state.dom.lookup_node(&previous);
+            if previous_node.kind() == DomNodeKind::Paragraph
+                && previous_node.has_no_text()
+            {
+                self.state.dom.remove(&previous);
+                table_handle = previous;
+            }
+        }
+        let start = self.position_of(&table_handle);
+        self.state.start = Location::from(start);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+
+    /// Add an empty row below the one the cursor is in.
+    pub fn add_table_row(&mut self) -> ComposerUpdate<S> {
+        let Some(cell) = self.table_cell_at_cursor() else {
+            return ComposerUpdate::keep();
+        };
+        self.push_state_to_history();
+        let row = cell.parent_handle();
+        self.insert_table_row_after(&row);
+        self.create_update_replace_all()
+    }
+
+    /// Remove the row the cursor is in, and the whole table if it was its
+    /// only row. The cursor moves to the start of the row taking its place.
+    pub fn remove_table_row(&mut self) -> ComposerUpdate<S> {
+        let Some(cell) = self.table_cell_at_cursor() else {
+            return ComposerUpdate::keep();
+        };
+        self.push_state_to_history();
+        let row = cell.parent_handle();
+        let table = row.parent_handle();
+        let row_count =
+            self.state.dom.lookup_container(&table).children().len();
+        if row_count == 1 {
+            return self.remove_table(&table);
+        }
+        self.state.dom.remove(&row);
+        let next_row = if row.index_in_parent() < row_count - 1 {
+            row
+        } else {
+            row.prev_sibling()
+        };
+        self.move_to_cell_start(&next_row.child_handle(0));
+        self.create_update_replace_all()
+    }
+
+    /// Add an empty column to the right of the one the cursor is in.
+    pub fn add_table_column(&mut self) -> ComposerUpdate<S> {
+        let Some(cell) = self.table_cell_at_cursor() else {
+            return ComposerUpdate::keep();
+        };
+        self.push_state_to_history();
+        let (s, _) = self.safe_selection();
+        let offset = s - self.position_of(&cell);
+        let column = cell.index_in_parent();
+        let table = cell.parent_handle().parent_handle();
+        for row in self.table_rows(&table) {
+            let is_header =
+                self.state.dom.lookup_container(&row).is_table_header_row();
+            self.state.dom.insert_at(
+                &row.child_handle(column + 1),
+                DomNode::new_table_cell(is_header, Vec::new()),
+            );
+        }
+        let start = self.position_of(&cell) + offset;
+        self.state.start = Location::from(start);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+
+    /// Remove the column the cursor is in, and the whole table if it was
+    /// its only column. The cursor moves to the start of the cell taking
+    /// its place.
+    pub fn remove_table_column(&mut self) -> ComposerUpdate<S> {
+        let Some(cell) = self.table_cell_at_cursor() else {
+            return ComposerUpdate::keep();
+        };
+        self.push_state_to_history();
+        let column = cell.index_in_parent();
+        let row = cell.parent_handle();
+        let table = row.parent_handle();
+        let column_count =
+            self.state.dom.lookup_container(&row).children().len();
+        if column_count == 1 {
+            return self.remove_table(&table);
+        }
+        for row in self.table_rows(&table) {
+            self.state.dom.remove(&row.child_handle(column));
+        }
+        let next_cell = if column < column_count - 1 {
+            cell
+        } else {
+            cell.prev_sibling()
+        };
+        self.move_to_cell_start(&next_cell);
+        self.create_update_replace_all()
+    }
+
+    /// Select the contents of the next cell of the table, as when pressing
+    /// Tab. From the last cell, a new row is added first. Does nothing
+    /// outside of tables, so that platforms can handle Tab themselves.
+    pub fn next_table_cell(&mut self) -> ComposerUpdate<S> {
+        let Some(cell) = self.table_cell_at_cursor() else {
+            return ComposerUpdate::keep();
+        };
+        let row = cell.parent_handle();
+        let table = row.parent_handle();
+        let column_count =
+            self.state.dom.lookup_container(&row).children().len();
+        let row_count =
+            self.state.dom.lookup_container(&table).children().len();
+        if cell.index_in_parent() < column_count - 1 {
+            self.select_cell(&cell.next_sibling())
+        } else if row.index_in_parent() < row_count - 1 {
+            self.select_cell(&row.next_sibling().child_handle(0))
+        } else {
+            self.push_state_to_history();
+            self.insert_table_row_after(&row);
+            self.move_to_cell_start(&row.next_sibling().child_handle(0));
+            self.create_update_replace_all()
+        }
+    }
+
+    /// Select the contents of the previous cell of the table, as when
+    /// pressing Shift+Tab. Does nothing in the first cell or outside of
+    /// tables.
+    pub fn previous_table_cell(&mut self) -> ComposerUpdate<S> {
+        let Some(cell) = self.table_cell_at_cursor() else {
+            return ComposerUpdate::keep();
+        };
+        let row = cell.parent_handle();
+        if cell.index_in_parent() > 0 {
+            self.select_cell(&cell.prev_sibling())
+        } else if row.index_in_parent() > 0 {
+            let previous_row = row.prev_sibling();
+            let last = self
+                .state
+                .dom
+                .lookup_container(&previous_row)
+                .children()
+                .len()
+                - 1;
+            self.select_cell(&previous_row.child_handle(last))
+        } else {
+            ComposerUpdate::keep()
+        }
+    }
+
+    /// The deepest table cell containing [position], if any. As cells are
+    /// block nodes, two of them never share a position.
+    pub(crate) fn table_cell_at(&self, position: usize) -> Option<DomHandle> {
+        let range = self.state.dom.find_range(position, position);
+        range
+            .locations
+            .iter()
+            // The location of a block also covers the position after it,
+            // which belongs to the next block
+            .filter(|l| {
+                l.kind == DomNodeKind::TableCell
+                    && l.start_offset
+                        <= self.state.dom.lookup_node(&l.node_handle).text_len()
+            })
+            .map(|l| l.node_handle.clone())
+            .max_by_key(|h| h.depth())
+    }
+
+    /// True if the selection is inside a list, quote, code block or table,
+    /// where no other block structures can be added.
+    pub(crate) fn selection_is_in_nested_block(&self) -> bool {
+        let (s, e) = self.safe_selection();
+        let range = self.state.dom.find_range(s, e);
+        let Some(block) = range.deepest_block_node(None) else {
+            return false;
+        };
+        block.node_handle.with_ancestors().iter().any(|h| {
+            matches!(
+                self.state.dom.lookup_node(h).kind(),
+                DomNodeKind::ListItem
+                    | DomNodeKind::Quote
+                    | DomNodeKind::CodeBlock
+                    | DomNodeKind::TableCell
+            )
+        })
+    }
+
+    /// True if replacing start..end would merge a table cell with another
+    /// cell, or with a block outside of its table.
+    pub(crate) fn range_crosses_table_cells(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> bool {
+        start != end && self.table_cell_at(start) != self.table_cell_at(end)
+    }
+
+    /// Remove what start..end covers without merging table cells with each
+    /// other or with the blocks around their table. Tables the range covers
+    /// entirely are removed, in the others only the contents of the cells
+    /// inside the range are. Afterwards, the selection is collapsed to
+    /// [start] if it was start..end.
+    pub(crate) fn remove_across_table_cells(
+        &mut self,
+        start: usize,
+        end: usize,
+    ) {
+        let (s, e) = self.safe_selection();
+        let mut removals = Vec::new();
+        let mut cursor = start;
+        for table in self.tables_in(start, end) {
+            let cells = self.table_cells(&table);
+            let (Some(first), Some(last)) = (cells.first(), cells.last())
+            else {
+                continue;
+            };
+            let table_start = self.position_of(first);
+            let table_end = self.position_of(last)
+                + self.state.dom.lookup_node(last).text_len();
+            // Up to the end of the block before the table
+            if cursor + 1 < table_start {
+                removals.push(TableRemoval::Text(cursor, table_start - 1));
+            }
+            if start <= table_start && table_end <= end {
+                removals.push(TableRemoval::Table(table));
+            } else {
+                for cell in cells {
+                    let cell_start = self.position_of(&cell);
+                    let cell_end = cell_start
+                        + self.state.dom.lookup_node(&cell).text_len();
+                    let (from, to) = (start.max(cell_start), end.min(cell_end));
+                    if from < to {
+                        removals.push(TableRemoval::Text(from, to));
+                    }
+                }
+            }
+            // From the start of the block after the table
+            cursor = table_end + 1;
+        }
+        if cursor < end {
+            removals.push(TableRemoval::Text(cursor, end));
+        }
+
+        // Later removals first, so that earlier positions and handles
+        // stay valid
+        for removal in removals.into_iter().rev() {
+            match removal {
+                TableRemoval::Table(table) => {
+                    self.state.dom.remove(&table);
+                }
+                TableRemoval::Text(from, to) => {
+                    self.state.dom.replace_text_in(S::default(), from, to);
+                }
+            }
+        }
+        if s == start && e == end {
+            self.state.start = Location::from(start);
+            self.state.end = self.state.start;
+        }
+    }
+
+    /// The tables overlapping start..end, in document order.
+    fn tables_in(&self, start: usize, end: usize) -> Vec<DomHandle> {
+        let range = self.state.dom.find_range(start, end);
+        let mut tables: Vec<DomHandle> = range
+            .locations
+            .iter()
+            .filter(|l| l.kind == DomNodeKind::Table)
+            .map(|l| l.node_handle.clone())
+            .collect();
+        tables.sort();
+        tables.retain(|table| {
+            let cells = self.table_cells(table);
+            match (cells.first(), cells.last()) {
+                (Some(first), Some(last)) => {
+                    self.position_of(first) <= end
+                        && start
+                            <= self.position_of(last)
+                                + self.state.dom.lookup_node(last).text_len()
+                }
+                _ => false,
+            }
+        });
+        tables
+    }
+
+    /// The cell containing the start of the selection, if the whole
+    /// selection is inside it.
+    fn table_cell_at_cursor(&self) -> Option<DomHandle> {
+        let (s, e) = self.safe_selection();
+        if self.range_crosses_table_cells(s, e) {
+            return None;
+        }
+        self.table_cell_at(s)
+    }
+
+    fn new_table_row(columns: usize, is_header: bool) -> DomNode<S> {
+        DomNode::new_table_row(
+            (0..columns)
+                .map(|_| DomNode::new_table_cell(is_header, Vec::new()))
+                .collect(),
+        )
+    }
+
+    fn insert_table_row_after(&mut self, row: &DomHandle) {
+        let columns = self.state.dom.lookup_container(row).children().len();
+        self.state.dom.insert_at(
+            &row.next_sibling(),
+            Self::new_table_row(columns, false),
+        );
+    }
+
+    fn remove_table(&mut self, table: &DomHandle) -> ComposerUpdate<S> {
+        let start = self.position_of(table);
+        self.state.dom.remove(table);
+        self.state.start = Location::from(start);
+        self.state.end = self.state.start;
+        self.create_update_replace_all()
+    }
+
+    fn table_cells(&self, table: &DomHandle) -> Vec<DomHandle> {
+        self.table_rows(table)
+            .iter()
+            .flat_map(|row| {
+                self.state
+                    .dom
+                    .lookup_container(row)
+                    .children()
+                    .iter()
+                    .map(DomNode::handle)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn table_rows(&self, table: &DomHandle) -> Vec<DomHandle> {
+        self.state
+            .dom
+            .lookup_container(table)
+            .children()
+            .iter()
+            .map(DomNode::handle)
+            .collect()
+    }
+
+    fn move_to_cell_start(&mut self, cell: &DomHandle) {
+        let start = self.position_of(cell);
+        self.state.start = Location::from(start);
+        self.state.end = self.state.start;
+    }
+
+    fn select_cell(&mut self, cell: &DomHandle) -> ComposerUpdate<S> {
+        let start = self.position_of(cell);
+        let end = start + self.state.dom.lookup_node(cell).text_len();
+        self.select(Location::from(start), Location::from(end))
+    }
+
+    /// The position where the node at [handle] starts.
+    pub(crate) fn position_of(&self, handle: &DomHandle) -> usize {
+        let range = self.state.dom.find_range(0, self.state.dom.text_len());
+        range
+            .find_location(handle)
+            .map(|l| l.position)
+            .unwrap_or_default()
+    }
+}
+
+/// Something removed by [ComposerModel::remove_across_table_cells]
+enum TableRemoval {
+    /// A whole table
+    Table(DomHandle),
+    /// The text between two positions, inside a single cell or outside of
+    /// any table
+    Text(usize, usize),
+}
@@ -19,26 +19,29 @@ where
     S: UnicodeString,
 {
     pub fn undo(&mut self) -> ComposerUpdate<S> {
+        self.commit_pending_composition();
         if let Some(prev) = self.previous_states.pop() {
             self.next_states.push(self.state.clone());
             self.state = prev;
-            self.create_update_replace_all()
+            self.create_update_replace_all_unchanged()
         } else {
             ComposerUpdate::keep()
         }
     }
 
     pub fn redo(&mut self) -> ComposerUpdate<S> {
+        self.commit_pending_composition();
         if let Some(next) = self.next_states.pop() {
             self.previous_states.push(self.state.clone());
             self.state = next;
-            self.create_update_replace_all()
+            self.create_update_replace_all_unchanged()
         } else {
             ComposerUpdate::keep()
         }
     }
 
     pub(crate) fn push_state_to_history(&mut self) {
+        self.commit_pending_composition();
         // Clear future events as they're no longer valid
         self.next_states.clear();
         // Store a copy of the current state in the previous_states
@@ -24,6 +24,12 @@ where
     pub start: Location,
     pub end: Location,
     pub toggled_format_types: Vec<InlineFormatType>,
+    /// The range of the text being composed by the keyboard (e.g. an IME),
+    /// if any
+    pub composition: Option<(Location, Location)>,
+    /// Whether the content is Markdown typed as plain text, one line per
+    /// paragraph, instead of rich text
+    pub markdown_mode: bool,
 }
 
 impl<S> ComposerState<S>
@@ -36,6 +42,8 @@ where
             start: Location::default(),
             end: Location::default(),
             toggled_format_types: Vec::new(),
+            composition: None,
+            markdown_mode: false,
         }
     }
 
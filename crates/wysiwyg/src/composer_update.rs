@@ -14,7 +14,8 @@
 
 use crate::dom::UnicodeString;
 use crate::{
-    Location, MenuAction, MenuState, ReplaceAll, Selection, TextUpdate,
+    LimitResult, Location, MenuAction, MenuState, ReplaceAll, Selection,
+    TextUpdate,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,8 +24,11 @@ where
     S: UnicodeString,
 {
     pub text_update: TextUpdate<S>,
-    pub menu_state: MenuState,
+    pub menu_state: MenuState<S>,
     pub menu_action: MenuAction,
+    /// Whether the configured content limit stopped some text from being
+    /// inserted
+    pub limit_result: LimitResult,
 }
 
 impl<S> ComposerUpdate<S>
@@ -36,30 +40,33 @@ where
             text_update: TextUpdate::<S>::Keep,
             menu_state: MenuState::Keep,
             menu_action: MenuAction::Keep,
+            limit_result: LimitResult::Applied,
         }
     }
 
     pub fn update_menu_state(
-        menu_state: MenuState,
+        menu_state: MenuState<S>,
         menu_action: MenuAction,
     ) -> Self {
         Self {
             text_update: TextUpdate::<S>::Keep,
             menu_state,
             menu_action,
+            limit_result: LimitResult::Applied,
         }
     }
 
     pub fn update_selection(
         start: Location,
         end: Location,
-        menu_state: MenuState,
+        menu_state: MenuState<S>,
         menu_action: MenuAction,
     ) -> Self {
         Self {
             text_update: TextUpdate::<S>::Select(Selection { start, end }),
             menu_state,
             menu_action,
+            limit_result: LimitResult::Applied,
         }
     }
 
@@ -67,7 +74,7 @@ where
         replacement_html: S,
         start: Location,
         end: Location,
-        menu_state: MenuState,
+        menu_state: MenuState<S>,
         menu_action: MenuAction,
     ) -> Self {
         Self {
@@ -78,6 +85,15 @@ where
             }),
             menu_state,
             menu_action,
+            limit_result: LimitResult::Applied,
         }
     }
+
+    pub(crate) fn with_limit_result(
+        mut self,
+        limit_result: LimitResult,
+    ) -> Self {
+        self.limit_result = limit_result;
+        self
+    }
 }
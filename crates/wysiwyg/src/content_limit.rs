@@ -0,0 +1,75 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Sizes of the content of the composer, as it would be sent in a message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContentMetrics {
+    /// User-perceived characters in the plain text body.
+    pub graphemes: usize,
+    /// Code units of the plain text body, in the model's encoding.
+    pub code_units: usize,
+    /// UTF-8 bytes of the HTML returned by `get_content_as_message_html`.
+    pub message_html_bytes: usize,
+    /// UTF-8 bytes of the plain text body.
+    pub plain_text_bytes: usize,
+}
+
+impl ContentMetrics {
+    pub fn get(&self, unit: LimitUnit) -> usize {
+        match unit {
+            LimitUnit::Graphemes => self.graphemes,
+            LimitUnit::CodeUnits => self.code_units,
+            LimitUnit::MessageHtmlBytes => self.message_html_bytes,
+            LimitUnit::PlainTextBytes => self.plain_text_bytes,
+        }
+    }
+}
+
+/// A maximum size for the content, checked when text is typed or pasted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLimit {
+    pub unit: LimitUnit,
+    pub max: usize,
+    pub behaviour: LimitBehaviour,
+}
+
+/// Which of the [ContentMetrics] a [ContentLimit] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitUnit {
+    Graphemes,
+    CodeUnits,
+    MessageHtmlBytes,
+    PlainTextBytes,
+}
+
+/// What to do with new text that would make the content exceed its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitBehaviour {
+    /// Insert as much of the text as fits.
+    Truncate,
+    /// Don't insert any of the text.
+    Reject,
+}
+
+/// Whether a content limit changed what an update did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LimitResult {
+    /// The update was applied in full.
+    #[default]
+    Applied,
+    /// Only part of the new text was inserted.
+    Truncated,
+    /// None of the new text was inserted.
+    Rejected,
+}
@@ -0,0 +1,53 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::UnicodeString;
+
+/// Information about where the selection currently is in the document,
+/// to help platforms draw their toolbars.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextState<S>
+where
+    S: UnicodeString,
+{
+    /// The block the start of the selection is in.
+    pub block_type: BlockType,
+    /// How many lists the start of the selection is nested in, 0 if it is
+    /// not inside a list.
+    pub list_depth: usize,
+    /// The url of the link under the cursor, if any.
+    pub link_url: Option<S>,
+    /// True if the cursor is inside a mention, or the selection covers one.
+    pub in_mention: bool,
+    /// The text colour under the cursor, as a `#rrggbb` hex string.
+    pub text_color: Option<S>,
+    /// The background colour under the cursor, as a `#rrggbb` hex string.
+    pub background_color: Option<S>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BlockType {
+    #[default]
+    Paragraph,
+    Quote,
+    CodeBlock,
+    ListItem,
+    TableCell,
+    /// An `<h1>` to `<h6>` heading, with its level from 1 to 6.
+    Heading {
+        level: u8,
+    },
+    /// Any other block we keep without editing it, like a `<div>`.
+    Other,
+}
@@ -27,6 +27,7 @@ pub mod insert_parent;
 pub mod iter;
 pub mod join_nodes;
 pub mod nodes;
+pub mod normalize;
 pub mod parser;
 pub mod range;
 pub mod to_html;
@@ -39,7 +40,9 @@ pub mod unicode_string;
 pub use dom_creation_error::DomCreationError;
 pub use dom_creation_error::HtmlParseError;
 pub use dom_creation_error::MarkdownParseError;
+pub use dom_creation_error::UnknownTagError;
 pub use dom_handle::DomHandle;
+pub use dom_invariants::InvariantViolation;
 pub use dom_struct::Dom;
 pub use find_result::FindResult;
 pub use range::DomLocation;
@@ -50,3 +50,17 @@ impl fmt::Display for MarkdownParseError {
         write!(f, "{message}")
     }
 }
+
+/// A tag name that doesn't correspond to the requested kind of node.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownTagError {
+    pub tag: String,
+}
+
+impl std::error::Error for UnknownTagError {}
+
+impl fmt::Display for UnknownTagError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown tag `{}`", self.tag)
+    }
+}
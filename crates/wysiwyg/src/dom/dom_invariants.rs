@@ -12,10 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Assertions that guarantee the Dom is in a known state.
+//! Checks that guarantee the Dom is in a known state.
 //!
 //! To see the full list of the invariants we enforce, look at the
-//! assert_invariants method.
+//! [InvariantViolation] enum. [Dom::validate] reports every place where the
+//! Dom breaks them, and [Dom::normalize] repairs them.
 //!
 //! In future, every public method on Dom should call assert_invariants at the
 //! beginning and the end of the method. This will allow us to find places
@@ -29,14 +30,107 @@
 //! "assert-invariants".
 //!
 //! TODO: build the demo app with these assertions enabled
-//! TODO: add more assertions - see the code of assert_invariants for ideas
+//! TODO: add more invariants - see the code of validate for ideas
 
-#[cfg(any(test, feature = "assert-invariants"))]
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::dom::nodes::ContainerNodeKind;
 use crate::dom::unicode_string::UnicodeStrExt;
 use crate::dom::Dom;
-use crate::UnicodeString;
 #[cfg(any(test, feature = "assert-invariants"))]
-use crate::{DomNode, ToTree};
+use crate::ToTree;
+use crate::{DomHandle, DomNode, UnicodeString};
+
+/// A place where a [Dom] breaks one of the invariants the rest of the code
+/// relies on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// A text node with no text in it.
+    EmptyTextNode(DomHandle),
+    /// A text node that is immediately followed by another text node.
+    AdjacentTextNodes(DomHandle),
+    /// A formatting or link node with no children, inside a block node that
+    /// has some text. (Empty blocks keep them to remember their formatting.)
+    EmptyInlineContainer(DomHandle),
+    /// A generic container that is not the root node.
+    MultipleGenericContainers(DomHandle),
+    /// A container whose children mix inline and block nodes.
+    MixedBlockAndInlineNodes(DomHandle),
+    /// A list item whose parent is not a list.
+    ListItemOutsideList(DomHandle),
+    /// A child of a list that is not a list item.
+    NonListItemInList(DomHandle),
+    /// A table or row that is empty, or contains something other than rows
+    /// or cells respectively.
+    MalformedTable(DomHandle),
+    /// A table row or cell that is not inside a table or row.
+    TablePartOutsideTable(DomHandle),
+    /// A table whose rows have different numbers of cells.
+    RaggedTable(DomHandle),
+}
+
+impl InvariantViolation {
+    /// The node that breaks the invariant.
+    pub fn handle(&self) -> &DomHandle {
+        match self {
+            Self::EmptyTextNode(handle)
+            | Self::AdjacentTextNodes(handle)
+            | Self::EmptyInlineContainer(handle)
+            | Self::MultipleGenericContainers(handle)
+            | Self::MixedBlockAndInlineNodes(handle)
+            | Self::ListItemOutsideList(handle)
+            | Self::NonListItemInList(handle)
+            | Self::MalformedTable(handle)
+            | Self::TablePartOutsideTable(handle)
+            | Self::RaggedTable(handle) => handle,
+        }
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyTextNode(handle) => {
+                write!(f, "Empty text node found! handle: {handle:?}")
+            }
+            Self::AdjacentTextNodes(handle) => {
+                write!(f, "Adjacent text nodes found! handle: {handle:?}")
+            }
+            Self::EmptyInlineContainer(handle) => write!(
+                f,
+                "Empty formatting or link node found! handle: {handle:?}"
+            ),
+            Self::MultipleGenericContainers(handle) => write!(
+                f,
+                "More than one generic container node found. Handle: {:?}",
+                handle.raw()
+            ),
+            Self::MixedBlockAndInlineNodes(handle) => write!(
+                f,
+                "All child nodes of handle {handle:?} must be either inline nodes or block nodes"
+            ),
+            Self::ListItemOutsideList(handle) => {
+                write!(f, "List item found outside of a list! handle: {handle:?}")
+            }
+            Self::NonListItemInList(handle) => write!(
+                f,
+                "List child that is not a list item found! handle: {handle:?}"
+            ),
+            Self::MalformedTable(handle) => {
+                write!(f, "Malformed table found! handle: {handle:?}")
+            }
+            Self::TablePartOutsideTable(handle) => write!(
+                f,
+                "Table row or cell found outside of its parent! handle: {handle:?}"
+            ),
+            Self::RaggedTable(handle) => write!(
+                f,
+                "Table rows have different numbers of cells! handle: {handle:?}"
+            ),
+        }
+    }
+}
 
 impl<S> Dom<S>
 where
@@ -58,36 +152,56 @@ where
             // as the DOM is known to be in an inconsistent state
             return;
         }
-        self.assert_no_empty_text_nodes();
-        self.assert_no_adjacent_text_nodes();
-        self.assert_exactly_one_generic_container();
-        self.assert_all_nodes_in_containers_are_block_or_inline();
+        // Some tests deliberately start from lists nested directly inside
+        // lists, or empty formatting next to text, so we leave these to
+        // normalize instead of treating them as bugs.
+        let violation = self.validate().into_iter().find(|v| {
+            !matches!(
+                v,
+                InvariantViolation::EmptyInlineContainer(_)
+                    | InvariantViolation::ListItemOutsideList(_)
+                    | InvariantViolation::NonListItemInList(_)
+            )
+        });
+        if let Some(violation) = violation {
+            panic!("{violation}\n{}", self.to_tree());
+        }
+    }
 
-        // We probably want some more asserts like these:
-        // self.assert_document_node_is_a_container();
-        // self.assert_no_empty_containers_except_at_root();
-        // self.assert_inline_code_contains_no_tags_except_line_breaks
-        // self.assert_code_blocks_do_not_contain_structure_tags
-        // self.assert_links_do_not_contain_structure_tags
-        // self.assert_links_do_not_contain_links
-        // self.assert_zero_width_spaces_are_only_in_empty_list_item_tags
+    /// Return every place where this Dom breaks our invariants, or an empty
+    /// list if it is well formed. Use [Dom::normalize] to repair them.
+    pub fn validate(&self) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+        self.find_empty_text_nodes(&mut violations);
+        self.find_adjacent_text_nodes(&mut violations);
+        self.find_empty_inline_containers(&mut violations);
+        self.find_extra_generic_containers(&mut violations);
+        self.find_mixed_block_and_inline_nodes(&mut violations);
+        self.find_misplaced_list_items(&mut violations);
+        self.find_malformed_tables(&mut violations);
+
+        // We probably want some more checks like these:
+        // self.find_inline_code_containing_tags_except_line_breaks
+        // self.find_code_blocks_containing_structure_tags
+        // self.find_links_containing_structure_tags
+        // self.find_links_containing_links
+        // self.find_zero_width_spaces_outside_empty_list_items
+        violations
     }
 
-    #[cfg(any(test, feature = "assert-invariants"))]
-    fn assert_no_empty_text_nodes(&self) {
+    fn find_empty_text_nodes(&self, violations: &mut Vec<InvariantViolation>) {
         for text in self.iter_text() {
             if text.data().is_empty() {
-                panic!(
-                    "Empty text node found! handle: {:?}\n{}",
-                    text.handle(),
-                    self.to_tree(),
-                );
+                violations
+                    .push(InvariantViolation::EmptyTextNode(text.handle()));
             }
         }
     }
 
-    #[cfg(any(test, feature = "assert-invariants"))]
-    fn assert_no_adjacent_text_nodes(&self) {
+    fn find_adjacent_text_nodes(
+        &self,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
         for node in self.iter_containers() {
             let mut prev_node: Option<&DomNode<S>> = None;
             for child in node.children() {
@@ -95,11 +209,9 @@ where
                     if let (DomNode::Text(_), DomNode::Text(_)) =
                         (prev_node, child)
                     {
-                        panic!(
-                            "Adjacent text nodes found! handle: {:?}\n{}",
+                        violations.push(InvariantViolation::AdjacentTextNodes(
                             prev_node.handle(),
-                            self.to_tree()
-                        );
+                        ));
                     }
                 }
                 prev_node = Some(child);
@@ -107,35 +219,132 @@ where
         }
     }
 
+    fn find_empty_inline_containers(
+        &self,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
+        for container in self.iter_containers() {
+            if (container.is_formatting_node() || container.is_link())
+                && container.is_empty()
+                && self.block_ancestor_has_text(&container.handle())
+            {
+                violations.push(InvariantViolation::EmptyInlineContainer(
+                    container.handle(),
+                ));
+            }
+        }
+    }
+
+    fn block_ancestor_has_text(&self, handle: &DomHandle) -> bool {
+        let mut handle = handle.clone();
+        while handle.has_parent() {
+            handle = handle.parent_handle();
+            let ancestor = self.lookup_node(&handle);
+            if ancestor.is_block_node() {
+                return !ancestor.has_no_text();
+            }
+        }
+        false
+    }
+
     /// Check there is only one generic container and that it is the root node
-    #[cfg(any(test, feature = "assert-invariants"))]
-    fn assert_exactly_one_generic_container(&self) {
-        use super::nodes::ContainerNodeKind;
-
-        let generic_nodes = self
-            .iter_containers()
-            .filter(|n| matches!(n.kind(), ContainerNodeKind::Generic));
-        let handles = generic_nodes.map(|n| n.handle()).collect::<Vec<_>>();
-
-        if handles.len() > 1 {
-            let first = handles.into_iter().find(|h| !h.is_root());
-            panic!(
-                "More than one generic container node found. Handle: {:?}\n{}",
-                first.unwrap().raw(),
-                self.to_tree()
-            );
+    fn find_extra_generic_containers(
+        &self,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
+        for container in self.iter_containers() {
+            if matches!(container.kind(), ContainerNodeKind::Generic)
+                && !container.handle().is_root()
+            {
+                violations.push(InvariantViolation::MultipleGenericContainers(
+                    container.handle(),
+                ));
+            }
         }
     }
 
-    #[cfg(any(test, feature = "assert-invariants"))]
-    fn assert_all_nodes_in_containers_are_block_or_inline(&self) {
+    fn find_mixed_block_and_inline_nodes(
+        &self,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
         for container in self.iter_containers() {
             let all_nodes_are_inline =
                 container.children().iter().all(|n| !n.is_block_node());
             let all_nodes_are_block =
                 container.children().iter().all(|n| n.is_block_node());
             if !all_nodes_are_inline && !all_nodes_are_block {
-                panic!("All child nodes of handle {:?} must be either inline nodes or block nodes:\n{}", container.handle(), container.to_tree());
+                violations.push(InvariantViolation::MixedBlockAndInlineNodes(
+                    container.handle(),
+                ));
+            }
+        }
+    }
+
+    /// Check lists only contain list items, and list items are never found
+    /// outside of them.
+    fn find_misplaced_list_items(
+        &self,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
+        for container in self.iter_containers() {
+            let is_list = container.is_list();
+            for child in container.children() {
+                if is_list && !child.is_list_item() {
+                    violations.push(InvariantViolation::NonListItemInList(
+                        child.handle(),
+                    ));
+                } else if !is_list && child.is_list_item() {
+                    violations.push(InvariantViolation::ListItemOutsideList(
+                        child.handle(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Check tables only contain rows, rows only contain cells, rows and
+    /// cells are never found outside of them, and every row of a table has
+    /// the same number of cells.
+    fn find_malformed_tables(&self, violations: &mut Vec<InvariantViolation>) {
+        use ContainerNodeKind::{Table, TableCell, TableRow};
+
+        for container in self.iter_containers() {
+            let expected_child_kind = match container.kind() {
+                Table => TableRow,
+                TableRow => TableCell,
+                _ => {
+                    let misplaced = container.children().iter().filter(|n| {
+                        matches!(
+                            n,
+                            DomNode::Container(c)
+                                if matches!(c.kind(), TableRow | TableCell)
+                        )
+                    });
+                    violations.extend(misplaced.map(|n| {
+                        InvariantViolation::TablePartOutsideTable(n.handle())
+                    }));
+                    continue;
+                }
+            };
+            let children_are_valid = !container.children().is_empty()
+                && container.children().iter().all(|n| {
+                    matches!(n, DomNode::Container(c) if *c.kind() == expected_child_kind)
+                });
+            if !children_are_valid {
+                violations.push(InvariantViolation::MalformedTable(
+                    container.handle(),
+                ));
+            } else if container.is_table() {
+                let widths = container
+                    .children()
+                    .iter()
+                    .filter_map(DomNode::as_container)
+                    .map(|row| row.children().len());
+                if widths.collect::<HashSet<_>>().len() != 1 {
+                    violations.push(InvariantViolation::RaggedTable(
+                        container.handle(),
+                    ));
+                }
             }
         }
     }
@@ -206,4 +415,56 @@ mod test {
 
         dom.assert_invariants();
     }
+
+    #[test]
+    fn well_formed_table_is_fine() {
+        let dom = Dom::<Utf16String>::new(vec![DomNode::new_table(vec![
+            DomNode::new_table_row(vec![
+                DomNode::new_table_cell(true, vec![]),
+                DomNode::new_table_cell(true, vec![]),
+            ]),
+            DomNode::new_table_row(vec![
+                DomNode::new_table_cell(false, vec![]),
+                DomNode::new_table_cell(false, vec![]),
+            ]),
+        ])]);
+
+        dom.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "Table rows have different numbers of cells")]
+    fn ragged_table_fails_invariants() {
+        let dom = Dom::<Utf16String>::new(vec![DomNode::new_table(vec![
+            DomNode::new_table_row(vec![
+                DomNode::new_table_cell(false, vec![]),
+                DomNode::new_table_cell(false, vec![]),
+            ]),
+            DomNode::new_table_row(vec![DomNode::new_table_cell(
+                false,
+                vec![],
+            )]),
+        ])]);
+
+        dom.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "Malformed table found")]
+    fn empty_table_fails_invariants() {
+        let dom = Dom::<Utf16String>::new(vec![DomNode::new_table(vec![])]);
+
+        dom.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "Table row or cell found outside of its parent")]
+    fn cell_outside_of_row_fails_invariants() {
+        let dom = Dom::<Utf16String>::new(vec![DomNode::new_table_cell(
+            false,
+            vec![],
+        )]);
+
+        dom.assert_invariants();
+    }
 }
@@ -15,7 +15,9 @@
 //! Methods on Dom that modify its contents and are guaranteed to conform to
 //! our invariants e.g. no empty text nodes, no adjacent text nodes.
 
-use crate::dom::nodes::dom_node::DomNodeKind::{Generic, ListItem, Paragraph};
+use crate::dom::nodes::dom_node::DomNodeKind::{
+    Generic, ListItem, Paragraph, TableCell,
+};
 use crate::dom::range::DomLocationPosition::After;
 use crate::dom::unicode_string::UnicodeStr;
 use crate::dom::DomLocation;
@@ -320,7 +322,11 @@ where
                     }
                 }
                 DomNode::Text(t) => Some(t),
-                DomNode::LineBreak(_) | DomNode::Mention(_) => None,
+                DomNode::LineBreak(_)
+                | DomNode::HorizontalRule(_)
+                | DomNode::Mention(_)
+                | DomNode::Image(_)
+                | DomNode::Opaque(_) => None,
             }
         }
 
@@ -375,7 +381,11 @@ where
                     }
                 }
                 DomNode::Text(t) => Some(t),
-                DomNode::LineBreak(_) | DomNode::Mention(_) => None,
+                DomNode::LineBreak(_)
+                | DomNode::HorizontalRule(_)
+                | DomNode::Mention(_)
+                | DomNode::Image(_)
+                | DomNode::Opaque(_) => None,
             }
         }
 
@@ -502,6 +512,37 @@ where
         }
     }
 
+    /// Replace the part of [loc] in the range, where it is a leaf that is
+    /// also a block, like a horizontal rule.
+    fn replace_in_block_leaf(
+        loc: &DomLocation,
+        new_text: &S,
+        first_text_node: &mut bool,
+        action_list: &mut DomActionList<S>,
+    ) {
+        match (loc.start_offset, loc.end_offset) {
+            (0, 1) => {
+                // The whole node is selected, delete it
+                action_list
+                    .push(DomAction::remove_node(loc.node_handle.clone()));
+            }
+            (start_offset, _) => {
+                // Text can't sit next to a rule or another block, only in a
+                // new paragraph before or after it
+                if *first_text_node && !new_text.is_empty() {
+                    action_list.push(DomAction::add_node(
+                        loc.node_handle.parent_handle(),
+                        loc.node_handle.index_in_parent() + start_offset,
+                        DomNode::new_paragraph(vec![DomNode::new_text(
+                            new_text.clone(),
+                        )]),
+                    ));
+                    *first_text_node = false;
+                }
+            }
+        }
+    }
+
     /// Given a range to replace and some new text, modify the nodes in the
     /// range to replace the text with the supplied text.
     /// Returns a list of actions to be done to the Dom (add or remove nodes).
@@ -530,7 +571,7 @@ where
                                 first_text_node = false;
                             } else if first_text_node {
                                 match loc.kind {
-                                    Paragraph | ListItem => {
+                                    Paragraph | ListItem | TableCell => {
                                         let text_node = DomNode::new_text(new_text.clone());
                                         action_list.push(DomAction::add_node(
                                             loc.node_handle.clone(),
@@ -562,7 +603,26 @@ where
                         first_text_node = false;
                     }
                 }
-                DomNode::LineBreak(_) | DomNode::Mention(_) => {
+                DomNode::HorizontalRule(_) => {
+                    Self::replace_in_block_leaf(
+                        loc,
+                        &new_text,
+                        &mut first_text_node,
+                        &mut action_list,
+                    );
+                }
+                DomNode::Opaque(opaque) if opaque.is_block() => {
+                    Self::replace_in_block_leaf(
+                        loc,
+                        &new_text,
+                        &mut first_text_node,
+                        &mut action_list,
+                    );
+                }
+                DomNode::LineBreak(_)
+                | DomNode::Mention(_)
+                | DomNode::Image(_)
+                | DomNode::Opaque(_) => {
                     match (loc.start_offset, loc.end_offset) {
                         (0, 1) => {
                             // Whole line break or mention is selected, delete it
@@ -633,7 +693,12 @@ where
 
         // If text wasn't added in any previous iteration, just append it next to the last leaf
         if first_text_node && !new_text.is_empty() {
-            if let Some(last_leaf) = range.leaves().last() {
+            // Horizontal rules are blocks, text can't go next to them
+            if let Some(last_leaf) = range
+                .leaves()
+                .filter(|l| l.kind != DomNodeKind::HorizontalRule)
+                .last()
+            {
                 action_list.push(DomAction::add_node(
                     last_leaf.node_handle.parent_handle(),
                     last_leaf.node_handle.index_in_parent() + 1,
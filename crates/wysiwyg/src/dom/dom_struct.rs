@@ -358,6 +358,24 @@ where
         self.document_node().lookup_node(node_handle)
     }
 
+    /// Find the node based on its handle, or return None if the handle is
+    /// unset or does not refer to a node in this DOM.
+    pub fn try_lookup_node(
+        &self,
+        node_handle: &DomHandle,
+    ) -> Option<&DomNode<S>> {
+        if !node_handle.is_set() {
+            return None;
+        }
+        node_handle
+            .raw()
+            .iter()
+            .try_fold(self.document_node(), |node, idx| match node {
+                DomNode::Container(n) => n.children().get(*idx),
+                _ => None,
+            })
+    }
+
     /// Shortcut for looking up a container at given handle.
     /// Should only be used from contexts where node is
     /// guaranteed to be a container. (e.g. if `DomLocation`
@@ -402,6 +420,10 @@ where
                     "Handle is invalid: refers to the child of a line break, \
                     but line breaks cannot have children."
                 ),
+                DomNode::HorizontalRule(_) => panic!(
+                    "Handle is invalid: refers to the child of a horizontal \
+                    rule, but horizontal rules cannot have children."
+                ),
                 DomNode::Text(_) => panic!(
                     "Handle is invalid: refers to the child of a text node, \
                     but text nodes cannot have children."
@@ -410,6 +432,14 @@ where
                     "Handle is invalid: refers to the child of a mention node, \
                     but mention nodes cannot have children."
                 ),
+                DomNode::Image(_) => panic!(
+                    "Handle is invalid: refers to the child of an image, \
+                    but images cannot have children."
+                ),
+                DomNode::Opaque(_) => panic!(
+                    "Handle is invalid: refers to the child of an opaque node, \
+                    but opaque nodes cannot be edited."
+                ),
             }
         }
 
@@ -444,7 +474,11 @@ where
             DomNode::Container(_) => {
                 panic!("Can't insert into a non-text node!")
             }
-            DomNode::LineBreak(_) | DomNode::Mention(_) => {
+            DomNode::LineBreak(_)
+            | DomNode::HorizontalRule(_)
+            | DomNode::Mention(_)
+            | DomNode::Image(_)
+            | DomNode::Opaque(_) => {
                 if offset == 0 {
                     Where::Before
                 } else if offset == 1 {
@@ -966,6 +1000,23 @@ mod test {
         assert!(!d.contains(&handle));
     }
 
+    #[test]
+    fn try_lookup_node_finds_existing_node() {
+        let d = cm("<ul><li>b<strong>c</strong></li></ul>d|").state.dom;
+        let handle = DomHandle::from_raw(vec![0, 0, 1, 0]);
+        assert_eq!(d.try_lookup_node(&handle), Some(d.lookup_node(&handle)));
+    }
+
+    #[test]
+    fn try_lookup_node_returns_none_for_invalid_handles() {
+        let d = cm("<ul><li>b<strong>c</strong></li></ul>d|").state.dom;
+        let child_of_leaf = DomHandle::from_raw(vec![0, 0, 1, 0, 2]);
+        let missing_sibling = DomHandle::from_raw(vec![0, 0, 1, 5]);
+        assert_eq!(d.try_lookup_node(&child_of_leaf), None);
+        assert_eq!(d.try_lookup_node(&missing_sibling), None);
+        assert_eq!(d.try_lookup_node(&DomHandle::new_unset()), None);
+    }
+
     #[test]
     fn find_range_by_node() {
         let d = cm("<b><u>Hello, <i>world|</i></u></b>").state.dom;
@@ -1052,7 +1103,11 @@ mod test {
     fn kids(node: &DomNode<Utf16String>) -> &Vec<DomNode<Utf16String>> {
         match node {
             DomNode::Container(n) => n.children(),
-            DomNode::LineBreak(_) | DomNode::Mention(_) => NO_CHILDREN,
+            DomNode::LineBreak(_)
+            | DomNode::HorizontalRule(_)
+            | DomNode::Mention(_)
+            | DomNode::Image(_)
+            | DomNode::Opaque(_) => NO_CHILDREN,
             DomNode::Text(_) => {
                 panic!("We expected an Element, but found Text")
             }
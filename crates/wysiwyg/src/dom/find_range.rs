@@ -20,7 +20,7 @@ use crate::dom::{Dom, DomHandle, FindResult, Range};
 use crate::UnicodeString;
 use std::cmp::{max, min};
 
-use super::nodes::MentionNode;
+use super::nodes::{HorizontalRuleNode, ImageNode, MentionNode, OpaqueNode};
 
 pub fn find_range<S>(dom: &Dom<S>, start: usize, end: usize) -> Range
 where
@@ -106,12 +106,31 @@ where
                 locations.push(location);
             }
         }
+        DomNode::HorizontalRule(n) => {
+            if let Some(location) =
+                process_horizontal_rule_node(dom, n, start, end, offset)
+            {
+                locations.push(location);
+            }
+        }
         DomNode::Mention(n) => {
             if let Some(location) = process_mention_node(n, start, end, offset)
             {
                 locations.push(location);
             }
         }
+        DomNode::Image(n) => {
+            if let Some(location) = process_image_node(n, start, end, offset) {
+                locations.push(location);
+            }
+        }
+        DomNode::Opaque(n) => {
+            if let Some(location) =
+                process_opaque_node(dom, n, start, end, offset)
+            {
+                locations.push(location);
+            }
+        }
         DomNode::Container(n) => {
             locations
                 .extend(process_container_node(dom, n, start, end, offset));
@@ -204,6 +223,32 @@ where
     )
 }
 
+fn process_horizontal_rule_node<S>(
+    dom: &Dom<S>,
+    node: &HorizontalRuleNode<S>,
+    start: usize,
+    end: usize,
+    offset: &mut usize,
+) -> Option<DomLocation>
+where
+    S: UnicodeString,
+{
+    // Horizontal rules are like 1-character text nodes, followed by the
+    // gap between blocks when another block comes after them
+    let location = process_textlike_node(
+        node.handle(),
+        1,
+        start,
+        end,
+        offset,
+        DomNodeKind::HorizontalRule,
+    );
+    if !dom.is_last_in_parent(&node.handle()) {
+        *offset += 1;
+    }
+    location
+}
+
 fn process_mention_node<S>(
     node: &MentionNode<S>,
     start: usize,
@@ -224,6 +269,52 @@ where
     )
 }
 
+fn process_image_node<S>(
+    node: &ImageNode<S>,
+    start: usize,
+    end: usize,
+    offset: &mut usize,
+) -> Option<DomLocation>
+where
+    S: UnicodeString,
+{
+    // Images are like 1-character text nodes
+    process_textlike_node(
+        node.handle(),
+        1,
+        start,
+        end,
+        offset,
+        DomNodeKind::Image,
+    )
+}
+
+fn process_opaque_node<S>(
+    dom: &Dom<S>,
+    node: &OpaqueNode<S>,
+    start: usize,
+    end: usize,
+    offset: &mut usize,
+) -> Option<DomLocation>
+where
+    S: UnicodeString,
+{
+    // Opaque nodes are like 1-character text nodes. Block ones are followed
+    // by the gap between blocks, like horizontal rules.
+    let location = process_textlike_node(
+        node.handle(),
+        1,
+        start,
+        end,
+        offset,
+        DomNodeKind::Opaque,
+    );
+    if node.is_block() && !dom.is_last_in_parent(&node.handle()) {
+        *offset += 1;
+    }
+    location
+}
+
 fn process_textlike_node(
     handle: DomHandle,
     node_len: usize,
@@ -704,7 +704,10 @@ mod test {
             DomNode::Container(c) => c.name().to_string(),
             DomNode::Text(t) => format!("'{}'", t.data()),
             DomNode::LineBreak(_) => String::from("br"),
+            DomNode::HorizontalRule(_) => String::from("hr"),
             DomNode::Mention(_) => String::from("mention"),
+            DomNode::Image(_) => String::from("img"),
+            DomNode::Opaque(_) => String::from("opaque"),
         }
     }
 }
@@ -14,14 +14,20 @@
 
 pub mod container_node;
 pub mod dom_node;
+pub mod horizontal_rule_node;
+pub mod image_node;
 pub mod line_break_node;
 pub mod mention_node;
+pub mod opaque_node;
 pub mod text_node;
 
 pub use container_node::ContainerNode;
 pub use container_node::ContainerNodeKind;
 pub use dom_node::DomNode;
+pub use horizontal_rule_node::HorizontalRuleNode;
+pub use image_node::ImageNode;
 pub use line_break_node::LineBreakNode;
 pub use mention_node::MentionNode;
 pub use mention_node::MentionNodeKind;
+pub use opaque_node::OpaqueNode;
 pub use text_node::TextNode;
@@ -52,6 +52,9 @@ where
     CodeBlock,
     Quote,
     Paragraph,
+    Table,
+    TableRow,
+    TableCell,
 }
 
 impl<S: dom::unicode_string::UnicodeString> Default for ContainerNode<S> {
@@ -106,7 +109,7 @@ where
         format: S,
         children: Vec<DomNode<S>>,
     ) -> Option<Self> {
-        InlineFormatType::try_from(format.clone())
+        InlineFormatType::try_from(format.to_string().as_str())
             .map(|f| Self {
                 name: format,
                 kind: ContainerNodeKind::Formatting(f),
@@ -121,10 +124,13 @@ where
         format: InlineFormatType,
         children: Vec<DomNode<S>>,
     ) -> Self {
+        let attrs = format
+            .color_attribute()
+            .map(|(name, value)| vec![(name.into(), value.into())]);
         Self {
             name: format.tag().into(),
             kind: ContainerNodeKind::Formatting(format),
-            attrs: None,
+            attrs,
             children,
             handle: DomHandle::new_unset(),
         }
@@ -170,6 +176,38 @@ where
         }
     }
 
+    pub fn new_table(children: Vec<DomNode<S>>) -> Self {
+        Self {
+            name: "table".into(),
+            kind: ContainerNodeKind::Table,
+            attrs: None,
+            children,
+            handle: DomHandle::new_unset(),
+        }
+    }
+
+    pub fn new_table_row(children: Vec<DomNode<S>>) -> Self {
+        Self {
+            name: "tr".into(),
+            kind: ContainerNodeKind::TableRow,
+            attrs: None,
+            children,
+            handle: DomHandle::new_unset(),
+        }
+    }
+
+    /// Create a table cell, `<th>` if `is_header` is true and `<td>`
+    /// otherwise.
+    pub fn new_table_cell(is_header: bool, children: Vec<DomNode<S>>) -> Self {
+        Self {
+            name: if is_header { "th" } else { "td" }.into(),
+            kind: ContainerNodeKind::TableCell,
+            attrs: None,
+            children,
+            handle: DomHandle::new_unset(),
+        }
+    }
+
     pub fn append_child(&mut self, mut child: DomNode<S>) -> DomHandle {
         assert!(self.handle.is_set());
 
@@ -342,6 +380,26 @@ where
         matches!(&self.kind, ContainerNodeKind::List(f) if f == list_type)
     }
 
+    pub fn is_table(&self) -> bool {
+        matches!(self.kind, ContainerNodeKind::Table)
+    }
+
+    pub fn is_table_cell(&self) -> bool {
+        matches!(self.kind, ContainerNodeKind::TableCell)
+    }
+
+    pub(crate) fn is_table_header_cell(&self) -> bool {
+        self.is_table_cell() && self.name == S::from("th")
+    }
+
+    /// True if this is a table row made only of header cells.
+    pub(crate) fn is_table_header_row(&self) -> bool {
+        matches!(self.kind, ContainerNodeKind::TableRow)
+            && self.children.iter().all(|cell| {
+                matches!(cell, DomNode::Container(c) if c.is_table_header_cell())
+            })
+    }
+
     pub(crate) fn is_structure_node(&self) -> bool {
         use ContainerNodeKind::*;
 
@@ -623,6 +681,12 @@ where
                 state,
                 as_message,
             ),
+            ContainerNodeKind::Table => self.fmt_table_html(
+                formatter,
+                selection_writer,
+                state,
+                as_message,
+            ),
             _ => self.fmt_default_html(
                 formatter,
                 selection_writer,
@@ -761,6 +825,47 @@ impl<S: UnicodeString> ContainerNode<S> {
         self.fmt_tag_close(&S::from("pre"), formatter);
     }
 
+    /// Write the table, moving a first row made only of header cells to a
+    /// `<thead>` and the rest of the rows to a `<tbody>`.
+    fn fmt_table_html(
+        &self,
+        formatter: &mut S,
+        mut selection_writer: Option<&mut SelectionWriter>,
+        state: ToHtmlState,
+        as_message: bool,
+    ) {
+        assert!(matches!(self.kind, ContainerNodeKind::Table));
+        let header_rows = match self.children.first() {
+            Some(DomNode::Container(row)) if row.is_table_header_row() => 1,
+            _ => 0,
+        };
+        let no_attrs = None::<Vec<(S, S)>>;
+
+        self.fmt_tag_open(self.name(), formatter, &self.attrs);
+        for (i, child) in self.children.iter().enumerate() {
+            if i == 0 && header_rows > 0 {
+                self.fmt_tag_open(&S::from("thead"), formatter, &no_attrs);
+            }
+            if i == header_rows {
+                self.fmt_tag_open(&S::from("tbody"), formatter, &no_attrs);
+            }
+            let state = self.updated_state(state, i);
+            child.fmt_html(
+                formatter,
+                selection_writer.as_deref_mut(),
+                state,
+                as_message,
+            );
+            if i + 1 == header_rows {
+                self.fmt_tag_close(&S::from("thead"), formatter);
+            }
+        }
+        if self.children.len() > header_rows {
+            self.fmt_tag_close(&S::from("tbody"), formatter);
+        }
+        self.fmt_tag_close(self.name(), formatter);
+    }
+
     fn fmt_children_html(
         &self,
         formatter: &mut S,
@@ -823,10 +928,27 @@ where
         match self.kind {
             ContainerNodeKind::List(_) => fmt_list(self, &mut text),
             ContainerNodeKind::ListItem => fmt_list_item(self, &mut text),
+            ContainerNodeKind::TableRow => fmt_table_row(self, &mut text),
+            ContainerNodeKind::TableCell => fmt_list_item(self, &mut text),
             _ => fmt_default(self, &mut text),
         }
         return text;
 
+        #[inline(always)]
+        fn fmt_table_row<S: UnicodeString>(
+            container: &ContainerNode<S>,
+            text: &mut S,
+        ) {
+            for (index, cell) in container.children.iter().enumerate() {
+                if index != 0 {
+                    text.push(" | ");
+                }
+                let cell_text = cell.to_plain_text().to_string();
+                text.push(cell_text.trim_end_matches('\n'));
+            }
+            text.push("\n");
+        }
+
         #[inline(always)]
         fn fmt_list<S: UnicodeString>(
             container: &ContainerNode<S>,
@@ -939,6 +1061,14 @@ where
                 fmt_inline_code(self, buffer, &mut options)?;
             }
 
+            Formatting(Superscript) | Formatting(Subscript) => {
+                fmt_raw_html(self, buffer, &options)?;
+            }
+
+            Formatting(TextColor(_)) | Formatting(BackgroundColor(_)) => {
+                fmt_color(self, buffer, &options)?;
+            }
+
             Link(url) => {
                 fmt_link(self, buffer, &options, url)?;
             }
@@ -962,6 +1092,15 @@ where
             Paragraph => {
                 fmt_paragraph(self, buffer, &options)?;
             }
+
+            Table => {
+                fmt_table(self, buffer, &options)?;
+            }
+
+            // Rows and cells are written by their table.
+            TableRow | TableCell => {
+                fmt_children(self, buffer, &options)?;
+            }
         };
 
         return Ok(());
@@ -1078,6 +1217,44 @@ where
             Ok(())
         }
 
+        #[inline(always)]
+        fn fmt_raw_html<S>(
+            this: &ContainerNode<S>,
+            buffer: &mut S,
+            options: &MarkdownOptions,
+        ) -> Result<(), MarkdownError<S>>
+        where
+            S: UnicodeString,
+        {
+            // Superscript and subscript are absent from Markdown too,
+            // use raw HTML like for underline.
+
+            this.fmt_tag_open(this.name(), buffer, &this.attrs);
+            fmt_children(this, buffer, options)?;
+            this.fmt_tag_close(this.name(), buffer);
+
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn fmt_color<S>(
+            this: &ContainerNode<S>,
+            buffer: &mut S,
+            options: &MarkdownOptions,
+        ) -> Result<(), MarkdownError<S>>
+        where
+            S: UnicodeString,
+        {
+            // Colours are absent from Markdown too, use the same raw
+            // HTML as in messages.
+
+            this.fmt_tag_open(this.name(), buffer, &this.attrs);
+            fmt_children(this, buffer, options)?;
+            this.fmt_tag_close(this.name(), buffer);
+
+            Ok(())
+        }
+
         #[inline(always)]
         fn fmt_inline_code<S>(
             this: &ContainerNode<S>,
@@ -1193,6 +1370,12 @@ where
                         )))
                     }
 
+                    DomNode::HorizontalRule(horizontal_rule) => {
+                        return Err(MarkdownError::InvalidListItem(Some(
+                            horizontal_rule.name(),
+                        )))
+                    }
+
                     DomNode::Text(_) => {
                         return Err(MarkdownError::InvalidListItem(None))
                     }
@@ -1202,6 +1385,18 @@ where
                             mention.name(),
                         )))
                     }
+
+                    DomNode::Image(image) => {
+                        return Err(MarkdownError::InvalidListItem(Some(
+                            image.name(),
+                        )))
+                    }
+
+                    DomNode::Opaque(opaque) => {
+                        return Err(MarkdownError::InvalidListItem(Some(
+                            opaque.name(),
+                        )))
+                    }
                 };
 
                 // What's the current indentation, for this specific list only.
@@ -1320,6 +1515,57 @@ where
             Ok(())
         }
 
+        #[inline(always)]
+        fn fmt_table<S>(
+            this: &ContainerNode<S>,
+            buffer: &mut S,
+            options: &MarkdownOptions,
+        ) -> Result<(), MarkdownError<S>>
+        where
+            S: UnicodeString,
+        {
+            // GitHub Flavored Markdown pipe tables always start with a
+            // header row, so the first row is used as the header even if
+            // its cells are not `<th>`. Cells can only hold one line, and
+            // pipes inside them must be escaped.
+
+            // A table straight after a line of text would be read as part
+            // of that paragraph, so it needs a blank line before it.
+            let text = buffer.to_string();
+            if text.ends_with('\n') && !text.ends_with("\n\n") {
+                buffer.push("\n");
+            }
+
+            for (nth, row) in this.children.iter().enumerate() {
+                let DomNode::Container(row) = row else {
+                    continue;
+                };
+                buffer.push("|");
+                for cell in row.children() {
+                    let mut cell_buffer = S::default();
+                    cell.fmt_markdown(&mut cell_buffer, options)?;
+                    let cell_text = cell_buffer
+                        .to_string()
+                        .replace('|', "\\|")
+                        .replace('\n', " ");
+                    buffer.push(" ");
+                    buffer.push(cell_text.trim());
+                    buffer.push(" |");
+                }
+                buffer.push("\n");
+
+                if nth == 0 {
+                    buffer.push("|");
+                    for _ in row.children() {
+                        buffer.push(" --- |");
+                    }
+                    buffer.push("\n");
+                }
+            }
+
+            Ok(())
+        }
+
         #[inline(always)]
         fn fmt_paragraph<S>(
             this: &ContainerNode<S>,
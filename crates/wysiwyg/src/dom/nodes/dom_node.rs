@@ -15,7 +15,8 @@
 use crate::composer_model::example_format::SelectionWriter;
 use crate::dom::dom_handle::DomHandle;
 use crate::dom::nodes::{
-    ContainerNode, ContainerNodeKind, LineBreakNode, TextNode,
+    ContainerNode, ContainerNodeKind, HorizontalRuleNode, LineBreakNode,
+    TextNode,
 };
 use crate::dom::to_html::{ToHtml, ToHtmlState};
 use crate::dom::to_markdown::{MarkdownError, MarkdownOptions, ToMarkdown};
@@ -26,7 +27,7 @@ use crate::dom::unicode_string::UnicodeStrExt;
 use crate::dom::{self, UnicodeString};
 use crate::{InlineFormatType, ListType};
 
-use super::MentionNode;
+use super::{ImageNode, MentionNode, OpaqueNode};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum DomNode<S>
@@ -36,7 +37,10 @@ where
     Container(ContainerNode<S>), // E.g. html, div
     Text(TextNode<S>),
     LineBreak(LineBreakNode<S>),
+    HorizontalRule(HorizontalRuleNode<S>),
     Mention(MentionNode<S>),
+    Image(ImageNode<S>),
+    Opaque(OpaqueNode<S>), // E.g. video, iframe
 }
 
 impl<S: dom::unicode_string::UnicodeString> Default for DomNode<S> {
@@ -61,6 +65,10 @@ where
         DomNode::LineBreak(LineBreakNode::default())
     }
 
+    pub fn new_horizontal_rule() -> DomNode<S> {
+        DomNode::HorizontalRule(HorizontalRuleNode::default())
+    }
+
     pub fn new_formatting(
         format: InlineFormatType,
         children: Vec<DomNode<S>>,
@@ -103,12 +111,30 @@ where
         DomNode::Container(ContainerNode::new_paragraph(children))
     }
 
+    pub fn new_table(children: Vec<DomNode<S>>) -> DomNode<S> {
+        DomNode::Container(ContainerNode::new_table(children))
+    }
+
+    pub fn new_table_row(children: Vec<DomNode<S>>) -> DomNode<S> {
+        DomNode::Container(ContainerNode::new_table_row(children))
+    }
+
+    pub fn new_table_cell(
+        is_header: bool,
+        children: Vec<DomNode<S>>,
+    ) -> DomNode<S> {
+        DomNode::Container(ContainerNode::new_table_cell(is_header, children))
+    }
+
     pub fn handle(&self) -> DomHandle {
         match self {
             DomNode::Container(n) => n.handle(),
             DomNode::LineBreak(n) => n.handle(),
+            DomNode::HorizontalRule(n) => n.handle(),
             DomNode::Text(n) => n.handle(),
             DomNode::Mention(n) => n.handle(),
+            DomNode::Image(n) => n.handle(),
+            DomNode::Opaque(n) => n.handle(),
         }
     }
 
@@ -116,8 +142,11 @@ where
         match self {
             DomNode::Container(n) => n.set_handle(handle),
             DomNode::LineBreak(n) => n.set_handle(handle),
+            DomNode::HorizontalRule(n) => n.set_handle(handle),
             DomNode::Text(n) => n.set_handle(handle),
             DomNode::Mention(n) => n.set_handle(handle),
+            DomNode::Image(n) => n.set_handle(handle),
+            DomNode::Opaque(n) => n.set_handle(handle),
         }
     }
 
@@ -125,8 +154,11 @@ where
         match self {
             DomNode::Text(n) => n.data().len(),
             DomNode::LineBreak(n) => n.text_len(),
+            DomNode::HorizontalRule(n) => n.text_len(),
             DomNode::Container(n) => n.text_len(),
             DomNode::Mention(n) => n.text_len(),
+            DomNode::Image(n) => n.text_len(),
+            DomNode::Opaque(n) => n.text_len(),
         }
     }
 
@@ -150,6 +182,23 @@ where
         DomNode::Mention(MentionNode::new_at_room(attributes))
     }
 
+    pub fn new_image(src: S, alt: S, attributes: Vec<(S, S)>) -> DomNode<S> {
+        DomNode::Image(ImageNode::new(src, alt, attributes))
+    }
+
+    pub fn new_custom_emoji(mxc: S, shortcode: S) -> DomNode<S> {
+        DomNode::Image(ImageNode::new_custom_emoji(mxc, shortcode))
+    }
+
+    pub fn new_opaque(
+        name: S,
+        attributes: Vec<(S, S)>,
+        inner_html: S,
+        text: S,
+    ) -> DomNode<S> {
+        DomNode::Opaque(OpaqueNode::new(name, attributes, inner_html, text))
+    }
+
     pub fn is_container_node(&self) -> bool {
         matches!(self, DomNode::Container(_))
     }
@@ -162,6 +211,14 @@ where
         matches!(self, DomNode::Mention(_))
     }
 
+    pub fn is_image_node(&self) -> bool {
+        matches!(self, DomNode::Image(_))
+    }
+
+    pub fn is_opaque_node(&self) -> bool {
+        matches!(self, DomNode::Opaque(_))
+    }
+
     /// Returns `true` if the dom node is [`LineBreak`].
     ///
     /// [`LineBreak`]: DomNode::LineBreak
@@ -170,6 +227,14 @@ where
         matches!(self, Self::LineBreak(..))
     }
 
+    /// Returns `true` if the dom node is [`HorizontalRule`].
+    ///
+    /// [`HorizontalRule`]: DomNode::HorizontalRule
+    #[must_use]
+    pub fn is_horizontal_rule(&self) -> bool {
+        matches!(self, Self::HorizontalRule(..))
+    }
+
     /// Returns `true` if thie dom node is not a container i.e. a text node or
     /// a text-like node like a line break.
     pub fn is_leaf(&self) -> bool {
@@ -192,14 +257,18 @@ where
     }
 
     pub(crate) fn is_block_node(&self) -> bool {
-        matches!(self, Self::Container(container) if container.is_block_node())
+        match self {
+            Self::Container(container) => container.is_block_node(),
+            Self::HorizontalRule(_) => true,
+            Self::Opaque(opaque) => opaque.is_block(),
+            _ => false,
+        }
     }
 
     pub(crate) fn is_list_item(&self) -> bool {
         matches!(self, Self::Container(container) if container.is_list_item())
     }
 
-    #[allow(dead_code)]
     pub(crate) fn is_list(&self) -> bool {
         matches!(self, Self::Container(container) if container.is_list())
     }
@@ -240,8 +309,11 @@ where
         match self {
             DomNode::Text(_) => DomNodeKind::Text,
             DomNode::LineBreak(_) => DomNodeKind::LineBreak,
+            DomNode::HorizontalRule(_) => DomNodeKind::HorizontalRule,
             DomNode::Container(n) => DomNodeKind::from_container_kind(n.kind()),
             DomNode::Mention(_) => DomNodeKind::Mention,
+            DomNode::Image(_) => DomNodeKind::Image,
+            DomNode::Opaque(_) => DomNodeKind::Opaque,
         }
     }
 
@@ -252,7 +324,10 @@ where
             DomNode::Container(c) => c.has_leading_line_break(),
             DomNode::Text(_) => false,
             DomNode::LineBreak(_) => true,
-            DomNode::Mention(_) => false,
+            DomNode::HorizontalRule(_)
+            | DomNode::Mention(_)
+            | DomNode::Image(_)
+            | DomNode::Opaque(_) => false,
         }
     }
 
@@ -295,7 +370,12 @@ where
             }
             DomNode::Text(t) => DomNode::Text(t.slice_after(position)),
             DomNode::LineBreak(_) => panic!("Can't slice a linebreak"),
+            DomNode::HorizontalRule(_) => {
+                panic!("Can't slice a horizontal rule")
+            }
             DomNode::Mention(_) => panic!("Can't slice a mention"),
+            DomNode::Image(_) => panic!("Can't slice an image"),
+            DomNode::Opaque(_) => panic!("Can't slice an opaque node"),
         }
     }
 
@@ -310,7 +390,12 @@ where
             }
             DomNode::Text(t) => DomNode::Text(t.slice_before(position)),
             DomNode::LineBreak(_) => panic!("Can't slice a linebreak"),
+            DomNode::HorizontalRule(_) => {
+                panic!("Can't slice a horizontal rule")
+            }
             DomNode::Mention(_) => panic!("Can't slice a mention"),
+            DomNode::Image(_) => panic!("Can't slice an image"),
+            DomNode::Opaque(_) => panic!("Can't slice an opaque node"),
         }
     }
 
@@ -344,6 +429,10 @@ where
                     "Handle is invalid: refers to the child of a line break, \
                     but line breaks cannot have children."
                 ),
+                DomNode::HorizontalRule(_) => panic!(
+                    "Handle is invalid: refers to the child of a horizontal \
+                    rule, but horizontal rules cannot have children."
+                ),
                 DomNode::Text(_) => panic!(
                     "Handle {:?} is invalid: refers to the child of a text node, \
                     but text nodes cannot have children.", node_handle
@@ -352,6 +441,14 @@ where
                     "Handle {:?} is invalid: refers to the child of a mention node, \
                     but text nodes cannot have children.", node_handle
                 ),
+                DomNode::Image(_) => panic!(
+                    "Handle {:?} is invalid: refers to the child of an image, \
+                    but images cannot have children.", node_handle
+                ),
+                DomNode::Opaque(_) => panic!(
+                    "Handle {:?} is invalid: refers to the child of an opaque node, \
+                    whose children can't be edited.", node_handle
+                ),
             }
         }
         node
@@ -408,12 +505,21 @@ where
             DomNode::LineBreak(s) => {
                 s.fmt_html(buf, selection_writer, state, as_message)
             }
+            DomNode::HorizontalRule(s) => {
+                s.fmt_html(buf, selection_writer, state, as_message)
+            }
             DomNode::Text(s) => {
                 s.fmt_html(buf, selection_writer, state, as_message)
             }
             DomNode::Mention(s) => {
                 s.fmt_html(buf, selection_writer, state, as_message)
             }
+            DomNode::Image(s) => {
+                s.fmt_html(buf, selection_writer, state, as_message)
+            }
+            DomNode::Opaque(s) => {
+                s.fmt_html(buf, selection_writer, state, as_message)
+            }
         }
     }
 }
@@ -426,8 +532,11 @@ where
         match self {
             DomNode::Container(n) => n.to_raw_text(),
             DomNode::LineBreak(n) => n.to_raw_text(),
+            DomNode::HorizontalRule(n) => n.to_raw_text(),
             DomNode::Text(n) => n.to_raw_text(),
             DomNode::Mention(n) => n.to_raw_text(),
+            DomNode::Image(n) => n.to_raw_text(),
+            DomNode::Opaque(n) => n.to_raw_text(),
         }
     }
 }
@@ -440,8 +549,11 @@ where
         match self {
             DomNode::Container(n) => n.to_plain_text(),
             DomNode::LineBreak(n) => n.to_plain_text(),
+            DomNode::HorizontalRule(n) => n.to_plain_text(),
             DomNode::Text(n) => n.to_plain_text(),
             DomNode::Mention(n) => n.to_plain_text(),
+            DomNode::Image(n) => n.to_plain_text(),
+            DomNode::Opaque(n) => n.to_plain_text(),
         }
     }
 }
@@ -454,8 +566,13 @@ where
         match self {
             DomNode::Container(n) => n.to_tree_display(continuous_positions),
             DomNode::LineBreak(n) => n.to_tree_display(continuous_positions),
+            DomNode::HorizontalRule(n) => {
+                n.to_tree_display(continuous_positions)
+            }
             DomNode::Text(n) => n.to_tree_display(continuous_positions),
             DomNode::Mention(n) => n.to_tree_display(continuous_positions),
+            DomNode::Image(n) => n.to_tree_display(continuous_positions),
+            DomNode::Opaque(n) => n.to_tree_display(continuous_positions),
         }
     }
 }
@@ -475,7 +592,10 @@ where
             }
             DomNode::Text(text) => text.fmt_markdown(buffer, options),
             DomNode::LineBreak(node) => node.fmt_markdown(buffer, options),
+            DomNode::HorizontalRule(node) => node.fmt_markdown(buffer, options),
             DomNode::Mention(node) => node.fmt_markdown(buffer, options),
+            DomNode::Image(node) => node.fmt_markdown(buffer, options),
+            DomNode::Opaque(node) => node.fmt_markdown(buffer, options),
         }
     }
 }
@@ -485,7 +605,12 @@ pub enum DomNodeKind {
     Generic, // Should only be used for root node so far
     Text,
     LineBreak,
+    /// A block node in the tree, but a leaf of length 1 in ranges, like a
+    /// line break.
+    HorizontalRule,
     Mention,
+    Image,
+    Opaque,
     Formatting(InlineFormatType),
     Link,
     ListItem,
@@ -493,6 +618,9 @@ pub enum DomNodeKind {
     CodeBlock,
     Quote,
     Paragraph,
+    Table,
+    TableRow,
+    TableCell,
 }
 
 impl DomNodeKind {
@@ -510,6 +638,9 @@ impl DomNodeKind {
             ContainerNodeKind::CodeBlock => DomNodeKind::CodeBlock,
             ContainerNodeKind::Quote => DomNodeKind::Quote,
             ContainerNodeKind::Paragraph => DomNodeKind::Paragraph,
+            ContainerNodeKind::Table => DomNodeKind::Table,
+            ContainerNodeKind::TableRow => DomNodeKind::TableRow,
+            ContainerNodeKind::TableCell => DomNodeKind::TableCell,
         }
     }
 
@@ -526,12 +657,20 @@ impl DomNodeKind {
                 | Self::CodeBlock
                 | Self::Quote
                 | Self::Paragraph
+                | Self::Table
+                | Self::TableRow
+                | Self::TableCell
         )
     }
 
     pub fn is_leaf_kind(&self) -> bool {
         match self {
-            Self::Text | Self::LineBreak | Self::Mention => true,
+            Self::Text
+            | Self::LineBreak
+            | Self::HorizontalRule
+            | Self::Mention
+            | Self::Image
+            | Self::Opaque => true,
             Self::Generic
             | Self::Formatting(_)
             | Self::Link
@@ -539,7 +678,10 @@ impl DomNodeKind {
             | Self::List
             | Self::CodeBlock
             | Self::Quote
-            | Self::Paragraph => false,
+            | Self::Paragraph
+            | Self::Table
+            | Self::TableRow
+            | Self::TableCell => false,
         }
     }
 
@@ -0,0 +1,230 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::composer_model::example_format::SelectionWriter;
+use crate::dom::dom_handle::DomHandle;
+use crate::dom::to_html::{ToHtml, ToHtmlExt, ToHtmlState};
+use crate::dom::to_markdown::{MarkdownError, MarkdownOptions, ToMarkdown};
+use crate::dom::to_plain_text::ToPlainText;
+use crate::dom::to_raw_text::ToRawText;
+use crate::dom::to_tree::ToTree;
+use crate::dom::unicode_string::{UnicodeStrExt, UnicodeStringExt};
+use crate::dom::UnicodeString;
+
+/// Attribute marking an image as a custom emoji (MSC2545)
+const EMOTICON_ATTRIBUTE: &str = "data-mx-emoticon";
+
+/// Height custom emoji are shown at, as recommended by MSC2545
+const EMOTICON_HEIGHT: &str = "32";
+
+/// True if [url] is a Matrix content URI (`mxc://<server-name>/<media-id>`).
+/// Images are only allowed to point at Matrix media.
+pub fn is_mxc_url(url: &str) -> bool {
+    let Some((server_name, media_id)) = url
+        .strip_prefix("mxc://")
+        .and_then(|rest| rest.split_once('/'))
+    else {
+        return false;
+    };
+    let is_server_name_char = |c: char| {
+        c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']')
+    };
+    let is_media_id_char =
+        |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-');
+    !server_name.is_empty()
+        && server_name.chars().all(is_server_name_char)
+        && !media_id.is_empty()
+        && media_id.chars().all(is_media_id_char)
+}
+
+/// An inline image (`<img>`) pointing at Matrix media, e.g. a custom emoji.
+/// Like a mention, it acts as a single character that can be deleted but
+/// not edited, and its alt text stands in for it in plain text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageNode<S>
+where
+    S: UnicodeString,
+{
+    src: S,
+    alt: S,
+    attributes: Vec<(S, S)>,
+    handle: DomHandle,
+}
+
+impl<S> ImageNode<S>
+where
+    S: UnicodeString,
+{
+    /// Create a new ImageNode showing the media at [src]. [attributes]
+    /// are any other attributes of the `<img>` tag.
+    ///
+    /// NOTE: Its handle() will be unset until you call set_handle() or
+    /// append() it to another node.
+    pub fn new(src: S, alt: S, attributes: Vec<(S, S)>) -> Self {
+        Self {
+            src,
+            alt,
+            attributes,
+            handle: DomHandle::new_unset(),
+        }
+    }
+
+    /// Create a new ImageNode for the custom emoji at [mxc], whose alt text
+    /// is its [shortcode] wrapped in colons (e.g. `:party:`).
+    pub fn new_custom_emoji(mxc: S, shortcode: S) -> Self {
+        let shortcode = shortcode.to_string();
+        let shortcode = shortcode.trim_matches(':');
+        let alt = S::from(format!(":{shortcode}:").as_str());
+        let attributes = vec![
+            (EMOTICON_ATTRIBUTE.into(), S::default()),
+            ("title".into(), alt.clone()),
+            ("height".into(), EMOTICON_HEIGHT.into()),
+        ];
+        Self::new(mxc, alt, attributes)
+    }
+
+    pub fn name(&self) -> S {
+        "img".into()
+    }
+
+    pub fn src(&self) -> &S {
+        &self.src
+    }
+
+    pub fn alt(&self) -> &S {
+        &self.alt
+    }
+
+    pub fn attributes(&self) -> &Vec<(S, S)> {
+        &self.attributes
+    }
+
+    /// True if this image is a custom emoji rather than a picture
+    pub fn is_custom_emoji(&self) -> bool {
+        self.attributes
+            .iter()
+            .any(|(name, _)| name.to_string() == EMOTICON_ATTRIBUTE)
+    }
+
+    pub fn set_handle(&mut self, handle: DomHandle) {
+        self.handle = handle;
+    }
+
+    pub fn handle(&self) -> DomHandle {
+        self.handle.clone()
+    }
+
+    pub fn text_len(&self) -> usize {
+        // Like mentions, images can't be edited so they act as a single
+        // character.
+        1
+    }
+}
+
+impl<S> ToHtml<S> for ImageNode<S>
+where
+    S: UnicodeString,
+{
+    fn fmt_html(
+        &self,
+        formatter: &mut S,
+        selection_writer: Option<&mut SelectionWriter>,
+        _: ToHtmlState,
+        _as_message: bool,
+    ) {
+        let cur_pos = formatter.len();
+        let mut attributes = self.attributes.clone();
+        attributes.push(("src".into(), self.src.clone()));
+        attributes.push(("alt".into(), self.alt.clone()));
+        self.fmt_tag_open(&self.name(), formatter, &Some(attributes));
+
+        if let Some(sel_writer) = selection_writer {
+            sel_writer.write_selection_image_node(formatter, cur_pos, self);
+        }
+    }
+}
+
+impl<S> ToRawText<S> for ImageNode<S>
+where
+    S: UnicodeString,
+{
+    fn to_raw_text(&self) -> S {
+        self.alt.clone()
+    }
+}
+
+impl<S> ToPlainText<S> for ImageNode<S>
+where
+    S: UnicodeString,
+{
+    fn to_plain_text(&self) -> S {
+        self.alt.clone()
+    }
+}
+
+impl<S> ToTree<S> for ImageNode<S>
+where
+    S: UnicodeString,
+{
+    fn to_tree_display(&self, continuous_positions: Vec<usize>) -> S {
+        let mut description = self.name();
+        description.push(" \"");
+        description.push(self.alt.clone());
+        description.push("\", ");
+        description.push(self.src.clone());
+
+        self.tree_line(
+            description,
+            self.handle.raw().len(),
+            continuous_positions,
+        )
+    }
+}
+
+impl<S> ToMarkdown<S> for ImageNode<S>
+where
+    S: UnicodeString,
+{
+    fn fmt_markdown(
+        &self,
+        buffer: &mut S,
+        _: &MarkdownOptions,
+    ) -> Result<(), MarkdownError<S>> {
+        // Markdown images can't point at Matrix media, so use the alt text
+        buffer.push(self.alt.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_mxc_url;
+
+    #[test]
+    fn mxc_urls_are_valid() {
+        assert!(is_mxc_url("mxc://matrix.org/abc123"));
+        assert!(is_mxc_url("mxc://localhost:8448/a_b-C"));
+        assert!(is_mxc_url("mxc://[::1]:8448/media"));
+    }
+
+    #[test]
+    fn other_urls_are_not_mxc_urls() {
+        assert!(!is_mxc_url("https://matrix.org/image.png"));
+        assert!(!is_mxc_url("mxc://matrix.org"));
+        assert!(!is_mxc_url("mxc://matrix.org/"));
+        assert!(!is_mxc_url("mxc:///abc"));
+        assert!(!is_mxc_url("mxc://matrix.org/a/b"));
+        assert!(!is_mxc_url("mxc://matrix.org/a\"onerror=\"x"));
+    }
+}
@@ -99,6 +99,10 @@ where
     pub fn kind(&self) -> &MentionNodeKind<S> {
         &self.kind
     }
+
+    pub fn attributes(&self) -> &Vec<(S, S)> {
+        &self.attributes
+    }
 }
 
 impl<S> ToHtml<S> for MentionNode<S>
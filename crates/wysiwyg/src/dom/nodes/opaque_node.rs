@@ -0,0 +1,299 @@
+// Copyright 2023 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::composer_model::example_format::SelectionWriter;
+use crate::dom::dom_handle::DomHandle;
+use crate::dom::to_html::{ToHtml, ToHtmlExt, ToHtmlState};
+use crate::dom::to_markdown::{MarkdownError, MarkdownOptions, ToMarkdown};
+use crate::dom::to_plain_text::ToPlainText;
+use crate::dom::to_raw_text::ToRawText;
+use crate::dom::to_tree::ToTree;
+use crate::dom::unicode_string::{UnicodeStrExt, UnicodeStringExt};
+use crate::dom::UnicodeString;
+
+/// Elements that never have children or a closing tag
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+    "source", "track", "wbr",
+];
+
+pub(crate) fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS.contains(&name)
+}
+
+/// Elements the Matrix spec allows in the HTML of a message
+const ALLOWED_ELEMENTS: [&str; 39] = [
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "code",
+    "del",
+    "details",
+    "div",
+    "em",
+    "font",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "s",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Elements whose contents are thrown away along with them, rather than
+/// being kept without the element around them
+const DROPPED_WITH_CONTENTS: [&str; 8] = [
+    "iframe", "noscript", "object", "script", "style", "template", "textarea",
+    "title",
+];
+
+/// Allowed elements that start a new block
+const BLOCK_ELEMENTS: [&str; 9] = [
+    "caption", "details", "div", "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+pub(crate) fn is_allowed_element(name: &str) -> bool {
+    ALLOWED_ELEMENTS.contains(&name)
+}
+
+pub(crate) fn is_dropped_with_contents(name: &str) -> bool {
+    DROPPED_WITH_CONTENTS.contains(&name)
+}
+
+/// Whether the Matrix spec allows attribute [name] with [value] on
+/// [element]. Event handlers (`on*`) and `style` are never allowed.
+pub(crate) fn is_allowed_attribute(
+    element: &str,
+    name: &str,
+    value: &str,
+) -> bool {
+    match (element, name) {
+        ("font", "color" | "data-mx-bg-color" | "data-mx-color") => true,
+        (
+            "span",
+            "data-mx-bg-color" | "data-mx-color" | "data-mx-spoiler"
+            | "data-mx-maths",
+        ) => true,
+        ("div", "data-mx-maths") => true,
+        ("a", "name" | "target") => true,
+        ("a", "href") => ["https:", "http:", "ftp:", "mailto:", "magnet:"]
+            .iter()
+            .any(|scheme| value.to_lowercase().starts_with(scheme)),
+        ("img", "width" | "height" | "alt" | "title") => true,
+        ("img", "src") => value.starts_with("mxc://"),
+        ("ol", "start") => true,
+        ("code", "class") => value.starts_with("language-"),
+        _ => false,
+    }
+}
+
+/// An element we don't know how to edit (e.g. a heading or a spoiler), kept
+/// as it was parsed so that it isn't lost when the content is edited. Only
+/// the elements and attributes the Matrix spec allows are kept. Like a
+/// mention, it acts as a single character that can be deleted but not edited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    name: S,
+    attributes: Vec<(S, S)>,
+    inner_html: S,
+    text: S,
+    handle: DomHandle,
+}
+
+impl<S> OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    /// Create a new OpaqueNode for an element with tag [name], whose
+    /// children are [inner_html]. [text] is how it appears in plain text.
+    ///
+    /// NOTE: Its handle() will be unset until you call set_handle() or
+    /// append() it to another node.
+    pub fn new(
+        name: S,
+        attributes: Vec<(S, S)>,
+        inner_html: S,
+        text: S,
+    ) -> Self {
+        Self {
+            name,
+            attributes,
+            inner_html,
+            text,
+            handle: DomHandle::new_unset(),
+        }
+    }
+
+    pub fn name(&self) -> S {
+        self.name.clone()
+    }
+
+    pub fn attributes(&self) -> &Vec<(S, S)> {
+        &self.attributes
+    }
+
+    pub fn inner_html(&self) -> &S {
+        &self.inner_html
+    }
+
+    pub fn text(&self) -> &S {
+        &self.text
+    }
+
+    pub fn set_handle(&mut self, handle: DomHandle) {
+        self.handle = handle;
+    }
+
+    pub fn handle(&self) -> DomHandle {
+        self.handle.clone()
+    }
+
+    pub fn text_len(&self) -> usize {
+        // Like mentions, these can't be edited so they act as a single
+        // character.
+        1
+    }
+
+    /// Whether this is an element like `<div>` or `<h1>`, which is a block
+    /// in the tree but, like a horizontal rule, a leaf of length 1 in ranges.
+    pub fn is_block(&self) -> bool {
+        BLOCK_ELEMENTS.contains(&self.name.to_string().as_str())
+    }
+
+    fn is_void(&self) -> bool {
+        is_void_element(&self.name.to_string())
+    }
+}
+
+impl<S> ToHtml<S> for OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    fn fmt_html(
+        &self,
+        formatter: &mut S,
+        selection_writer: Option<&mut SelectionWriter>,
+        _: ToHtmlState,
+        as_message: bool,
+    ) {
+        let cur_pos = formatter.len();
+        let name = self.name.to_string();
+        if !is_allowed_element(&name) {
+            // Only written if created with a name the parser never uses
+            formatter.push(
+                html_escape::encode_text(&self.text.to_string()).as_ref(),
+            );
+            return;
+        }
+        let mut attributes: Vec<(S, S)> = self
+            .attributes
+            .iter()
+            .filter(|(k, v)| {
+                is_allowed_attribute(&name, &k.to_string(), &v.to_string())
+            })
+            .cloned()
+            .collect();
+        if !as_message {
+            attributes.push(("contenteditable".into(), "false".into()));
+        }
+        self.fmt_tag_open(&self.name, formatter, &Some(attributes));
+        if !self.is_void() {
+            formatter.push(self.inner_html.clone());
+            self.fmt_tag_close(&self.name, formatter);
+        }
+
+        if let Some(sel_writer) = selection_writer {
+            sel_writer.write_selection_opaque_node(formatter, cur_pos, self);
+        }
+    }
+}
+
+impl<S> ToRawText<S> for OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    fn to_raw_text(&self) -> S {
+        self.text.clone()
+    }
+}
+
+impl<S> ToPlainText<S> for OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    fn to_plain_text(&self) -> S {
+        self.text.clone()
+    }
+}
+
+impl<S> ToTree<S> for OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    fn to_tree_display(&self, continuous_positions: Vec<usize>) -> S {
+        let mut description = S::from("opaque ");
+        description.push(self.name.clone());
+        description.push(" \"");
+        description.push(self.text.clone());
+        description.push("\"");
+
+        self.tree_line(
+            description,
+            self.handle.raw().len(),
+            continuous_positions,
+        )
+    }
+}
+
+impl<S> ToMarkdown<S> for OpaqueNode<S>
+where
+    S: UnicodeString,
+{
+    fn fmt_markdown(
+        &self,
+        buffer: &mut S,
+        _: &MarkdownOptions,
+    ) -> Result<(), MarkdownError<S>> {
+        // Markdown allows inline HTML, so keep the element as it is
+        self.fmt_html(buffer, None, ToHtmlState::default(), true);
+        Ok(())
+    }
+}
@@ -26,7 +26,7 @@ use html_escape;
 use std::ops::Range;
 
 // categories of character for backspace/delete word
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum CharType {
     Whitespace,
     Punctuation,
@@ -127,6 +127,9 @@ where
         offset: usize,
         direction: &Direction,
     ) -> Option<char> {
+        if !self.offset_is_inside_node(offset, direction) {
+            return None;
+        }
         self.data()
             .chars()
             .nth(direction.get_index_from_cursor(offset))
@@ -194,7 +197,7 @@ where
 }
 
 /// Given a character, determine its type
-fn get_char_type(c: char) -> CharType {
+pub(crate) fn get_char_type(c: char) -> CharType {
     // in order to determine where a ctrl/opt + delete type operation finishes
     // we need to distinguish between whitespace (nb no newline characters), punctuation
     // and then everything else is treated as the same type
@@ -0,0 +1,347 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Repairs for Doms that break our invariants, e.g. because they were
+//! parsed from HTML we didn't write.
+
+use crate::dom::nodes::ContainerNodeKind;
+use crate::dom::unicode_string::UnicodeStrExt;
+use crate::dom::Dom;
+use crate::{DomHandle, DomNode, ListType, UnicodeString};
+
+impl<S> Dom<S>
+where
+    S: UnicodeString,
+{
+    /// Repair every invariant violation reported by [Dom::validate]:
+    /// - empty text nodes and empty formatting or link nodes are removed,
+    ///   unless they hold the formatting of an empty block,
+    /// - adjacent text nodes are merged,
+    /// - generic containers other than the root are replaced by their
+    ///   children,
+    /// - list items outside lists are wrapped in a list, and other nodes
+    ///   inside lists are moved into list items,
+    /// - table rows and cells are wrapped in their missing parents, empty
+    ///   tables are removed and short rows are padded with empty cells,
+    /// - inline nodes next to block nodes are wrapped in paragraphs.
+    ///
+    /// Block nodes inside inline nodes are left as they are.
+    pub fn normalize(&mut self) {
+        let document = self.document_mut();
+        let has_text = !document.has_no_text();
+        let children = document.remove_children();
+        let children =
+            normalize_children(&ContainerNodeKind::Generic, has_text, children);
+        document.insert_children(0, children);
+        document.set_handle(DomHandle::root());
+
+        self.wrap_inline_nodes_into_paragraphs_if_needed(&DomHandle::root());
+    }
+
+    /// Find where [location] is relative to the text of the leaves, which
+    /// [Dom::normalize] keeps in order, unlike the block boundaries between
+    /// them.
+    pub(crate) fn text_anchor(&self, location: usize) -> TextAnchor {
+        let mut text_before = 0;
+        let mut end_of_text = 0;
+        for (start, length) in self.leaf_spans() {
+            if start >= location {
+                break;
+            }
+            let inside = (location - start).min(length);
+            text_before += inside;
+            end_of_text = start + inside;
+        }
+        TextAnchor {
+            text_before,
+            gap: location - end_of_text,
+        }
+    }
+
+    /// The location of [anchor] in this Dom, staying before the next leaf
+    /// if there are fewer block boundaries than when it was found.
+    pub(crate) fn location_of_text_anchor(&self, anchor: TextAnchor) -> usize {
+        let mut remaining = anchor.text_before;
+        let mut end_of_text = 0;
+        for (start, length) in self.leaf_spans() {
+            if remaining == 0 {
+                return (end_of_text + anchor.gap).min(start);
+            }
+            let inside = remaining.min(length);
+            remaining -= inside;
+            end_of_text = start + inside;
+        }
+        (end_of_text + anchor.gap).min(self.text_len())
+    }
+
+    /// The start and length of each leaf with some text, in order.
+    fn leaf_spans(&self) -> Vec<(usize, usize)> {
+        let range = self.find_range(0, self.text_len());
+        let mut spans: Vec<(usize, usize)> = range
+            .leaves()
+            .filter(|leaf| leaf.length > 0)
+            .map(|leaf| (leaf.position, leaf.length))
+            .collect();
+        spans.sort();
+        spans
+    }
+}
+
+/// A location in a Dom, as the amount of leaf text before it, plus how far
+/// past the end of that text it is, e.g. when it is in an empty paragraph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TextAnchor {
+    text_before: usize,
+    gap: usize,
+}
+
+/// Normalize the children of a container of kind [parent_kind].
+/// [block_has_text] tells whether the closest block node containing these
+/// children has any text, in which case empty inline containers are useless.
+fn normalize_children<S>(
+    parent_kind: &ContainerNodeKind<S>,
+    block_has_text: bool,
+    children: Vec<DomNode<S>>,
+) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut normalized = Vec::new();
+    for child in children {
+        match child {
+            DomNode::Text(text) if text.data().is_empty() => {}
+            DomNode::Container(mut container) => {
+                let has_text = if container.is_block_node() {
+                    !container.has_no_text()
+                } else {
+                    block_has_text
+                };
+                let grandchildren = container.remove_children();
+                let grandchildren = normalize_children(
+                    container.kind(),
+                    has_text,
+                    grandchildren,
+                );
+                match container.kind() {
+                    ContainerNodeKind::Generic => {
+                        normalized.extend(grandchildren)
+                    }
+                    ContainerNodeKind::Formatting(_)
+                    | ContainerNodeKind::Link(_)
+                        if grandchildren.is_empty() && block_has_text => {}
+                    ContainerNodeKind::Table if grandchildren.is_empty() => {}
+                    ContainerNodeKind::Table => normalized.push(
+                        DomNode::Container(container.clone_with_new_children(
+                            pad_table_rows(grandchildren),
+                        )),
+                    ),
+                    _ => normalized.push(DomNode::Container(
+                        container.clone_with_new_children(grandchildren),
+                    )),
+                }
+            }
+            _ => normalized.push(child),
+        }
+    }
+
+    let normalized = match parent_kind {
+        ContainerNodeKind::List(_) => move_into_list_items(normalized),
+        ContainerNodeKind::Table => move_into_table_rows(normalized),
+        ContainerNodeKind::TableRow => move_into_table_cells(normalized),
+        _ => wrap_table_parts(wrap_list_items(normalized)),
+    };
+    merge_adjacent_text_nodes(normalized)
+}
+
+/// Put every child of a list that isn't a list item into one. Nested lists
+/// join the list item before them, as our indented lists do.
+fn move_into_list_items<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut list_items: Vec<DomNode<S>> = Vec::new();
+    for child in children {
+        if child.is_list_item() {
+            list_items.push(child);
+        } else if let (true, Some(DomNode::Container(prev))) =
+            (child.is_list(), list_items.last_mut())
+        {
+            let mut prev_children = prev.remove_children();
+            prev_children.push(child);
+            *prev = prev.clone_with_new_children(prev_children);
+        } else {
+            list_items.push(DomNode::new_list_item(vec![child]));
+        }
+    }
+    list_items
+}
+
+/// Wrap list items found outside of a list into the list before them, or a
+/// new unordered list if there isn't one.
+fn wrap_list_items<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut wrapped: Vec<DomNode<S>> = Vec::new();
+    for child in children {
+        if !child.is_list_item() {
+            wrapped.push(child);
+        } else if let Some(DomNode::Container(list)) =
+            wrapped.last_mut().filter(|prev| prev.is_list())
+        {
+            let mut list_items = list.remove_children();
+            list_items.push(child);
+            *list = list.clone_with_new_children(list_items);
+        } else {
+            wrapped.push(DomNode::new_list(ListType::Unordered, vec![child]));
+        }
+    }
+    wrapped
+}
+
+fn move_into_table_rows<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut rows: Vec<DomNode<S>> = Vec::new();
+    for child in wrap_cells_into_rows(children) {
+        if is_container_of_kind(&child, &ContainerNodeKind::TableRow) {
+            rows.push(child);
+        } else {
+            rows.push(DomNode::new_table_row(vec![DomNode::new_table_cell(
+                false,
+                vec![child],
+            )]));
+        }
+    }
+    rows
+}
+
+fn move_into_table_cells<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    children
+        .into_iter()
+        .map(|child| {
+            if is_container_of_kind(&child, &ContainerNodeKind::TableCell) {
+                child
+            } else {
+                DomNode::new_table_cell(false, vec![child])
+            }
+        })
+        .collect()
+}
+
+/// Wrap table cells and rows found outside of their parents into new rows
+/// and tables.
+fn wrap_table_parts<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut wrapped: Vec<DomNode<S>> = Vec::new();
+    for child in wrap_cells_into_rows(children) {
+        if !is_container_of_kind(&child, &ContainerNodeKind::TableRow) {
+            wrapped.push(child);
+        } else if let Some(DomNode::Container(table)) =
+            wrapped.last_mut().filter(|prev| {
+                is_container_of_kind(prev, &ContainerNodeKind::Table)
+            })
+        {
+            let mut rows = table.remove_children();
+            rows.push(child);
+            *table = table.clone_with_new_children(pad_table_rows(rows));
+        } else {
+            wrapped.push(DomNode::new_table(vec![child]));
+        }
+    }
+    wrapped
+}
+
+/// Group runs of consecutive table cells into new rows.
+fn wrap_cells_into_rows<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut wrapped: Vec<DomNode<S>> = Vec::new();
+    let mut cells: Vec<DomNode<S>> = Vec::new();
+    for child in children {
+        if is_container_of_kind(&child, &ContainerNodeKind::TableCell) {
+            cells.push(child);
+            continue;
+        }
+        if !cells.is_empty() {
+            wrapped.push(DomNode::new_table_row(std::mem::take(&mut cells)));
+        }
+        wrapped.push(child);
+    }
+    if !cells.is_empty() {
+        wrapped.push(DomNode::new_table_row(cells));
+    }
+    wrapped
+}
+
+/// Add empty cells to the end of any row shorter than the longest one.
+fn pad_table_rows<S>(rows: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let width = rows
+        .iter()
+        .filter_map(DomNode::as_container)
+        .map(|row| row.children().len())
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    rows.into_iter()
+        .map(|row| match row {
+            DomNode::Container(mut row) if row.children().len() < width => {
+                let is_header = !row.is_empty() && row.is_table_header_row();
+                let mut cells = row.remove_children();
+                cells.resize_with(width, || {
+                    DomNode::new_table_cell(is_header, Vec::new())
+                });
+                DomNode::Container(row.clone_with_new_children(cells))
+            }
+            _ => row,
+        })
+        .collect()
+}
+
+fn merge_adjacent_text_nodes<S>(children: Vec<DomNode<S>>) -> Vec<DomNode<S>>
+where
+    S: UnicodeString,
+{
+    let mut merged: Vec<DomNode<S>> = Vec::new();
+    for child in children {
+        if let (Some(DomNode::Text(prev)), DomNode::Text(text)) =
+            (merged.last_mut(), &child)
+        {
+            prev.push(text);
+        } else {
+            merged.push(child);
+        }
+    }
+    merged
+}
+
+fn is_container_of_kind<S>(
+    node: &DomNode<S>,
+    kind: &ContainerNodeKind<S>,
+) -> bool
+where
+    S: UnicodeString,
+{
+    matches!(node, DomNode::Container(c) if c.kind() == kind)
+}
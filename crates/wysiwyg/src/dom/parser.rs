@@ -37,6 +37,7 @@ mod panode_text;
 #[cfg(feature = "sys")]
 mod paqual_name;
 mod parse;
+mod parse_warning;
 
 // Group all re-exports for `feature = "sys"`.
 #[cfg(feature = "sys")]
@@ -57,3 +58,7 @@ mod sys {
 use sys::*;
 
 pub use parse::parse;
+pub use parse::parse_with_diagnostics;
+pub use parse::parse_with_reply_fallback;
+pub use parse_warning::ParseWarning;
+pub use parse_warning::ParseWarningKind;
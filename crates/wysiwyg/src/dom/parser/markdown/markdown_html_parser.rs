@@ -27,6 +27,7 @@ impl MarkdownHTMLParser {
 
         let mut options = Options::empty();
         options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TABLES);
 
         let markdown = markdown.to_string();
 
@@ -38,9 +39,13 @@ impl MarkdownHTMLParser {
 
         // By default, there is a `<p>…</p>\n` around the HTML content. That's the
         // correct way to handle a text block in Markdown. But it breaks our
-        // assumption regarding the HTML markup. So let's remove it.
+        // assumption regarding the HTML markup. So let's remove it, when it's
+        // the only block.
         let html = {
-            if !html.starts_with("<p>") {
+            if !html.starts_with("<p>")
+                || !html.ends_with("</p>\n")
+                || html.matches("<p>").count() > 1
+            {
                 &html[..]
             } else {
                 let p = "<p>".len();
@@ -57,7 +62,17 @@ impl MarkdownHTMLParser {
             .replace("<ol>\n", "<ol>")
             .replace("</ol>\n", "</ol>")
             .replace("</li>\n", "</li>")
-            .replace("<br />\n", "<br />");
+            .replace("</p>\n", "</p>")
+            .replace("<br />\n", "<br />")
+            .replace("<hr />\n", "<hr />")
+            .replace("<table>\n", "<table>")
+            .replace("</table>\n", "</table>")
+            .replace("<thead>\n", "<thead>")
+            .replace("</thead>\n", "</thead>")
+            .replace("<tbody>\n", "<tbody>")
+            .replace("</tbody>\n", "</tbody>")
+            .replace("<tr>\n", "<tr>")
+            .replace("</tr>\n", "</tr>");
 
         Ok(S::try_from(html).unwrap())
     }
@@ -19,6 +19,8 @@ use super::{paqual_name, PaDomHandle, PaDomNode, PaNodeContainer, PaNodeText};
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct PaDom {
     pub(crate) nodes: Vec<PaDomNode>,
+    /// The parent of each node in [Self::nodes], if it has one
+    pub(crate) parents: Vec<Option<PaDomHandle>>,
     pub(crate) document_handle: PaDomHandle,
 }
 
@@ -28,6 +30,7 @@ impl PaDom {
             name: paqual_name(""),
             attrs: Vec::new(),
             children: Vec::new(),
+            line: None,
         });
         Self::from(document)
     }
@@ -35,6 +38,7 @@ impl PaDom {
     pub(crate) fn from(document: PaDomNode) -> Self {
         Self {
             nodes: vec![document],
+            parents: vec![None],
             document_handle: PaDomHandle(0),
         }
     }
@@ -64,17 +68,131 @@ impl PaDom {
         &self.document_handle
     }
 
+    /// The handles of the children of [handle], which is empty for text and
+    /// comments.
+    pub(crate) fn children(&self, handle: &PaDomHandle) -> &[PaDomHandle] {
+        match self.get_node(handle) {
+            PaDomNode::Container(n) | PaDomNode::Document(n) => &n.children,
+            PaDomNode::Text(_) | PaDomNode::Comment(_) => &[],
+        }
+    }
+
+    fn children_mut(
+        &mut self,
+        handle: &PaDomHandle,
+    ) -> Option<&mut Vec<PaDomHandle>> {
+        match self.get_mut_node(handle) {
+            PaDomNode::Container(n) | PaDomNode::Document(n) => {
+                Some(&mut n.children)
+            }
+            PaDomNode::Text(_) | PaDomNode::Comment(_) => None,
+        }
+    }
+
+    /// The node whose children include [handle], if any.
+    pub(crate) fn parent_of(
+        &self,
+        handle: &PaDomHandle,
+    ) -> Option<&PaDomHandle> {
+        self.parents.get(handle.0).and_then(Option::as_ref)
+    }
+
+    /// The child of the same parent just before [handle], if any.
+    pub(crate) fn previous_sibling(
+        &self,
+        handle: &PaDomHandle,
+    ) -> Option<&PaDomHandle> {
+        let siblings = self.children(self.parent_of(handle)?);
+        let index = siblings.iter().position(|h| h == handle)?;
+        siblings.get(index.checked_sub(1)?)
+    }
+
     pub(crate) fn add_node(&mut self, node: PaDomNode) -> PaDomHandle {
         let handle = PaDomHandle(self.nodes.len());
         self.nodes.push(node);
+        self.parents.push(None);
         handle
     }
 
+    /// Add [child] after the other children of [parent], taking it out of
+    /// its old parent if it had one.
+    pub(crate) fn append_child(
+        &mut self,
+        parent: &PaDomHandle,
+        child: PaDomHandle,
+    ) -> Result<(), String> {
+        if self.children_mut(parent).is_none() {
+            return Err(cant_have_children(parent));
+        }
+        self.detach(&child);
+        self.parents[child.0] = Some(parent.clone());
+        if let Some(children) = self.children_mut(parent) {
+            children.push(child);
+        }
+        Ok(())
+    }
+
+    /// Add [node] to the parent of [sibling], just before it, taking it out
+    /// of its old parent if it had one.
+    pub(crate) fn insert_before(
+        &mut self,
+        sibling: &PaDomHandle,
+        node: PaDomHandle,
+    ) -> Result<(), String> {
+        let parent = self
+            .parent_of(sibling)
+            .cloned()
+            .ok_or_else(|| format!("Node has no parent! {sibling:?}"))?;
+        self.detach(&node);
+        let children = self
+            .children_mut(&parent)
+            .ok_or_else(|| cant_have_children(&parent))?;
+        let index = children
+            .iter()
+            .position(|h| h == sibling)
+            .unwrap_or(children.len());
+        children.insert(index, node.clone());
+        self.parents[node.0] = Some(parent);
+        Ok(())
+    }
+
+    /// Take [handle] out of its parent, if it has one.
+    pub(crate) fn detach(&mut self, handle: &PaDomHandle) {
+        if let Some(parent) = self.parents[handle.0].take() {
+            if let Some(children) = self.children_mut(&parent) {
+                children.retain(|h| h != handle);
+            }
+        }
+    }
+
+    /// Move the children of [from] after the children of [to].
+    pub(crate) fn move_children(
+        &mut self,
+        from: &PaDomHandle,
+        to: &PaDomHandle,
+    ) -> Result<(), String> {
+        if self.children_mut(to).is_none() {
+            return Err(cant_have_children(to));
+        }
+        let children = self
+            .children_mut(from)
+            .map(std::mem::take)
+            .unwrap_or_default();
+        for child in &children {
+            self.parents[child.0] = Some(to.clone());
+        }
+        if let Some(to_children) = self.children_mut(to) {
+            to_children.extend(children);
+        }
+        Ok(())
+    }
+
     pub(crate) fn create_element(
         &mut self,
         name: html5ever::QualName,
         attrs: Vec<html5ever::Attribute>,
         _flags: html5ever::tree_builder::ElementFlags,
+        line: Option<u64>,
     ) -> PaDomHandle {
         // We ignore flags
         let node = match name.local.as_ref() {
@@ -93,6 +211,7 @@ impl PaDom {
                     })
                     .collect(),
                 children: Vec::new(),
+                line,
             }),
         };
 
@@ -100,6 +219,10 @@ impl PaDom {
     }
 }
 
+fn cant_have_children(handle: &PaDomHandle) -> String {
+    format!("Text and comments can't have children! {handle:?}")
+}
+
 impl Display for PaDom {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("")
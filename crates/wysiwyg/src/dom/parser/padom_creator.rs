@@ -25,6 +25,7 @@ pub(crate) type DomCreationResult = Result<PaDom, PaDomCreationError>;
 
 pub(crate) struct PaDomCreator {
     state: PaDomCreationError,
+    current_line: u64,
 }
 
 impl PaDomCreator {
@@ -44,6 +45,7 @@ impl Default for PaDomCreator {
     fn default() -> Self {
         Self {
             state: PaDomCreationError::new(),
+            current_line: 1,
         }
     }
 }
@@ -78,11 +80,22 @@ impl TreeSink for PaDomCreator {
         attrs: Vec<Attribute>,
         flags: ElementFlags,
     ) -> Self::Handle {
-        self.state.dom.create_element(name, attrs, flags)
+        self.state.dom.create_element(
+            name,
+            attrs,
+            flags,
+            Some(self.current_line),
+        )
+    }
+
+    fn set_current_line(&mut self, line_number: u64) {
+        self.current_line = line_number;
     }
 
     fn create_comment(&mut self, _text: StrTendril) -> Self::Handle {
-        todo!("Comments not yet supported")
+        self.state
+            .dom
+            .add_node(PaDomNode::Comment(Some(self.current_line)))
     }
 
     fn create_pi(
@@ -90,7 +103,9 @@ impl TreeSink for PaDomCreator {
         _target: StrTendril,
         _data: StrTendril,
     ) -> Self::Handle {
-        todo!("create_pi not yet supported")
+        // Processing instructions only exist in XML, so we treat them like
+        // comments and drop them later.
+        self.create_comment(StrTendril::new())
     }
 
     fn append(
@@ -100,17 +115,13 @@ impl TreeSink for PaDomCreator {
     ) {
         match child {
             NodeOrText::AppendNode(child) => {
-                match self.state.dom.get_mut_node(parent) {
-                    PaDomNode::Container(p) => p.children.push(child),
-                    PaDomNode::Document(p) => p.children.push(child),
-                    PaDomNode::Text(_) => {
-                        panic!("Appending node to text! {:?}", parent)
-                    }
+                if let Err(e) = self.state.dom.append_child(parent, child) {
+                    self.state.parse_errors.push(e);
                 }
             }
             NodeOrText::AppendText(tendril) => {
                 let text_handle = match self.state.dom.get_node(parent) {
-                    PaDomNode::Document(_) => None,
+                    PaDomNode::Document(_) | PaDomNode::Comment(_) => None,
                     PaDomNode::Text(_) => Some(parent.clone()),
                     PaDomNode::Container(PaNodeContainer {
                         children, ..
@@ -141,12 +152,10 @@ impl TreeSink for PaDomCreator {
                             content: tendril.as_ref().to_owned(),
                         }));
 
-                    match self.state.dom.get_mut_node(parent) {
-                        PaDomNode::Container(p) => p.children.push(new_handle),
-                        PaDomNode::Document(p) => p.children.push(new_handle),
-                        PaDomNode::Text(_) => {
-                            panic!("parent changed from container to text!")
-                        }
+                    if let Err(e) =
+                        self.state.dom.append_child(parent, new_handle)
+                    {
+                        self.state.parse_errors.push(e);
                     }
                 }
             }
@@ -155,11 +164,15 @@ impl TreeSink for PaDomCreator {
 
     fn append_based_on_parent_node(
         &mut self,
-        _element: &Self::Handle,
-        _prev_element: &Self::Handle,
-        _child: NodeOrText<Self::Handle>,
+        element: &Self::Handle,
+        prev_element: &Self::Handle,
+        child: NodeOrText<Self::Handle>,
     ) {
-        todo!("append_based_on_parent_node not yet supported")
+        if self.state.dom.parent_of(element).is_some() {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
     }
 
     fn append_doctype_to_document(
@@ -168,14 +181,13 @@ impl TreeSink for PaDomCreator {
         _public_id: StrTendril,
         _system_id: StrTendril,
     ) {
-        todo!("append_doctype_to_document not yet supported")
+        // Doctypes are not part of a Dom, so there is nothing to keep
     }
 
-    fn get_template_contents(
-        &mut self,
-        _target: &Self::Handle,
-    ) -> Self::Handle {
-        todo!("get_template_contents not yet supported")
+    fn get_template_contents(&mut self, target: &Self::Handle) -> Self::Handle {
+        // We don't keep a separate document fragment for the contents of a
+        // <template>: they become children of the element itself.
+        target.clone()
     }
 
     fn same_node(&self, x: &Self::Handle, y: &Self::Handle) -> bool {
@@ -188,10 +200,39 @@ impl TreeSink for PaDomCreator {
 
     fn append_before_sibling(
         &mut self,
-        _sibling: &Self::Handle,
-        _new_node: NodeOrText<Self::Handle>,
+        sibling: &Self::Handle,
+        new_node: NodeOrText<Self::Handle>,
     ) {
-        todo!("append_before_sibling not yet supported")
+        let new_handle = match new_node {
+            NodeOrText::AppendNode(handle) => handle,
+            NodeOrText::AppendText(tendril) => {
+                // Join the text onto a text node just before the sibling
+                if let Some(prev) =
+                    self.state.dom.previous_sibling(sibling).cloned()
+                {
+                    if let PaDomNode::Text(p) =
+                        self.state.dom.get_mut_node(&prev)
+                    {
+                        p.content += tendril.as_ref();
+                        return;
+                    }
+                }
+                self.state.dom.add_node(PaDomNode::Text(PaNodeText {
+                    content: tendril.as_ref().to_owned(),
+                }))
+            }
+        };
+
+        if let Err(e) =
+            self.state.dom.insert_before(sibling, new_handle.clone())
+        {
+            // Keep the node at the end of the document rather than losing it
+            self.state.parse_errors.push(e);
+            let document = self.state.dom.document_handle().clone();
+            if let Err(e) = self.state.dom.append_child(&document, new_handle) {
+                self.state.parse_errors.push(e);
+            }
+        }
     }
 
     fn add_attrs_if_missing(
@@ -221,16 +262,18 @@ impl TreeSink for PaDomCreator {
         }
     }
 
-    fn remove_from_parent(&mut self, _target: &Self::Handle) {
-        todo!("remove_from_parent not yet supported")
+    fn remove_from_parent(&mut self, target: &Self::Handle) {
+        self.state.dom.detach(target);
     }
 
     fn reparent_children(
         &mut self,
-        _node: &Self::Handle,
-        _new_parent: &Self::Handle,
+        node: &Self::Handle,
+        new_parent: &Self::Handle,
     ) {
-        todo!("reparent_children not yet supported")
+        if let Err(e) = self.state.dom.move_children(node, new_parent) {
+            self.state.parse_errors.push(e);
+        }
     }
 }
 
@@ -265,7 +308,7 @@ mod test {
                     PaDomNode::Document(p) => {
                         children.extend(p.children.iter().cloned());
                     }
-                    PaDomNode::Text(_) => {}
+                    PaDomNode::Text(_) | PaDomNode::Comment(_) => {}
                 }
                 for ch in children {
                     find_used(dom_container, deleted_indices, &ch)
@@ -318,14 +361,25 @@ mod test {
                             *c = remap_handle(&deleted_indices, c);
                         }
                     }
-                    PaDomNode::Text(_) => {}
+                    PaDomNode::Text(_) | PaDomNode::Comment(_) => {}
                 }
             }
 
             let new_document_handle =
                 remap_handle(&deleted_indices, &self.document_handle);
 
+            let new_parents = self
+                .parents
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !deleted_indices.contains(i))
+                .map(|(_, parent)| {
+                    parent.as_ref().map(|p| remap_handle(&deleted_indices, p))
+                })
+                .collect();
+
             self.nodes = new_nodes;
+            self.parents = new_parents;
             self.document_handle = new_document_handle;
         }
     }
@@ -346,16 +400,8 @@ mod test {
         ) -> PaDomHandle {
             let child = ret.add_node(test_node.dom_node);
 
-            let parent = ret.get_mut_node(parent);
-            match parent {
-                PaDomNode::Container(p) => {
-                    p.children.push(child.clone());
-                }
-                PaDomNode::Document(p) => {
-                    p.children.push(child.clone());
-                }
-                PaDomNode::Text(_) => panic!("Parent can't be a text node"),
-            }
+            ret.append_child(parent, child.clone())
+                .expect("Parent can't be a text node");
 
             for ch in test_node.children {
                 add(ret, &child, ch);
@@ -381,6 +427,7 @@ mod test {
                 name: paqual_name(name),
                 attrs: Vec::new(),
                 children: Vec::new(),
+                line: None,
             }),
             children: children.into_iter().cloned().collect(),
         }
@@ -399,6 +446,7 @@ mod test {
                     .map(|&(n, v)| (n.to_owned(), v.to_owned()))
                     .collect(),
                 children: Vec::new(),
+                line: None,
             }),
             children: children.into_iter().cloned().collect(),
         }
@@ -415,6 +463,12 @@ mod test {
 
     fn d(mut node: PaDom) -> String {
         node.gc();
+        // Source lines are not part of these comparisons
+        for n in node.nodes.iter_mut() {
+            if let PaDomNode::Container(n) = n {
+                n.line = None;
+            }
+        }
         format!("{:?}", node)
     }
 
@@ -479,6 +533,47 @@ mod test {
         )
     }
 
+    #[test]
+    fn formatting_inside_a_table_is_moved_before_it() {
+        let dom =
+            PaDomCreator::parse("<table><b>x</b><tr><td>a</td></tr></table>")
+                .unwrap_err()
+                .dom;
+        let html = &dom.children(dom.document_handle())[0];
+        let names: Vec<&str> = dom
+            .children(html)
+            .iter()
+            .map(|child| dom.get_node(child).name().local.as_ref())
+            .collect();
+        assert_eq!(names, vec!["b", "table"]);
+        let b = &dom.children(html)[0];
+        assert_eq!(dom.parent_of(b), Some(html));
+    }
+
+    #[test]
+    fn appending_before_a_node_with_no_parent_appends_to_the_document() {
+        let mut creator = PaDomCreator::default();
+        let orphan = creator.state.dom.add_node(PaDomNode::Comment(None));
+        creator.append_before_sibling(
+            &orphan,
+            NodeOrText::AppendText(StrTendril::from("a")),
+        );
+        let result = creator.finish().unwrap_err();
+        assert_eq!(result.parse_errors.len(), 1);
+        assert_eq!(d(result.dom), d(doc(&[tx("a")])));
+    }
+
+    #[test]
+    fn appending_to_a_text_node_is_an_error() {
+        let mut creator = PaDomCreator::default();
+        let text = creator.state.dom.add_node(PaDomNode::Text(PaNodeText {
+            content: "a".to_owned(),
+        }));
+        let child = creator.state.dom.add_node(PaDomNode::Comment(None));
+        creator.append(&text, NodeOrText::AppendNode(child));
+        assert_eq!(creator.finish().unwrap_err().parse_errors.len(), 1);
+    }
+
     // Note: more complex tests are in parse, because it's more ergonomic to
     // work with a real Dom instead of PaDom, because it converts back to HTML
     // nicely.
@@ -24,6 +24,10 @@ pub(crate) enum PaDomNode {
     Container(PaNodeContainer),
     Document(PaNodeContainer),
     Text(PaNodeText),
+    /// A comment or processing instruction, with the line in the source
+    /// HTML where it started. These are dropped when the PaDom is converted
+    /// into a [crate::Dom].
+    Comment(Option<u64>),
 }
 
 impl PaDomNode {
@@ -31,7 +35,7 @@ impl PaDomNode {
         match self {
             PaDomNode::Container(n) => &n.name,
             PaDomNode::Document(n) => &n.name,
-            PaDomNode::Text(_) => q(&TEXT, ""),
+            PaDomNode::Text(_) | PaDomNode::Comment(_) => q(&TEXT, ""),
         }
     }
 }
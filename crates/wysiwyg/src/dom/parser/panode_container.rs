@@ -21,6 +21,8 @@ pub(crate) struct PaNodeContainer {
     pub(crate) name: QualName,
     pub(crate) attrs: Vec<(String, String)>,
     pub(crate) children: Vec<PaDomHandle>,
+    /// The line in the source HTML where this element started
+    pub(crate) line: Option<u64>,
 }
 impl PaNodeContainer {
     pub(crate) fn get_attr(&self, name: &str) -> Option<&str> {
@@ -13,20 +13,94 @@
 // limitations under the License.
 
 use crate::dom::dom_creation_error::HtmlParseError;
-use crate::dom::nodes::dom_node::DomNodeKind::CodeBlock;
+use crate::dom::nodes::dom_node::DomNodeKind::{self, CodeBlock};
+use crate::dom::nodes::image_node::is_mxc_url;
 use crate::dom::nodes::ContainerNode;
+use crate::dom::parser::{ParseWarning, ParseWarningKind};
+use crate::dom::unicode_string::UnicodeStringExt;
 use crate::dom::Dom;
-use crate::{DomHandle, DomNode, UnicodeString};
+use crate::reply_fallback::strip_reply_fallback;
+use crate::{
+    parse_hex_color, DomHandle, DomNode, InlineFormatType, UnicodeString,
+};
 
+/// Parse [html] into a [Dom]. Any reply fallback (`<mx-reply>`) is removed.
 pub fn parse<S>(html: &str) -> Result<Dom<S>, HtmlParseError>
+where
+    S: UnicodeString,
+{
+    parse_with_reply_fallback(html).map(|(dom, _)| dom)
+}
+
+/// Parse [html] into a [Dom], also returning the HTML of the reply
+/// fallback (`<mx-reply>`) that was removed from it, if any.
+pub fn parse_with_reply_fallback<S>(
+    html: &str,
+) -> Result<(Dom<S>, Option<S>), HtmlParseError>
+where
+    S: UnicodeString,
+{
+    let (html, reply_fallback) = strip_reply_fallback(html);
+    let (dom, _) = parse_html(&html)?;
+    Ok((dom, reply_fallback.map(|html| S::from(html.as_str()))))
+}
+
+/// Parse [html] into a [Dom], also returning a [ParseWarning] for each
+/// part of [html] that could not be kept exactly as it was. Any reply
+/// fallback (`<mx-reply>`) is removed.
+pub fn parse_with_diagnostics<S>(
+    html: &str,
+) -> Result<(Dom<S>, Vec<ParseWarning>), HtmlParseError>
+where
+    S: UnicodeString,
+{
+    let (stripped, reply_fallback) = strip_reply_fallback(html);
+    let (dom, mut warnings) = parse_html(&stripped)?;
+    if reply_fallback.is_some() {
+        let reply_line = html
+            .to_ascii_lowercase()
+            .find("<mx-reply")
+            .map_or(1, |start| line_at(html, start));
+        // Lines after the reply fallback moved up when it was removed
+        let removed_lines =
+            html.matches('\n').count() - stripped.matches('\n').count();
+        for line in warnings.iter_mut().filter_map(|w| w.line.as_mut()) {
+            if *line >= reply_line {
+                *line += removed_lines as u64;
+            }
+        }
+        warnings.insert(
+            0,
+            ParseWarning::new(
+                ParseWarningKind::ReplyFallbackRemoved,
+                Some("mx-reply"),
+                Some(reply_line),
+            ),
+        );
+    }
+    Ok((dom, warnings))
+}
+
+/// The 1-based line of the byte at [offset] in [text]
+fn line_at(text: &str, offset: usize) -> u64 {
+    text[..offset].matches('\n').count() as u64 + 1
+}
+
+fn parse_html<S>(
+    html: &str,
+) -> Result<(Dom<S>, Vec<ParseWarning>), HtmlParseError>
 where
     S: UnicodeString,
 {
     cfg_if::cfg_if! {
         if #[cfg(feature = "sys")] {
-            sys::HtmlParser::default().parse(html)
+            let mut parser = sys::HtmlParser::default();
+            let dom = parser.parse(html)?;
+            Ok((dom, parser.warnings))
         } else if #[cfg(all(feature = "js", target_arch = "wasm32"))] {
-            js::HtmlParser::default().parse(html)
+            let mut parser = js::HtmlParser::default();
+            let dom = parser.parse(html)?;
+            Ok((dom, parser.warnings))
         } else {
             unreachable!("The `sys` or `js` are mutually exclusive, and one of them must be enabled.")
         }
@@ -41,17 +115,23 @@ mod sys {
     use super::*;
     use crate::dom::nodes::dom_node::DomNodeKind;
     use crate::dom::nodes::dom_node::DomNodeKind::CodeBlock;
+    use crate::dom::nodes::opaque_node::{
+        is_allowed_attribute, is_allowed_element, is_dropped_with_contents,
+        is_void_element,
+    };
     use crate::dom::nodes::{ContainerNode, DomNode};
     use crate::dom::parser::sys::PaNodeText;
     use crate::ListType;
 
     pub(super) struct HtmlParser {
         current_path: Vec<DomNodeKind>,
+        pub(super) warnings: Vec<ParseWarning>,
     }
     impl HtmlParser {
         pub(super) fn default() -> Self {
             Self {
                 current_path: Vec::new(),
+                warnings: Vec::new(),
             }
         }
 
@@ -121,8 +201,19 @@ mod sys {
                         // Special case for code block, translate '\n' into <br /> nodes
                         let is_inside_code_block =
                             self.current_path.contains(&CodeBlock);
-                        convert_text(&text.content, node, is_inside_code_block);
+                        convert_text(
+                            &text.content,
+                            node,
+                            is_inside_code_block,
+                            panode.line,
+                            &mut self.warnings,
+                        );
                     }
+                    PaDomNode::Comment(line) => self.warn(
+                        ParseWarningKind::NodeDropped,
+                        Some("#comment"),
+                        *line,
+                    ),
                 }
             }
         }
@@ -139,7 +230,9 @@ mod sys {
             let cur_path_idx = self.current_path.len();
             let tag = child.name.local.as_ref();
             match tag {
-                "b" | "code" | "del" | "em" | "i" | "strong" | "u" => {
+                "b" | "code" | "del" | "em" | "i" | "strong" | "sub"
+                | "sup" | "u" => {
+                    self.warn_dropped_attributes(child, &[]);
                     let formatting_node = Self::new_formatting(tag);
                     if tag == "code" && self.current_path.contains(&CodeBlock) {
                         self.convert_children(padom, child, Some(node));
@@ -155,9 +248,30 @@ mod sys {
                     }
                 }
                 "br" => {
+                    self.warn_dropped_attributes(child, &[]);
                     node.append_child(Self::new_line_break());
                 }
+                "hr" => {
+                    self.warn_dropped_attributes(child, &[]);
+                    node.append_child(new_horizontal_rule(&self.current_path));
+                }
+                "img" => match new_image(&child.attrs) {
+                    Some(image) => {
+                        self.warn_dropped_attributes(child, IMAGE_ATTRIBUTES);
+                        node.append_child(image);
+                    }
+                    None => {
+                        self.warn(
+                            ParseWarningKind::UnknownElement,
+                            Some(tag),
+                            child.line,
+                        );
+                        let opaque = self.new_opaque(padom, child);
+                        node.append_child(opaque);
+                    }
+                },
                 "ol" | "ul" => {
+                    self.warn_dropped_attributes(child, &[]);
                     self.current_path.push(DomNodeKind::List);
                     node.append_child(Self::new_list(tag));
                     self.convert_children(
@@ -168,6 +282,7 @@ mod sys {
                     self.current_path.remove(cur_path_idx);
                 }
                 "li" => {
+                    self.warn_dropped_attributes(child, &[]);
                     self.current_path.push(DomNodeKind::ListItem);
                     node.append_child(Self::new_list_item());
                     self.convert_children(
@@ -196,6 +311,11 @@ mod sys {
                     };
 
                     if is_mention && matches!(text, Some(_)) {
+                        // Mentions regenerate their own attributes
+                        self.warn_dropped_attributes(
+                            child,
+                            &["href", "contenteditable"],
+                        );
                         self.current_path.push(DomNodeKind::Mention);
                         let mention = Self::new_mention(child, text.unwrap());
                         node.append_child(mention);
@@ -213,6 +333,12 @@ mod sys {
                     self.current_path.remove(cur_path_idx);
                 }
                 "pre" => {
+                    self.warn_dropped_attributes(child, &[]);
+                    self.warn(
+                        ParseWarningKind::CodeBlockRestructured,
+                        Some(tag),
+                        child.line,
+                    );
                     self.current_path.push(DomNodeKind::CodeBlock);
                     node.append_child(Self::new_code_block());
                     self.convert_children(
@@ -223,6 +349,7 @@ mod sys {
                     self.current_path.remove(cur_path_idx);
                 }
                 "blockquote" => {
+                    self.warn_dropped_attributes(child, &[]);
                     self.current_path.push(DomNodeKind::Quote);
                     node.append_child(Self::new_quote());
                     self.convert_children(
@@ -232,12 +359,30 @@ mod sys {
                     );
                     self.current_path.remove(cur_path_idx);
                 }
+                "table" => {
+                    self.warn_dropped_attributes(child, &[]);
+                    self.current_path.push(DomNodeKind::Table);
+                    let mut rows = Vec::new();
+                    self.convert_table_rows(padom, child, &mut rows);
+                    self.current_path.remove(cur_path_idx);
+                    match new_table(rows) {
+                        Some(table) => {
+                            node.append_child(table);
+                        }
+                        None => self.warn(
+                            ParseWarningKind::NodeDropped,
+                            Some(tag),
+                            child.line,
+                        ),
+                    }
+                }
                 "html" => {
                     // Skip the html tag - add its children to the
                     // current node directly.
                     self.convert(padom, child, node);
                 }
                 "p" => {
+                    self.warn_dropped_attributes(child, &[]);
                     self.current_path.push(DomNodeKind::Paragraph);
                     node.append_child(Self::new_paragraph());
                     self.convert_children(
@@ -247,13 +392,152 @@ mod sys {
                     );
                     self.current_path.remove(cur_path_idx);
                 }
+                _ if color_formats(tag, &child.attrs).is_some() => {
+                    let formats =
+                        color_formats(tag, &child.attrs).unwrap_or_default();
+                    let mut container = node;
+                    for format in formats {
+                        self.current_path
+                            .push(DomNodeKind::Formatting(format.clone()));
+                        container.append_child(DomNode::new_formatting(
+                            format,
+                            Vec::new(),
+                        ));
+                        container = last_container_mut_in(container)
+                            .expect("Colour became non-container!");
+                    }
+                    self.convert(padom, child, container);
+                    self.current_path.truncate(cur_path_idx);
+                }
+                _ if is_allowed_element(tag) => {
+                    // Keep tags we don't recognise as they are, so that
+                    // they are not lost when the content is edited
+                    self.warn(
+                        ParseWarningKind::UnknownElement,
+                        Some(tag),
+                        child.line,
+                    );
+                    let opaque = self.new_opaque(padom, child);
+                    node.append_child(opaque);
+                }
+                _ if is_dropped_with_contents(tag) => {
+                    self.warn(
+                        ParseWarningKind::NodeDropped,
+                        Some(tag),
+                        child.line,
+                    );
+                }
                 _ => {
-                    // Ignore tags we don't recognise
-                    // We should log - see internal task PSU-741
+                    // Elements Matrix doesn't allow are removed, keeping
+                    // what is inside them
+                    self.warn(
+                        ParseWarningKind::ElementUnwrapped,
+                        Some(tag),
+                        child.line,
+                    );
+                    self.convert(padom, child, node);
                 }
             };
         }
 
+        /// Collect the cells of each row of [element], looking inside
+        /// `<thead>`, `<tbody>` and `<tfoot>`. Anything else is dropped.
+        fn convert_table_rows<S>(
+            &mut self,
+            padom: &PaDom,
+            element: &PaNodeContainer,
+            rows: &mut Vec<Vec<DomNode<S>>>,
+        ) where
+            S: UnicodeString,
+        {
+            for child_handle in &element.children {
+                // Text between rows is only the source's indentation
+                let PaDomNode::Container(child) = padom.get_node(child_handle)
+                else {
+                    continue;
+                };
+                match child.name.local.as_ref() {
+                    "thead" | "tbody" | "tfoot" => {
+                        self.warn_dropped_attributes(child, &[]);
+                        self.convert_table_rows(padom, child, rows);
+                    }
+                    "tr" => {
+                        self.warn_dropped_attributes(child, &[]);
+                        let cells = self.convert_table_cells(padom, child);
+                        rows.push(cells);
+                    }
+                    tag => self.warn(
+                        ParseWarningKind::NodeDropped,
+                        Some(tag),
+                        child.line,
+                    ),
+                }
+            }
+        }
+
+        fn convert_table_cells<S>(
+            &mut self,
+            padom: &PaDom,
+            row: &PaNodeContainer,
+        ) -> Vec<DomNode<S>>
+        where
+            S: UnicodeString,
+        {
+            let mut cells = Vec::new();
+            for child_handle in &row.children {
+                let PaDomNode::Container(child) = padom.get_node(child_handle)
+                else {
+                    continue;
+                };
+                let tag = child.name.local.as_ref();
+                if tag != "th" && tag != "td" {
+                    self.warn(
+                        ParseWarningKind::NodeDropped,
+                        Some(tag),
+                        child.line,
+                    );
+                    continue;
+                }
+                self.warn_dropped_attributes(child, &[]);
+                let cur_path_idx = self.current_path.len();
+                self.current_path.push(DomNodeKind::TableCell);
+                let mut cell =
+                    ContainerNode::new_table_cell(tag == "th", Vec::new());
+                // The cell gets its real handle when the table is added
+                cell.set_handle(DomHandle::root());
+                self.convert(padom, child, &mut cell);
+                self.current_path.remove(cur_path_idx);
+                cells.push(DomNode::Container(cell));
+            }
+            cells
+        }
+
+        fn warn(
+            &mut self,
+            kind: ParseWarningKind,
+            name: Option<&str>,
+            line: Option<u64>,
+        ) {
+            self.warnings.push(ParseWarning::new(kind, name, line));
+        }
+
+        /// Warn about each attribute of [element] that is not in [kept]
+        fn warn_dropped_attributes(
+            &mut self,
+            element: &PaNodeContainer,
+            kept: &[&str],
+        ) {
+            for (name, _) in &element.attrs {
+                if !kept.contains(&name.as_str()) {
+                    self.warn(
+                        ParseWarningKind::AttributeDropped,
+                        Some(name),
+                        element.line,
+                    );
+                }
+            }
+        }
+
         /// Recurse into panode's children and convert them too
         fn convert_children<S>(
             &mut self,
@@ -324,13 +608,120 @@ mod sys {
             )
         }
 
+        /// Create an opaque node holding the element and its children
+        fn new_opaque<S>(
+            &mut self,
+            padom: &PaDom,
+            element: &PaNodeContainer,
+        ) -> DomNode<S>
+        where
+            S: UnicodeString,
+        {
+            let name = element.name.local.as_ref();
+            let text = if name == "img" {
+                element.get_attr("alt").unwrap_or("").to_owned()
+            } else {
+                padom_text_content(padom, element)
+            };
+            let attributes = self.allowed_attributes(element);
+            let inner_html = self.opaque_inner_html(padom, element);
+            DomNode::new_opaque(
+                name.into(),
+                attributes
+                    .iter()
+                    .map(|(k, v)| (k.as_str().into(), v.as_str().into()))
+                    .collect(),
+                inner_html.as_str().into(),
+                text.as_str().into(),
+            )
+        }
+
+        /// The attributes of [element] the Matrix spec allows. The others
+        /// are reported as dropped.
+        fn allowed_attributes(
+            &mut self,
+            element: &PaNodeContainer,
+        ) -> Vec<(String, String)> {
+            let name = element.name.local.as_ref();
+            let (allowed, dropped): (Vec<_>, Vec<_>) = element
+                .attrs
+                .iter()
+                .cloned()
+                .partition(|(k, v)| is_allowed_attribute(name, k, v));
+            for (k, _) in dropped {
+                // Added when generating HTML for the editor
+                if k != "contenteditable" {
+                    self.warn(
+                        ParseWarningKind::AttributeDropped,
+                        Some(&k),
+                        element.line,
+                    );
+                }
+            }
+            allowed
+        }
+
+        /// Serialise the children of [element] as HTML, keeping only the
+        /// elements and attributes the Matrix spec allows
+        fn opaque_inner_html(
+            &mut self,
+            padom: &PaDom,
+            element: &PaNodeContainer,
+        ) -> String {
+            let mut html = String::new();
+            for child_handle in &element.children {
+                match padom.get_node(child_handle) {
+                    PaDomNode::Container(child) => {
+                        let name = child.name.local.as_ref();
+                        if is_dropped_with_contents(name) {
+                            self.warn(
+                                ParseWarningKind::NodeDropped,
+                                Some(name),
+                                child.line,
+                            );
+                        } else if !is_allowed_element(name) {
+                            self.warn(
+                                ParseWarningKind::ElementUnwrapped,
+                                Some(name),
+                                child.line,
+                            );
+                            html.push_str(
+                                &self.opaque_inner_html(padom, child),
+                            );
+                        } else {
+                            let attributes = self.allowed_attributes(child);
+                            push_start_tag(&mut html, name, &attributes);
+                            if !is_void_element(name) {
+                                html.push_str(
+                                    &self.opaque_inner_html(padom, child),
+                                );
+                                html.push_str(&format!("</{}>", name));
+                            }
+                        }
+                    }
+                    PaDomNode::Text(text) => {
+                        html.push_str(&html_escape::encode_text(&text.content))
+                    }
+                    PaDomNode::Comment(line) => self.warn(
+                        ParseWarningKind::NodeDropped,
+                        Some("#comment"),
+                        *line,
+                    ),
+                    PaDomNode::Document(_) => {
+                        panic!("Found a document inside a document!")
+                    }
+                }
+            }
+            html
+        }
+
         /// Create a list node
         fn new_list<S>(tag: &str) -> DomNode<S>
         where
             S: UnicodeString,
         {
             DomNode::Container(ContainerNode::new_list(
-                ListType::try_from(S::from(tag)).unwrap(),
+                ListType::try_from(tag).unwrap(),
                 Vec::new(),
             ))
         }
@@ -377,6 +768,24 @@ mod sys {
         }
     }
 
+    /// Concatenate all the text inside [element]
+    fn padom_text_content(padom: &PaDom, element: &PaNodeContainer) -> String {
+        element
+            .children
+            .iter()
+            .map(|child_handle| match padom.get_node(child_handle) {
+                PaDomNode::Container(child)
+                    if is_dropped_with_contents(child.name.local.as_ref()) =>
+                {
+                    String::new()
+                }
+                PaDomNode::Container(child) => padom_text_content(padom, child),
+                PaDomNode::Text(text) => text.content.clone(),
+                PaDomNode::Document(_) | PaDomNode::Comment(_) => String::new(),
+            })
+            .collect()
+    }
+
     #[cfg(test)]
     mod test {
         use crate::dom::parser::parse::sys::HtmlParser;
@@ -676,6 +1085,123 @@ fn post_process_code_blocks_lines<S: UnicodeString>(
     dom
 }
 
+/// The colour formats described by the attributes of a `<span>` or
+/// `<font>`, outermost first. None if the element has any other attribute,
+/// or an invalid colour, as it must then be kept as it is.
+fn color_formats(
+    tag: &str,
+    attrs: &[(String, String)],
+) -> Option<Vec<InlineFormatType>> {
+    if tag != "span" && tag != "font" {
+        return None;
+    }
+    let mut text_color = None;
+    let mut background_color = None;
+    for (name, value) in attrs {
+        match (tag, name.as_str()) {
+            (_, "data-mx-color") => text_color = Some(parse_hex_color(value)?),
+            (_, "data-mx-bg-color") => {
+                background_color = Some(parse_hex_color(value)?)
+            }
+            // The deprecated way of colouring text, only for `<font>`
+            ("font", "color") => {
+                let color = parse_hex_color(value)?;
+                text_color.get_or_insert(color);
+            }
+            _ => return None,
+        }
+    }
+    let formats: Vec<_> = text_color
+        .map(InlineFormatType::TextColor)
+        .into_iter()
+        .chain(background_color.map(InlineFormatType::BackgroundColor))
+        .collect();
+    (!formats.is_empty()).then_some(formats)
+}
+
+/// A horizontal rule, if one is allowed at [current_path]. Rules can't be
+/// placed inside inline formatting, lists, code blocks or tables, so a line
+/// break takes their place there.
+/// Write the start tag of an element called [name] with [attributes]
+fn push_start_tag(
+    html: &mut String,
+    name: &str,
+    attributes: &[(String, String)],
+) {
+    html.push('<');
+    html.push_str(name);
+    for (k, v) in attributes {
+        html.push_str(&format!(
+            " {}=\"{}\"",
+            k,
+            html_escape::encode_double_quoted_attribute(v)
+        ));
+    }
+    html.push('>');
+}
+
+fn new_horizontal_rule<S: UnicodeString>(
+    current_path: &[DomNodeKind],
+) -> DomNode<S> {
+    match current_path.last() {
+        None | Some(DomNodeKind::Quote) => DomNode::new_horizontal_rule(),
+        _ => DomNode::new_line_break(),
+    }
+}
+
+/// The `<img>` attributes kept on image nodes
+const IMAGE_ATTRIBUTES: &[&str] =
+    &["src", "alt", "data-mx-emoticon", "title", "width", "height"];
+
+/// An image with the given `<img>` [attributes], if its `src` is an mxc
+/// URL. Other images are kept as opaque nodes.
+fn new_image<S: UnicodeString>(
+    attributes: &[(String, String)],
+) -> Option<DomNode<S>> {
+    let attribute = |name: &str| {
+        attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let src = attribute("src").filter(|src| is_mxc_url(src))?;
+    let alt = attribute("alt").unwrap_or_default();
+    let others = attributes
+        .iter()
+        .filter(|(k, _)| {
+            k != "src" && k != "alt" && IMAGE_ATTRIBUTES.contains(&k.as_str())
+        })
+        .map(|(k, v)| (k.as_str().into(), v.as_str().into()))
+        .collect();
+    Some(DomNode::new_image(src.into(), alt.into(), others))
+}
+
+/// Build a table from the cells of each of its rows, padding short rows
+/// with empty cells so that they all have the same width. None if there
+/// are no cells at all.
+fn new_table<S: UnicodeString>(
+    rows: Vec<Vec<DomNode<S>>>,
+) -> Option<DomNode<S>> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if width == 0 {
+        return None;
+    }
+    let rows = rows
+        .into_iter()
+        .map(|mut cells| {
+            let is_header = !cells.is_empty()
+                && cells.iter().all(|cell| {
+                    matches!(cell, DomNode::Container(c) if c.is_table_header_cell())
+                });
+            cells.resize_with(width, || {
+                DomNode::new_table_cell(is_header, Vec::new())
+            });
+            DomNode::new_table_row(cells)
+        })
+        .collect();
+    Some(DomNode::new_table(rows))
+}
+
 #[cfg(feature = "sys")]
 fn last_container_mut_in<S: UnicodeString>(
     node: &mut ContainerNode<S>,
@@ -687,13 +1213,19 @@ fn convert_text<S: UnicodeString>(
     text: &str,
     node: &mut ContainerNode<S>,
     is_inside_code_block: bool,
+    line: Option<u64>,
+    warnings: &mut Vec<ParseWarning>,
 ) {
+    let nbsp_removed =
+        || ParseWarning::new(ParseWarningKind::NbspRemoved, None, line);
     if is_inside_code_block {
         let text_nodes: Vec<_> = text.split('\n').collect();
         let text_nodes_len = text_nodes.len();
         for (i, str) in text_nodes.into_iter().enumerate() {
             let is_nbsp = str == "\u{A0}" || str == "&nbsp;";
-            if !str.is_empty() && !is_nbsp {
+            if is_nbsp {
+                warnings.push(nbsp_removed());
+            } else if !str.is_empty() {
                 let text_node = DomNode::new_text(str.into());
                 node.append_child(text_node);
             }
@@ -705,6 +1237,7 @@ fn convert_text<S: UnicodeString>(
         let contents = text;
         let is_nbsp = contents == "\u{A0}" || contents == "&nbsp;";
         if is_nbsp {
+            warnings.push(nbsp_removed());
             return;
         }
 
@@ -712,8 +1245,20 @@ fn convert_text<S: UnicodeString>(
             if i > 0 {
                 node.append_child(DomNode::new_at_room_mention(vec![]));
             }
-            if !part.is_empty() {
-                node.append_child(DomNode::new_text(part.into()));
+            if part.is_empty() {
+                continue;
+            }
+            // Text either side of a removed element or comment becomes one
+            // text node
+            match node.last_child_mut() {
+                Some(DomNode::Text(last)) if i == 0 => {
+                    let mut data = last.data().to_owned();
+                    data.push(part);
+                    last.set_data(data);
+                }
+                _ => {
+                    node.append_child(DomNode::new_text(part.into()));
+                }
             }
         }
     }
@@ -723,6 +1268,10 @@ fn convert_text<S: UnicodeString>(
 mod js {
     use super::*;
     use crate::dom::nodes::dom_node::DomNodeKind;
+    use crate::dom::nodes::opaque_node::{
+        is_allowed_attribute, is_allowed_element, is_dropped_with_contents,
+        is_void_element,
+    };
     use crate::{
         dom::nodes::{ContainerNode, DomNode},
         InlineFormatType, ListType,
@@ -733,11 +1282,13 @@ mod js {
 
     pub(super) struct HtmlParser {
         current_path: Vec<DomNodeKind>,
+        pub(super) warnings: Vec<ParseWarning>,
     }
     impl HtmlParser {
         pub(super) fn default() -> Self {
             Self {
                 current_path: Vec::new(),
+                warnings: Vec::new(),
             }
         }
 
@@ -806,9 +1357,37 @@ mod js {
 
                 match node.node_name().as_str() {
                     "BR" => {
+                        self.warn_dropped_attributes(&node, &[]);
                         dom.append_child(DomNode::new_line_break());
                     }
 
+                    "HR" => {
+                        self.warn_dropped_attributes(&node, &[]);
+                        dom.append_child(new_horizontal_rule(
+                            &self.current_path,
+                        ));
+                    }
+
+                    "IMG" => {
+                        if let Some(image) =
+                            new_image(&element_attributes(&node))
+                        {
+                            self.warn_dropped_attributes(
+                                &node,
+                                IMAGE_ATTRIBUTES,
+                            );
+                            dom.append_child(image);
+                        } else if let Some(element) = node.dyn_ref::<Element>()
+                        {
+                            self.warn(
+                                ParseWarningKind::UnknownElement,
+                                Some("img"),
+                            );
+                            let opaque = self.new_opaque(element);
+                            dom.append_child(opaque);
+                        }
+                    }
+
                     "#text" => match node.node_value() {
                         Some(value) => {
                             let is_inside_code_block =
@@ -817,6 +1396,8 @@ mod js {
                                 value.as_str(),
                                 dom,
                                 is_inside_code_block,
+                                None,
+                                &mut self.warnings,
                             );
                         }
                         _ => {}
@@ -828,6 +1409,15 @@ mod js {
                         let mut attributes = vec![];
                         let valid_attributes =
                             ["contenteditable", "data-mention-type", "style"];
+                        self.warn_dropped_attributes(
+                            &node,
+                            &[
+                                "contenteditable",
+                                "data-mention-type",
+                                "style",
+                                "href",
+                            ],
+                        );
 
                         for attr in valid_attributes.into_iter() {
                             if node
@@ -886,6 +1476,7 @@ mod js {
                     }
 
                     "OL" => {
+                        self.warn_dropped_attributes(&node, &[]);
                         self.current_path.push(DomNodeKind::List);
                         dom.append_child(DomNode::Container(
                             ContainerNode::new_list(
@@ -898,6 +1489,7 @@ mod js {
                     }
 
                     "UL" => {
+                        self.warn_dropped_attributes(&node, &[]);
                         self.current_path.push(DomNodeKind::List);
                         dom.append_child(DomNode::Container(
                             ContainerNode::new_list(
@@ -910,6 +1502,7 @@ mod js {
                     }
 
                     "LI" => {
+                        self.warn_dropped_attributes(&node, &[]);
                         self.current_path.push(DomNodeKind::ListItem);
                         dom.append_child(DomNode::Container(
                             ContainerNode::new_list_item(
@@ -921,6 +1514,11 @@ mod js {
                     }
 
                     "PRE" => {
+                        self.warn_dropped_attributes(&node, &[]);
+                        self.warn(
+                            ParseWarningKind::CodeBlockRestructured,
+                            Some("pre"),
+                        );
                         self.current_path.push(DomNodeKind::CodeBlock);
                         let children = node.child_nodes();
                         let children = if children.length() == 1
@@ -941,6 +1539,7 @@ mod js {
                     }
 
                     "BLOCKQUOTE" => {
+                        self.warn_dropped_attributes(&node, &[]);
                         self.current_path.push(DomNodeKind::Quote);
                         dom.append_child(DomNode::Container(
                             ContainerNode::new_quote(
@@ -951,7 +1550,25 @@ mod js {
                         self.current_path.pop();
                     }
 
+                    "TABLE" => {
+                        self.warn_dropped_attributes(&node, &[]);
+                        self.current_path.push(DomNodeKind::Table);
+                        let mut rows = Vec::new();
+                        self.convert_table_rows(node.child_nodes(), &mut rows)?;
+                        self.current_path.pop();
+                        match new_table(rows) {
+                            Some(table) => {
+                                dom.append_child(table);
+                            }
+                            None => self.warn(
+                                ParseWarningKind::NodeDropped,
+                                Some("table"),
+                            ),
+                        }
+                    }
+
                     "P" => {
+                        self.warn_dropped_attributes(&node, &[]);
                         self.current_path.push(DomNodeKind::Paragraph);
                         dom.append_child(DomNode::Container(
                             ContainerNode::new_paragraph(
@@ -963,22 +1580,70 @@ mod js {
                     }
 
                     node_name => {
-                        let children_nodes =
-                            self.convert(node.child_nodes())?.take_children();
-
                         let formatting_kind = match node_name {
                             "STRONG" | "B" => InlineFormatType::Bold,
                             "EM" | "I" => InlineFormatType::Italic,
                             "DEL" => InlineFormatType::StrikeThrough,
                             "U" => InlineFormatType::Underline,
                             "CODE" => InlineFormatType::InlineCode,
+                            "SUP" => InlineFormatType::Superscript,
+                            "SUB" => InlineFormatType::Subscript,
+                            _ if color_formats(
+                                &node_name.to_lowercase(),
+                                &element_attributes(&node),
+                            )
+                            .is_some() =>
+                            {
+                                self.convert_color(&node, dom)?;
+                                continue;
+                            }
                             _ => {
-                                return Err(Error::UnknownNode(
-                                    node_name.to_owned(),
-                                ))
+                                let tag = node_name.to_lowercase();
+                                if let Some(element) = node.dyn_ref::<Element>()
+                                {
+                                    if is_allowed_element(&tag) {
+                                        // Keep tags we don't recognise as
+                                        // they are, so that they are not
+                                        // lost when the content is edited
+                                        self.warn(
+                                            ParseWarningKind::UnknownElement,
+                                            Some(&tag),
+                                        );
+                                        let opaque = self.new_opaque(element);
+                                        dom.append_child(opaque);
+                                    } else if is_dropped_with_contents(&tag) {
+                                        self.warn(
+                                            ParseWarningKind::NodeDropped,
+                                            Some(&tag),
+                                        );
+                                    } else {
+                                        // Elements Matrix doesn't allow are
+                                        // removed, keeping what is inside
+                                        // them
+                                        self.warn(
+                                            ParseWarningKind::ElementUnwrapped,
+                                            Some(&tag),
+                                        );
+                                        self.convert_container(
+                                            node.child_nodes(),
+                                            dom,
+                                        )?;
+                                    }
+                                } else {
+                                    // Comments are dropped
+                                    self.warn(
+                                        ParseWarningKind::NodeDropped,
+                                        Some(node_name),
+                                    );
+                                }
+                                continue;
                             }
                         };
 
+                        self.warn_dropped_attributes(&node, &[]);
+                        let children_nodes =
+                            self.convert(node.child_nodes())?.take_children();
+
                         self.current_path.push(DomNodeKind::Formatting(
                             formatting_kind.clone(),
                         ));
@@ -996,6 +1661,207 @@ mod js {
 
             Ok(())
         }
+
+        /// Convert a `<span>` or `<font>` into nested colour formats
+        fn convert_color<S>(
+            &mut self,
+            node: &web_sys::Node,
+            dom: &mut ContainerNode<S>,
+        ) -> Result<(), Error>
+        where
+            S: UnicodeString,
+        {
+            let formats = color_formats(
+                &node.node_name().to_lowercase(),
+                &element_attributes(node),
+            )
+            .unwrap_or_default();
+            for format in &formats {
+                self.current_path
+                    .push(DomNodeKind::Formatting(format.clone()));
+            }
+            let mut children =
+                self.convert(node.child_nodes())?.take_children();
+            for format in formats.into_iter().rev() {
+                children = vec![DomNode::new_formatting(format, children)];
+                self.current_path.pop();
+            }
+            dom.append_children(children);
+            Ok(())
+        }
+
+        /// Collect the cells of each row in [nodes], looking inside
+        /// `<thead>`, `<tbody>` and `<tfoot>`. Anything else is dropped.
+        fn convert_table_rows<S>(
+            &mut self,
+            nodes: NodeList,
+            rows: &mut Vec<Vec<DomNode<S>>>,
+        ) -> Result<(), Error>
+        where
+            S: UnicodeString,
+        {
+            for nth in 0..nodes.length() {
+                let node = nodes.get(nth).unwrap();
+                match node.node_name().as_str() {
+                    // Text between rows is only the source's indentation
+                    "#text" => {}
+                    "THEAD" | "TBODY" | "TFOOT" => {
+                        self.warn_dropped_attributes(&node, &[]);
+                        self.convert_table_rows(node.child_nodes(), rows)?;
+                    }
+                    "TR" => {
+                        self.warn_dropped_attributes(&node, &[]);
+                        let cells = self.convert_table_cells(&node)?;
+                        rows.push(cells);
+                    }
+                    node_name => self.warn(
+                        ParseWarningKind::NodeDropped,
+                        Some(&node_name.to_lowercase()),
+                    ),
+                }
+            }
+            Ok(())
+        }
+
+        fn convert_table_cells<S>(
+            &mut self,
+            row: &web_sys::Node,
+        ) -> Result<Vec<DomNode<S>>, Error>
+        where
+            S: UnicodeString,
+        {
+            let nodes = row.child_nodes();
+            let mut cells = Vec::new();
+            for nth in 0..nodes.length() {
+                let node = nodes.get(nth).unwrap();
+                let is_header = match node.node_name().as_str() {
+                    "TH" => true,
+                    "TD" => false,
+                    "#text" => continue,
+                    node_name => {
+                        self.warn(
+                            ParseWarningKind::NodeDropped,
+                            Some(&node_name.to_lowercase()),
+                        );
+                        continue;
+                    }
+                };
+                self.warn_dropped_attributes(&node, &[]);
+                self.current_path.push(DomNodeKind::TableCell);
+                let children =
+                    self.convert(node.child_nodes())?.take_children();
+                self.current_path.pop();
+                cells.push(DomNode::new_table_cell(is_header, children));
+            }
+            Ok(cells)
+        }
+
+        /// Source positions are not available from the browser's parser
+        fn warn(&mut self, kind: ParseWarningKind, name: Option<&str>) {
+            self.warnings.push(ParseWarning::new(kind, name, None));
+        }
+
+        /// Create an opaque node holding the element and its children
+        fn new_opaque<S>(&mut self, element: &Element) -> DomNode<S>
+        where
+            S: UnicodeString,
+        {
+            let name = element.tag_name().to_lowercase();
+            let text = if name == "img" {
+                element.get_attribute("alt").unwrap_or_default()
+            } else {
+                opaque_text_content(element)
+            };
+            let attributes = self.allowed_attributes(element);
+            let inner_html = self.opaque_inner_html(element);
+            DomNode::new_opaque(
+                name.into(),
+                attributes
+                    .iter()
+                    .map(|(k, v)| (k.as_str().into(), v.as_str().into()))
+                    .collect(),
+                inner_html.as_str().into(),
+                text.as_str().into(),
+            )
+        }
+
+        /// The attributes of [element] the Matrix spec allows. The others
+        /// are reported as dropped.
+        fn allowed_attributes(
+            &mut self,
+            element: &Element,
+        ) -> Vec<(String, String)> {
+            let name = element.tag_name().to_lowercase();
+            let (allowed, dropped): (Vec<_>, Vec<_>) =
+                element_attributes(element)
+                    .into_iter()
+                    .partition(|(k, v)| is_allowed_attribute(&name, k, v));
+            for (k, _) in dropped {
+                // Added when generating HTML for the editor
+                if k != "contenteditable" {
+                    self.warn(ParseWarningKind::AttributeDropped, Some(&k));
+                }
+            }
+            allowed
+        }
+
+        /// Serialise the children of [node] as HTML, keeping only the
+        /// elements and attributes the Matrix spec allows
+        fn opaque_inner_html(&mut self, node: &web_sys::Node) -> String {
+            let mut html = String::new();
+            let children = node.child_nodes();
+            for child in (0..children.length()).filter_map(|i| children.get(i))
+            {
+                let name = child.node_name().to_lowercase();
+                match child.dyn_ref::<Element>() {
+                    Some(_) if is_dropped_with_contents(&name) => {
+                        self.warn(ParseWarningKind::NodeDropped, Some(&name));
+                    }
+                    Some(_) if !is_allowed_element(&name) => {
+                        self.warn(
+                            ParseWarningKind::ElementUnwrapped,
+                            Some(&name),
+                        );
+                        html.push_str(&self.opaque_inner_html(&child));
+                    }
+                    Some(element) => {
+                        let attributes = self.allowed_attributes(element);
+                        push_start_tag(&mut html, &name, &attributes);
+                        if !is_void_element(&name) {
+                            html.push_str(&self.opaque_inner_html(&child));
+                            html.push_str(&format!("</{}>", name));
+                        }
+                    }
+                    None if child.node_type() == web_sys::Node::TEXT_NODE => {
+                        html.push_str(&html_escape::encode_text(
+                            &child.node_value().unwrap_or_default(),
+                        ));
+                    }
+                    None => {
+                        self.warn(ParseWarningKind::NodeDropped, Some(&name));
+                    }
+                }
+            }
+            html
+        }
+
+        /// Warn about each attribute of [node] that is not in [kept]
+        fn warn_dropped_attributes(
+            &mut self,
+            node: &web_sys::Node,
+            kept: &[&str],
+        ) {
+            let Some(element) = node.dyn_ref::<Element>() else {
+                return;
+            };
+            let attrs = element.attributes();
+            for attr in (0..attrs.length()).filter_map(|i| attrs.item(i)) {
+                let name = attr.name();
+                if !kept.contains(&name.as_str()) {
+                    self.warn(ParseWarningKind::AttributeDropped, Some(&name));
+                }
+            }
+        }
     }
 
     fn to_dom_creation_error<E>(error: E) -> HtmlParseError
@@ -1007,9 +1873,42 @@ mod js {
         }
     }
 
+    /// The name and value of each attribute of [node]
+    fn element_attributes(node: &web_sys::Node) -> Vec<(String, String)> {
+        let Some(element) = node.dyn_ref::<Element>() else {
+            return Vec::new();
+        };
+        let attrs = element.attributes();
+        (0..attrs.length())
+            .filter_map(|i| attrs.item(i))
+            .map(|attr| (attr.name(), attr.value()))
+            .collect()
+    }
+
+    /// Concatenate all the text inside [node], leaving out elements that
+    /// are dropped with their contents
+    fn opaque_text_content(node: &web_sys::Node) -> String {
+        let children = node.child_nodes();
+        (0..children.length())
+            .filter_map(|i| children.get(i))
+            .map(|child| match child.node_type() {
+                web_sys::Node::TEXT_NODE => {
+                    child.node_value().unwrap_or_default()
+                }
+                web_sys::Node::ELEMENT_NODE
+                    if !is_dropped_with_contents(
+                        &child.node_name().to_lowercase(),
+                    ) =>
+                {
+                    opaque_text_content(&child)
+                }
+                _ => String::new(),
+            })
+            .collect()
+    }
+
     enum Error {
         NoBody,
-        UnknownNode(String),
     }
 
     impl fmt::Display for Error {
@@ -1021,10 +1920,6 @@ mod js {
                         "The `Document` does not have a `<body>` element"
                     )
                 }
-
-                Self::UnknownNode(node_name) => {
-                    write!(formatter, "Node `{node_name}` is not supported")
-                }
             }
         }
     }
@@ -0,0 +1,85 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// Something that was changed or thrown away while parsing HTML into a
+/// [crate::Dom], as reported by [crate::parse_with_diagnostics].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    /// The tag or attribute name the warning is about, if any.
+    pub name: Option<String>,
+    /// The 1-based line in the source HTML, where the parser backend can
+    /// provide it.
+    pub line: Option<u64>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseWarningKind {
+    /// An element the editor does not support. It is kept as an opaque
+    /// node.
+    UnknownElement,
+    /// An element Matrix messages may not contain. What was inside it is
+    /// kept without it.
+    ElementUnwrapped,
+    /// An attribute of a supported element that is not kept.
+    AttributeDropped,
+    /// A node that is neither an element nor text, e.g. a comment.
+    NodeDropped,
+    /// Text that only contained a non-breaking space.
+    NbspRemoved,
+    /// A code block whose lines were split into paragraphs.
+    CodeBlockRestructured,
+    /// A reply fallback (`<mx-reply>`).
+    ReplyFallbackRemoved,
+}
+
+impl ParseWarning {
+    pub fn new(
+        kind: ParseWarningKind,
+        name: Option<&str>,
+        line: Option<u64>,
+    ) -> Self {
+        Self {
+            kind,
+            name: name.map(String::from),
+            line,
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            ParseWarningKind::UnknownElement => "unknown element kept as is",
+            ParseWarningKind::ElementUnwrapped => "element removed",
+            ParseWarningKind::AttributeDropped => "attribute dropped",
+            ParseWarningKind::NodeDropped => "node dropped",
+            ParseWarningKind::NbspRemoved => "non-breaking space removed",
+            ParseWarningKind::CodeBlockRestructured => {
+                "code block restructured"
+            }
+            ParseWarningKind::ReplyFallbackRemoved => "reply fallback removed",
+        };
+        write!(f, "{message}")?;
+        if let Some(name) = &self.name {
+            write!(f, ": {name}")?;
+        }
+        if let Some(line) = self.line {
+            write!(f, " (line {line})")?;
+        }
+        Ok(())
+    }
+}
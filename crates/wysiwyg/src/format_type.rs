@@ -12,15 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{ComposerAction, UnicodeString};
+use strum_macros::EnumIter;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+use crate::{ComposerAction, UnknownTagError};
+
+#[derive(Debug, Clone, EnumIter, Eq, PartialEq)]
 pub enum InlineFormatType {
     Bold,
     Italic,
     StrikeThrough,
     Underline,
     InlineCode,
+    Superscript,
+    Subscript,
+    /// A text colour, as a lowercase `#rrggbb` hex string. Pending formats
+    /// use an empty string to remove the colour.
+    #[strum(disabled)]
+    TextColor(String),
+    /// A background colour, as a lowercase `#rrggbb` hex string. Pending
+    /// formats use an empty string to remove the colour.
+    #[strum(disabled)]
+    BackgroundColor(String),
 }
 
 impl InlineFormatType {
@@ -31,6 +43,10 @@ impl InlineFormatType {
             InlineFormatType::StrikeThrough => "del",
             InlineFormatType::Underline => "u",
             InlineFormatType::InlineCode => "code",
+            InlineFormatType::Superscript => "sup",
+            InlineFormatType::Subscript => "sub",
+            InlineFormatType::TextColor(_)
+            | InlineFormatType::BackgroundColor(_) => "span",
         }
     }
 
@@ -41,21 +57,114 @@ impl InlineFormatType {
             InlineFormatType::StrikeThrough => ComposerAction::StrikeThrough,
             InlineFormatType::Underline => ComposerAction::Underline,
             InlineFormatType::InlineCode => ComposerAction::InlineCode,
+            InlineFormatType::Superscript => ComposerAction::Superscript,
+            InlineFormatType::Subscript => ComposerAction::Subscript,
+            InlineFormatType::TextColor(_) => ComposerAction::TextColor,
+            InlineFormatType::BackgroundColor(_) => {
+                ComposerAction::BackgroundColor
+            }
         }
     }
-}
 
-impl<S: UnicodeString> From<S> for InlineFormatType {
-    fn from(value: S) -> Self {
-        match value.to_string().as_str() {
-            "b" | "strong" => InlineFormatType::Bold,
-            "i" | "em" => InlineFormatType::Italic,
-            "del" => InlineFormatType::StrikeThrough,
-            "u" => InlineFormatType::Underline,
-            "code" => InlineFormatType::InlineCode,
-            _ => {
-                panic!("Unknown format type {}", value.to_string().as_str());
+    /// The attribute holding the value of a colour format.
+    pub(crate) fn color_attribute(&self) -> Option<(&'static str, &str)> {
+        match self {
+            InlineFormatType::TextColor(color) => {
+                Some(("data-mx-color", color))
             }
+            InlineFormatType::BackgroundColor(color) => {
+                Some(("data-mx-bg-color", color))
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_color(&self) -> bool {
+        self.color_attribute().is_some()
+    }
+
+    /// True if both formats are the same format, ignoring colour values.
+    pub(crate) fn is_same_kind(&self, other: &InlineFormatType) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// Validate a `#rgb` or `#rrggbb` hex colour, returning it in its
+/// lowercase `#rrggbb` form.
+pub fn parse_hex_color(color: &str) -> Option<String> {
+    let digits = color.strip_prefix('#')?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits = digits.to_ascii_lowercase();
+    match digits.len() {
+        3 => Some(digits.chars().fold(String::from("#"), |mut hex, c| {
+            hex.push(c);
+            hex.push(c);
+            hex
+        })),
+        6 => Some(format!("#{digits}")),
+        _ => None,
+    }
+}
+
+impl TryFrom<&str> for InlineFormatType {
+    type Error = UnknownTagError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "b" | "strong" => Ok(InlineFormatType::Bold),
+            "i" | "em" => Ok(InlineFormatType::Italic),
+            "del" => Ok(InlineFormatType::StrikeThrough),
+            "u" => Ok(InlineFormatType::Underline),
+            "code" => Ok(InlineFormatType::InlineCode),
+            "sup" => Ok(InlineFormatType::Superscript),
+            "sub" => Ok(InlineFormatType::Subscript),
+            tag => Err(UnknownTagError {
+                tag: tag.to_owned(),
+            }),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_colors_are_normalised() {
+        assert_eq!(parse_hex_color("#FF00aa"), Some("#ff00aa".into()));
+        assert_eq!(parse_hex_color("#F0a"), Some("#ff00aa".into()));
+    }
+
+    #[test]
+    fn invalid_hex_colors_are_rejected() {
+        assert_eq!(parse_hex_color("ff00aa"), None);
+        assert_eq!(parse_hex_color("#ff00a"), None);
+        assert_eq!(parse_hex_color("#gg00aa"), None);
+        assert_eq!(parse_hex_color("red"), None);
+        assert_eq!(parse_hex_color("#+1f"), None);
+    }
+
+    #[test]
+    fn format_types_are_read_from_tags() {
+        assert_eq!(
+            InlineFormatType::try_from("strong"),
+            Ok(InlineFormatType::Bold)
+        );
+        assert_eq!(
+            InlineFormatType::try_from("sub"),
+            Ok(InlineFormatType::Subscript)
+        );
+    }
+
+    #[test]
+    fn unknown_format_tags_are_rejected() {
+        assert_eq!(
+            InlineFormatType::try_from("blink"),
+            Err(UnknownTagError {
+                tag: String::from("blink")
+            })
+        );
+    }
+}
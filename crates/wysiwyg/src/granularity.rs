@@ -0,0 +1,30 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// How far [crate::ComposerModel::move_cursor] moves the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// A single grapheme, or a whole mention.
+    Character,
+    /// To the edge of the next word, using the same rules as
+    /// [crate::ComposerModel::backspace_word].
+    Word,
+    /// To the start or end of the current line. Lines are only ended by
+    /// line breaks and blocks, since the model knows nothing about wrapping.
+    Line,
+    /// To the start or end of the current block e.g. paragraph, list item.
+    Paragraph,
+    /// To the start or end of the whole document.
+    Document,
+}
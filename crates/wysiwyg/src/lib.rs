@@ -18,35 +18,62 @@ mod composer_action;
 mod composer_model;
 mod composer_state;
 mod composer_update;
+mod content_limit;
+mod context_state;
 mod dom;
+mod find;
 mod format_type;
+mod granularity;
 mod link_action;
 mod list_type;
 mod location;
 mod menu_action;
 mod menu_state;
+mod message_content;
 mod pattern_key;
+mod reply_fallback;
+mod slash_command;
 mod suggestion_pattern;
 mod tests;
 mod text_update;
 
 pub use crate::action_state::ActionState;
 pub use crate::composer_action::ComposerAction;
+pub use crate::composer_model::delete_text::Direction;
 pub use crate::composer_model::ComposerModel;
 pub use crate::composer_state::ComposerState;
 pub use crate::composer_update::ComposerUpdate;
+pub use crate::content_limit::ContentLimit;
+pub use crate::content_limit::ContentMetrics;
+pub use crate::content_limit::LimitBehaviour;
+pub use crate::content_limit::LimitResult;
+pub use crate::content_limit::LimitUnit;
+pub use crate::context_state::BlockType;
+pub use crate::context_state::ContextState;
+pub use crate::dom::nodes::ContainerNodeKind;
 pub use crate::dom::nodes::DomNode;
+pub use crate::dom::nodes::MentionNodeKind;
 pub use crate::dom::parser::parse;
+pub use crate::dom::parser::parse_with_diagnostics;
+pub use crate::dom::parser::parse_with_reply_fallback;
+pub use crate::dom::parser::ParseWarning;
+pub use crate::dom::parser::ParseWarningKind;
 pub use crate::dom::DomCreationError;
 pub use crate::dom::DomHandle;
 pub use crate::dom::HtmlParseError;
+pub use crate::dom::InvariantViolation;
 pub use crate::dom::MarkdownParseError;
 pub use crate::dom::ToHtml;
 pub use crate::dom::ToRawText;
 pub use crate::dom::ToTree;
 pub use crate::dom::UnicodeString;
+pub use crate::dom::UnknownTagError;
 pub use crate::dom::{MarkdownError, ToMarkdown};
+pub use crate::find::FindMatch;
+pub use crate::find::FindOptions;
+pub use crate::format_type::parse_hex_color;
 pub use crate::format_type::InlineFormatType;
+pub use crate::granularity::Granularity;
 pub use crate::link_action::LinkAction;
 pub use crate::list_type::ListType;
 pub use crate::location::Location;
@@ -54,7 +81,19 @@ pub use crate::menu_action::MenuAction;
 pub use crate::menu_action::MenuActionSuggestion;
 pub use crate::menu_state::MenuState;
 pub use crate::menu_state::MenuStateUpdate;
+pub use crate::message_content::Mentions;
+pub use crate::message_content::MessageContent;
+pub use crate::message_content::MessageContentOptions;
+pub use crate::message_content::MessageType;
 pub use crate::pattern_key::PatternKey;
+pub use crate::pattern_key::SuggestionTrigger;
+pub use crate::reply_fallback::reply_fallback_body;
+pub use crate::reply_fallback::reply_fallback_html;
+pub use crate::reply_fallback::strip_reply_fallback;
+pub use crate::reply_fallback::strip_reply_fallback_body;
+pub use crate::reply_fallback::ReplyParent;
+pub use crate::slash_command::ParsedCommand;
+pub use crate::slash_command::SlashCommand;
 pub use crate::suggestion_pattern::SuggestionPattern;
 pub use crate::text_update::ReplaceAll;
 pub use crate::text_update::Selection;
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::UnicodeString;
+use crate::UnknownTagError;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ListType {
@@ -29,14 +29,16 @@ impl ListType {
     }
 }
 
-impl<S: UnicodeString> From<S> for ListType {
-    fn from(value: S) -> Self {
-        match value.to_string().as_str() {
-            "ol" => ListType::Ordered,
-            "ul" => ListType::Unordered,
-            _ => {
-                panic!("Unknown list type {}", value.to_string().as_str());
-            }
+impl TryFrom<&str> for ListType {
+    type Error = UnknownTagError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "ol" => Ok(ListType::Ordered),
+            "ul" => Ok(ListType::Unordered),
+            tag => Err(UnknownTagError {
+                tag: tag.to_owned(),
+            }),
         }
     }
 }
@@ -13,16 +13,25 @@
 // limitations under the License.
 
 use crate::action_state::ActionState;
-use crate::ComposerAction;
+use crate::{ComposerAction, ContextState, UnicodeString};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum MenuState {
+pub enum MenuState<S>
+where
+    S: UnicodeString,
+{
     Keep,
-    Update(MenuStateUpdate),
+    Update(MenuStateUpdate<S>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MenuStateUpdate {
+pub struct MenuStateUpdate<S>
+where
+    S: UnicodeString,
+{
     pub action_states: HashMap<ComposerAction, ActionState>,
+    /// Block type, list depth, link and mention under the selection. See
+    /// [crate::ComposerModel::get_context_state].
+    pub context_state: Option<ContextState<S>>,
 }
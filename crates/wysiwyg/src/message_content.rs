@@ -0,0 +1,167 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::UnicodeString;
+
+/// The `msgtype` of an `m.room.message` event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageType {
+    #[default]
+    Text,
+    Emote,
+    Notice,
+}
+
+impl MessageType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageType::Text => "m.text",
+            MessageType::Emote => "m.emote",
+            MessageType::Notice => "m.notice",
+        }
+    }
+}
+
+/// How [crate::ComposerModel::to_message_content] builds the event content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageContentOptions {
+    pub msgtype: MessageType,
+    /// Use the content as markdown for the `body`, instead of plain text.
+    pub markdown_body: bool,
+}
+
+/// The content of an `m.room.message` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageContent<S>
+where
+    S: UnicodeString,
+{
+    pub msgtype: MessageType,
+    pub body: S,
+    /// `org.matrix.custom.html` if there is a formatted_body.
+    pub format: Option<S>,
+    /// Only present if the content has any formatting, links or mentions.
+    pub formatted_body: Option<S>,
+    pub mentions: Mentions<S>,
+    /// The event ID of the event this is a reply to, if any.
+    pub in_reply_to: Option<S>,
+}
+
+/// The `m.mentions` of an event: who is intentionally mentioned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mentions<S>
+where
+    S: UnicodeString,
+{
+    pub user_ids: Vec<S>,
+    /// True if the whole room is mentioned, with `@room`.
+    pub room: bool,
+}
+
+impl<S> MessageContent<S>
+where
+    S: UnicodeString,
+{
+    /// Serialise as the JSON content of an event, ready to be sent.
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![
+            ("msgtype", json_string(self.msgtype.as_str())),
+            ("body", json_string(&self.body.to_string())),
+        ];
+        if let Some(format) = &self.format {
+            fields.push(("format", json_string(&format.to_string())));
+        }
+        if let Some(formatted_body) = &self.formatted_body {
+            fields.push((
+                "formatted_body",
+                json_string(&formatted_body.to_string()),
+            ));
+        }
+        fields.push(("m.mentions", self.mentions.to_json()));
+        if let Some(event_id) = &self.in_reply_to {
+            let in_reply_to = json_object(&[(
+                "event_id",
+                json_string(&event_id.to_string()),
+            )]);
+            fields.push((
+                "m.relates_to",
+                json_object(&[("m.in_reply_to", in_reply_to)]),
+            ));
+        }
+        json_object(&fields)
+    }
+}
+
+impl<S> Mentions<S>
+where
+    S: UnicodeString,
+{
+    fn to_json(&self) -> String {
+        let mut fields = Vec::new();
+        if !self.user_ids.is_empty() {
+            let user_ids: Vec<String> = self
+                .user_ids
+                .iter()
+                .map(|id| json_string(&id.to_string()))
+                .collect();
+            fields.push(("user_ids", format!("[{}]", user_ids.join(","))));
+        }
+        if self.room {
+            fields.push(("room", String::from("true")));
+        }
+        json_object(&fields)
+    }
+}
+
+fn json_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if c.is_control() => {
+                ret.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+    }
+
+    #[test]
+    fn empty_mentions_are_an_empty_object() {
+        assert_eq!(Mentions::<String>::default().to_json(), "{}");
+    }
+}
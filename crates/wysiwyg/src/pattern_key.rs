@@ -17,6 +17,9 @@ pub enum PatternKey {
     At,
     Hash,
     Slash,
+    Colon,
+    /// One of the model's [SuggestionTrigger]s.
+    Custom(char),
 }
 
 impl PatternKey {
@@ -25,7 +28,22 @@ impl PatternKey {
             '\u{0040}' => Some(Self::At),
             '\u{0023}' => Some(Self::Hash),
             '\u{002F}' => Some(Self::Slash),
+            '\u{003A}' => Some(Self::Colon),
             _ => None,
         }
     }
 }
+
+/// A character, other than `@`, `#`, `/` and `:`, that starts a suggestion,
+/// e.g. `!` for bot commands.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuggestionTrigger {
+    pub key: char,
+    /// Only suggest when the trigger is the first character of the
+    /// document, like `/` commands.
+    pub only_at_start: bool,
+    /// Keep suggesting after whitespace is typed, e.g. for `!remind me`.
+    pub allow_whitespace: bool,
+    /// How many characters must follow the trigger before suggesting.
+    pub min_length: usize,
+}
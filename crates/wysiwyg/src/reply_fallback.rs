@@ -0,0 +1,233 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reply fallbacks, which quote the event being replied to at the start of
+//! a reply so that clients without reply support can show it. The HTML
+//! fallback is wrapped in `<mx-reply>`, which must be removed before the
+//! reply can be edited.
+
+use crate::{MessageType, UnicodeString};
+
+const OPEN_TAG: &str = "<mx-reply";
+const CLOSE_TAG: &str = "</mx-reply>";
+
+/// The event being replied to, as needed to build a reply fallback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplyParent<S>
+where
+    S: UnicodeString,
+{
+    pub event_id: S,
+    pub room_id: S,
+    pub sender: S,
+    pub msgtype: MessageType,
+    pub body: S,
+    pub formatted_body: Option<S>,
+}
+
+/// Remove the `<mx-reply>` block from the start of [html], if there is
+/// one. `<mx-reply>` tags nested inside it are balanced, and any later in
+/// the HTML are left alone. Returns the remaining HTML and the block that
+/// was removed, if any.
+pub fn strip_reply_fallback(html: &str) -> (String, Option<String>) {
+    // Lowercasing ASCII keeps byte offsets the same
+    let lowercase = html.to_ascii_lowercase();
+    let start = lowercase.len() - lowercase.trim_start().len();
+    if !is_open_tag_at(&lowercase, start) {
+        return (html.to_owned(), None);
+    }
+
+    let mut depth = 0;
+    let mut pos = start;
+    while let Some(i) = lowercase[pos..].find("<") {
+        let tag = pos + i;
+        if is_open_tag_at(&lowercase, tag) {
+            depth += 1;
+            pos = tag + OPEN_TAG.len();
+        } else if lowercase[tag..].starts_with(CLOSE_TAG) {
+            depth -= 1;
+            pos = tag + CLOSE_TAG.len();
+            if depth == 0 {
+                let mut stripped = html[..start].to_owned();
+                stripped.push_str(&html[pos..]);
+                return (stripped, Some(html[start..pos].to_owned()));
+            }
+        } else {
+            pos = tag + 1;
+        }
+    }
+    // Unclosed blocks are left as they are
+    (html.to_owned(), None)
+}
+
+/// True if an `<mx-reply>` opening tag starts at [pos] of [lowercase],
+/// rather than e.g. `<mx-replyx>`.
+fn is_open_tag_at(lowercase: &str, pos: usize) -> bool {
+    lowercase[pos..].starts_with(OPEN_TAG)
+        && lowercase[pos + OPEN_TAG.len()..]
+            .starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace())
+}
+
+/// Remove the quoted lines a reply fallback adds to the start of a plain
+/// text body.
+pub fn strip_reply_fallback_body(body: &str) -> &str {
+    if !body.starts_with("> ") {
+        return body;
+    }
+    let mut rest = body;
+    while rest.starts_with('>') {
+        rest = rest.split_once('\n').map_or("", |(_, after)| after);
+    }
+    rest.strip_prefix('\n').unwrap_or(rest)
+}
+
+/// The `<mx-reply>` block to put at the start of the formatted body of a
+/// reply to [parent].
+pub fn reply_fallback_html<S>(parent: &ReplyParent<S>) -> S
+where
+    S: UnicodeString,
+{
+    let parent_html = match &parent.formatted_body {
+        Some(html) => strip_reply_fallback(&html.to_string()).0,
+        None => {
+            let body = parent.body.to_string();
+            html_escape::encode_text(strip_reply_fallback_body(&body))
+                .replace('\n', "<br />")
+        }
+    };
+    let event_link =
+        format!("https://matrix.to/#/{}/{}", parent.room_id, parent.event_id);
+    let sender_link = format!("https://matrix.to/#/{}", parent.sender);
+    let emote = if parent.msgtype == MessageType::Emote {
+        "* "
+    } else {
+        ""
+    };
+    S::from(
+        format!(
+            "<mx-reply><blockquote>\
+                <a href=\"{}\">In reply to</a> \
+                {}<a href=\"{}\">{}</a>\
+                <br />{}\
+            </blockquote></mx-reply>",
+            html_escape::encode_double_quoted_attribute(&event_link),
+            emote,
+            html_escape::encode_double_quoted_attribute(&sender_link),
+            html_escape::encode_text(&parent.sender.to_string()),
+            parent_html,
+        )
+        .as_str(),
+    )
+}
+
+/// The quoted lines to put at the start of the plain text body of a reply
+/// to [parent], including the blank line that separates them from the
+/// reply.
+pub fn reply_fallback_body<S>(parent: &ReplyParent<S>) -> S
+where
+    S: UnicodeString,
+{
+    let body = parent.body.to_string();
+    let emote = if parent.msgtype == MessageType::Emote {
+        "* "
+    } else {
+        ""
+    };
+    let mut fallback = String::new();
+    for (i, line) in strip_reply_fallback_body(&body).lines().enumerate() {
+        if i == 0 {
+            fallback.push_str(&format!(
+                "> {}<{}> {}\n",
+                emote, parent.sender, line
+            ));
+        } else {
+            fallback.push_str(&format!("> {}\n", line));
+        }
+    }
+    if fallback.is_empty() {
+        fallback.push_str(&format!("> {}<{}>\n", emote, parent.sender));
+    }
+    fallback.push('\n');
+    S::from(fallback.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn strip_reply_fallback_removes_the_block() {
+        let (html, fallback) = strip_reply_fallback(
+            "<mx-reply><blockquote>quote</blockquote></mx-reply>reply",
+        );
+        assert_eq!(html, "reply");
+        assert_eq!(
+            fallback.unwrap(),
+            "<mx-reply><blockquote>quote</blockquote></mx-reply>"
+        );
+    }
+
+    #[test]
+    fn strip_reply_fallback_ignores_case() {
+        let (html, fallback) =
+            strip_reply_fallback("<MX-REPLY>quote</MX-REPLY>reply");
+        assert_eq!(html, "reply");
+        assert_eq!(fallback.unwrap(), "<MX-REPLY>quote</MX-REPLY>");
+    }
+
+    #[test]
+    fn strip_reply_fallback_leaves_unclosed_block() {
+        let (html, fallback) = strip_reply_fallback("<mx-reply>quote");
+        assert_eq!(html, "<mx-reply>quote");
+        assert_eq!(fallback, None);
+    }
+
+    #[test]
+    fn strip_reply_fallback_balances_nested_blocks() {
+        let (html, fallback) = strip_reply_fallback(
+            "<mx-reply>a<mx-reply>b</mx-reply>c</mx-reply>reply",
+        );
+        assert_eq!(html, "reply");
+        assert_eq!(
+            fallback.unwrap(),
+            "<mx-reply>a<mx-reply>b</mx-reply>c</mx-reply>"
+        );
+    }
+
+    #[test]
+    fn strip_reply_fallback_keeps_blocks_after_the_start() {
+        let (html, fallback) =
+            strip_reply_fallback("hello <mx-reply>x</mx-reply> world");
+        assert_eq!(html, "hello <mx-reply>x</mx-reply> world");
+        assert_eq!(fallback, None);
+    }
+
+    #[test]
+    fn strip_reply_fallback_only_removes_the_first_block() {
+        let (html, fallback) = strip_reply_fallback(
+            "<mx-reply>a</mx-reply>b<mx-reply>c</mx-reply>",
+        );
+        assert_eq!(html, "b<mx-reply>c</mx-reply>");
+        assert_eq!(fallback.unwrap(), "<mx-reply>a</mx-reply>");
+    }
+
+    #[test]
+    fn strip_reply_fallback_body_removes_quoted_lines() {
+        assert_eq!(
+            strip_reply_fallback_body("> <@a:b.c> hi\n> there\n\nreply"),
+            "reply"
+        );
+        assert_eq!(strip_reply_fallback_body("not > a reply"), "not > a reply");
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// A command that can be typed at the start of the composer, e.g.
+/// `/invite @alice:example.org`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashCommand {
+    /// The name of the command, without the leading `/`.
+    pub name: String,
+    /// The names of the arguments, each a single word, in order.
+    pub args: Vec<String>,
+    /// Whether the content after the arguments is kept as rich text, e.g.
+    /// for `/me` or `/spoiler`.
+    pub takes_remainder: bool,
+    pub description: String,
+}
+
+/// The content of the composer, read as a registered [SlashCommand].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub name: String,
+    /// The values of the arguments, in order. Arguments that haven't been
+    /// typed yet are left out.
+    pub args: Vec<String>,
+    /// The content after the arguments as message HTML, if the command
+    /// takes it.
+    pub remainder_html: Option<String>,
+}
@@ -15,19 +15,36 @@
 #![cfg(test)]
 
 pub mod test_characters;
+pub mod test_clear_formatting;
+pub mod test_colors;
+pub mod test_composition;
+pub mod test_content_limit;
+pub mod test_context_state;
+pub mod test_custom_emoji;
 pub mod test_deleting;
+pub mod test_find_replace;
 pub mod test_formatting;
 pub mod test_get_link_action;
+pub mod test_horizontal_rule;
 pub mod test_links;
 pub mod test_lists;
 pub mod test_lists_with_blocks;
+pub mod test_markdown_mode;
 pub mod test_menu_action;
 pub mod test_menu_state;
+pub mod test_message_content;
+pub mod test_navigation;
+pub mod test_normalize;
+pub mod test_opaque_nodes;
 pub mod test_paragraphs;
+pub mod test_parse_diagnostics;
 pub mod test_remove_links;
+pub mod test_reply_fallback;
 pub mod test_selection;
 pub mod test_set_content;
+pub mod test_slash_commands;
 pub mod test_suggestions;
+pub mod test_tables;
 pub mod test_to_markdown;
 pub mod test_to_plain_text;
 pub mod test_to_raw_text;
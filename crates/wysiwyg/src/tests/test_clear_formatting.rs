@@ -0,0 +1,104 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::{ComposerAction, Location};
+
+#[test]
+fn clearing_formatting_removes_all_formats_in_selection() {
+    let mut model = cm("{<strong><em>abc</em></strong><del>def</del>}|");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "{abcdef}|");
+}
+
+#[test]
+fn clearing_formatting_splits_partially_selected_nodes() {
+    let mut model = cm("<strong>a{bc</strong><em>de}|f</em>");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "<strong>a</strong>{bcde}|<em>f</em>");
+}
+
+#[test]
+fn clearing_formatting_inside_a_single_node() {
+    let mut model = cm("<u>ab{cd}|ef</u>");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "<u>ab</u>{cd}|<u>ef</u>");
+}
+
+#[test]
+fn clearing_formatting_removes_inline_code() {
+    let mut model = cm("a<code>{bc}|</code>d");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "a{bc}|d");
+}
+
+#[test]
+fn clearing_formatting_keeps_links_and_blocks() {
+    let mut model = cm(
+        "<ul><li><a href=\"https://matrix.org\"><b>{ab</b></a></li><li><i>cd}|</i></li></ul>",
+    );
+    model.clear_formatting();
+    assert_eq!(
+        tx(&model),
+        "<ul><li><a href=\"https://matrix.org\">{ab</a></li><li>cd}|</li></ul>"
+    );
+}
+
+#[test]
+fn clearing_formatting_and_links_removes_links() {
+    let mut model = cm("<a href=\"https://matrix.org\"><b>{ab}|</b></a>");
+    model.clear_formatting_and_links();
+    assert_eq!(tx(&model), "{ab}|");
+}
+
+#[test]
+fn clearing_formatting_without_formatting_does_nothing() {
+    let mut model = cm("a{bc}|d");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "a{bc}|d");
+    assert!(model.action_is_disabled(ComposerAction::Undo));
+}
+
+#[test]
+fn clearing_formatting_is_a_single_undo_step() {
+    let mut model = cm("{<b>ab</b><i>cd</i><u>ef</u>}|");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "{abcdef}|");
+    model.undo();
+    assert_eq!(tx(&model), "<b>{ab</b><i>cd</i><u>ef}|</u>");
+}
+
+#[test]
+fn clearing_formatting_at_cursor_disables_formats_for_next_input() {
+    let mut model = cm("<strong><em>ab|</em></strong>");
+    model.clear_formatting();
+    assert!(model.action_is_enabled(ComposerAction::Bold));
+    assert!(model.action_is_enabled(ComposerAction::Italic));
+    model.replace_text(Utf16String::from("c"));
+    assert_eq!(tx(&model), "<strong><em>ab</em></strong>c|");
+}
+
+#[test]
+fn clear_formatting_action_is_disabled_without_formatting() {
+    let mut model = cm("ab<b>cd</b>|");
+    assert!(model.action_is_enabled(ComposerAction::ClearFormatting));
+    model.select(Location::from(0), Location::from(1));
+    assert!(model.action_is_disabled(ComposerAction::ClearFormatting));
+    model.select(Location::from(1), Location::from(3));
+    assert!(model.action_is_enabled(ComposerAction::ClearFormatting));
+    model.select(Location::from(1), Location::from(1));
+    assert!(model.action_is_disabled(ComposerAction::ClearFormatting));
+}
@@ -0,0 +1,231 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{ActionState, ComposerAction, ComposerModel};
+
+fn red() -> Option<Utf16String> {
+    Some(utf16("#ff0000"))
+}
+
+fn blue() -> Option<Utf16String> {
+    Some(utf16("#0000ff"))
+}
+
+fn action_state(
+    model: &ComposerModel<Utf16String>,
+    action: ComposerAction,
+) -> ActionState {
+    model.action_states().get(&action).unwrap().clone()
+}
+
+#[test]
+fn setting_text_color_wraps_the_selection() {
+    let mut model = cm("a{bc}|d");
+    model.set_text_color(red());
+    assert_eq!(tx(&model), "a<span data-mx-color=\"#ff0000\">{bc}|</span>d");
+}
+
+#[test]
+fn setting_background_color_wraps_the_selection() {
+    let mut model = cm("{abc}|");
+    model.set_background_color(blue());
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-bg-color=\"#0000ff\">{abc}|</span>"
+    );
+}
+
+#[test]
+fn short_hex_colors_are_expanded() {
+    let mut model = cm("{abc}|");
+    model.set_text_color(Some(utf16("#F00")));
+    assert_eq!(tx(&model), "<span data-mx-color=\"#ff0000\">{abc}|</span>");
+}
+
+#[test]
+fn invalid_colors_are_ignored() {
+    let mut model = cm("{abc}|");
+    model.set_text_color(Some(utf16("red")));
+    model.set_text_color(Some(utf16("#ff00")));
+    assert_eq!(tx(&model), "{abc}|");
+    assert!(model.previous_states.is_empty());
+}
+
+#[test]
+fn setting_a_color_replaces_the_previous_one() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">a{bc}|d</span>");
+    model.set_text_color(blue());
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-color=\"#ff0000\">a</span>\
+        <span data-mx-color=\"#0000ff\">{bc}|</span>\
+        <span data-mx-color=\"#ff0000\">d</span>"
+    );
+}
+
+#[test]
+fn removing_a_color_splits_the_coloured_node() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">a{bc}|d</span>");
+    model.set_text_color(None);
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-color=\"#ff0000\">a</span>{bc}|\
+        <span data-mx-color=\"#ff0000\">d</span>"
+    );
+}
+
+#[test]
+fn removing_a_color_across_several_colors() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">a{b</span>\
+        <span data-mx-color=\"#0000ff\">c}|d</span>");
+    model.set_text_color(None);
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-color=\"#ff0000\">a</span>{bc}|\
+        <span data-mx-color=\"#0000ff\">d</span>"
+    );
+}
+
+#[test]
+fn same_colors_are_merged() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">ab</span>{cd}|");
+    model.set_text_color(red());
+    assert_eq!(tx(&model), "<span data-mx-color=\"#ff0000\">ab{cd}|</span>");
+}
+
+#[test]
+fn text_and_background_colors_are_independent() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">{abc}|</span>");
+    model.set_background_color(blue());
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-color=\"#ff0000\">\
+        <span data-mx-bg-color=\"#0000ff\">{abc}|</span></span>"
+    );
+    model.set_background_color(None);
+    assert_eq!(tx(&model), "<span data-mx-color=\"#ff0000\">{abc}|</span>");
+}
+
+#[test]
+fn color_set_at_cursor_applies_to_typed_text() {
+    let mut model = cm("a|");
+    model.set_text_color(red());
+    model.replace_text(utf16("bc"));
+    assert_eq!(tx(&model), "a<span data-mx-color=\"#ff0000\">bc|</span>");
+}
+
+#[test]
+fn color_removed_at_cursor_does_not_apply_to_typed_text() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">a|</span>");
+    model.set_text_color(None);
+    model.replace_text(utf16("b"));
+    assert_eq!(tx(&model), "<span data-mx-color=\"#ff0000\">a</span>b|");
+}
+
+#[test]
+fn color_changed_at_cursor_applies_to_typed_text() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">a|</span>");
+    model.set_text_color(blue());
+    model.replace_text(utf16("b"));
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-color=\"#ff0000\">a</span>\
+        <span data-mx-color=\"#0000ff\">b|</span>"
+    );
+}
+
+#[test]
+fn color_actions_are_reversed_inside_colored_text() {
+    let model = cm("<span data-mx-color=\"#ff0000\">a|</span>");
+    assert_eq!(
+        action_state(&model, ComposerAction::TextColor),
+        ActionState::Reversed
+    );
+    assert_eq!(
+        action_state(&model, ComposerAction::BackgroundColor),
+        ActionState::Enabled
+    );
+}
+
+#[test]
+fn context_state_reports_the_colors_under_the_cursor() {
+    let model = cm("<span data-mx-color=\"#ff0000\">\
+        <span data-mx-bg-color=\"#0000ff\">a|</span></span>");
+    let context = model.get_context_state();
+    assert_eq!(context.text_color, red());
+    assert_eq!(context.background_color, blue());
+}
+
+#[test]
+fn context_state_reports_pending_colors() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">a|</span>");
+    model.set_text_color(blue());
+    assert_eq!(model.get_context_state().text_color, blue());
+    model.set_text_color(None);
+    assert_eq!(model.get_context_state().text_color, None);
+}
+
+#[test]
+fn clear_formatting_removes_colors() {
+    let mut model = cm("<span data-mx-color=\"#ff0000\">{ab</span>\
+        <span data-mx-bg-color=\"#0000ff\">c}|</span>");
+    model.clear_formatting();
+    assert_eq!(tx(&model), "{abc}|");
+}
+
+#[test]
+fn span_with_both_colors_is_parsed_into_nested_colors() {
+    let model = cm(
+        "<span data-mx-color=\"#FF0000\" data-mx-bg-color=\"#00f\">a|</span>",
+    );
+    assert_eq!(
+        tx(&model),
+        "<span data-mx-color=\"#ff0000\">\
+        <span data-mx-bg-color=\"#0000ff\">a|</span></span>"
+    );
+}
+
+#[test]
+fn legacy_font_color_is_parsed_as_text_color() {
+    let model = cm("<font color=\"#ff0000\">a|</font>");
+    assert_eq!(tx(&model), "<span data-mx-color=\"#ff0000\">a|</span>");
+}
+
+#[test]
+fn spans_with_other_attributes_or_invalid_colors_are_kept_as_is() {
+    let model = cm("<font color=\"red\">a</font>|");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<font color=\"red\">a</font>"
+    );
+}
+
+#[test]
+fn colors_are_kept_in_markdown() {
+    let model = cm("<span data-mx-color=\"#ff0000\">a <b>b</b></span>|");
+    assert_eq!(
+        model.get_content_as_markdown(),
+        "<span data-mx-color=\"#ff0000\">a __b__</span>"
+    );
+}
+
+#[test]
+fn colors_are_dropped_in_plain_text() {
+    let model = cm("<span data-mx-color=\"#ff0000\">abc</span>|");
+    assert_eq!(model.get_content_as_plain_text(), "abc");
+}
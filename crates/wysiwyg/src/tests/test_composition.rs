@@ -0,0 +1,128 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{Location, MenuAction, TextUpdate};
+
+#[test]
+fn composing_text_replaces_previous_composing_text() {
+    let mut model = cm("ab|");
+    model.set_composing_text(utf16("k"));
+    model.set_composing_text(utf16("ka"));
+    model.set_composing_text(utf16("か"));
+    assert_eq!(tx(&model), "abか|");
+    assert_eq!(
+        model.state.composition,
+        Some((Location::from(2), Location::from(3)))
+    );
+}
+
+#[test]
+fn composing_text_replaces_the_selection() {
+    let mut model = cm("a{bc}|d");
+    model.set_composing_text(utf16("x"));
+    assert_eq!(tx(&model), "ax|d");
+}
+
+#[test]
+fn composing_text_is_marked_in_the_update_html() {
+    let mut model = cm("ab|cd");
+    let update = model.set_composing_text(utf16("xy"));
+    let TextUpdate::ReplaceAll(replace_all) = update.text_update else {
+        panic!("Expected a replace all update");
+    };
+    assert_eq!(
+        replace_all.replacement_html,
+        utf16("ab<span data-composing=\"true\">xy</span>cd")
+    );
+    // The content itself is not changed by the marker
+    assert_eq!(model.get_content_as_html(), utf16("abxycd"));
+}
+
+#[test]
+fn composing_text_does_not_trigger_suggestions() {
+    let mut model = cm("|");
+    let update = model.set_composing_text(utf16("@ali"));
+    assert_eq!(update.menu_action, MenuAction::Keep);
+    let update = model.commit_composition();
+    assert!(matches!(update.menu_action, MenuAction::Suggestion(_)));
+}
+
+#[test]
+fn composing_text_does_not_create_undo_steps_until_committed() {
+    let mut model = cm("ab|");
+    model.set_composing_text(utf16("c"));
+    model.set_composing_text(utf16("cd"));
+    assert!(model.previous_states.is_empty());
+    model.commit_composition();
+    assert_eq!(tx(&model), "abcd|");
+    assert_eq!(model.previous_states.len(), 1);
+    assert_eq!(model.state.composition, None);
+    model.undo();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn cancelling_a_composition_restores_the_previous_state() {
+    let mut model = cm("a{bc}|d");
+    model.set_composing_text(utf16("x"));
+    model.set_composing_text(utf16("xyz"));
+    model.cancel_composition();
+    assert_eq!(tx(&model), "a{bc}|d");
+    assert!(model.previous_states.is_empty());
+    assert_eq!(model.state.composition, None);
+}
+
+#[test]
+fn commit_or_cancel_without_composition_does_nothing() {
+    let mut model = cm("ab|");
+    model.commit_composition();
+    model.cancel_composition();
+    assert_eq!(tx(&model), "ab|");
+    assert!(model.previous_states.is_empty());
+}
+
+#[test]
+fn editing_commits_the_composition() {
+    let mut model = cm("ab|");
+    model.set_composing_text(utf16("cd"));
+    model.replace_text(utf16("e"));
+    assert_eq!(tx(&model), "abcde|");
+    assert_eq!(model.state.composition, None);
+    model.undo();
+    assert_eq!(tx(&model), "abcd|");
+    model.undo();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn moving_the_selection_commits_the_composition() {
+    let mut model = cm("ab|");
+    model.set_composing_text(utf16("cd"));
+    model.select(Location::from(0), Location::from(0));
+    assert_eq!(model.state.composition, None);
+    model.cancel_composition();
+    assert_eq!(tx(&model), "|abcd");
+}
+
+#[test]
+fn composing_text_keeps_pending_formats() {
+    let mut model = cm("ab|");
+    model.bold();
+    model.set_composing_text(utf16("c"));
+    model.set_composing_text(utf16("cd"));
+    model.commit_composition();
+    assert_eq!(tx(&model), "ab<strong>cd|</strong>");
+}
@@ -0,0 +1,308 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{
+    ContentLimit, ContentMetrics, LimitBehaviour, LimitResult, LimitUnit,
+};
+
+fn limit(
+    unit: LimitUnit,
+    max: usize,
+    behaviour: LimitBehaviour,
+) -> ContentLimit {
+    ContentLimit {
+        unit,
+        max,
+        behaviour,
+    }
+}
+
+#[test]
+fn content_metrics_measure_plain_text_and_message_html() {
+    let model = cm("<strong>a😀</strong>é|");
+    assert_eq!(
+        model.content_metrics(),
+        ContentMetrics {
+            graphemes: 3,
+            code_units: 4,
+            message_html_bytes: "<strong>a😀</strong>é".len(),
+            plain_text_bytes: "a😀é".len(),
+        }
+    );
+}
+
+#[test]
+fn content_metrics_of_empty_model_are_zero() {
+    let model = cm("|");
+    assert_eq!(model.content_metrics(), ContentMetrics::default());
+}
+
+#[test]
+fn text_within_limit_is_applied() {
+    let mut model = cm("ab|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        4,
+        LimitBehaviour::Reject,
+    )));
+    let update = model.replace_text(utf16("cd"));
+    assert_eq!(update.limit_result, LimitResult::Applied);
+    assert_eq!(tx(&model), "abcd|");
+}
+
+#[test]
+fn text_over_limit_is_rejected() {
+    let mut model = cm("ab|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Reject,
+    )));
+    let update = model.replace_text(utf16("cd"));
+    assert_eq!(update.limit_result, LimitResult::Rejected);
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn rejected_text_does_not_create_an_undo_step() {
+    let mut model = cm("ab|");
+    model.replace_text(utf16("c"));
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Reject,
+    )));
+    model.replace_text(utf16("d"));
+    model.undo();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn text_over_limit_is_truncated() {
+    let mut model = cm("ab|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        4,
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.replace_text(utf16("cdef"));
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    assert_eq!(tx(&model), "abcd|");
+}
+
+#[test]
+fn truncation_does_not_split_graphemes() {
+    let mut model = cm("a|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::CodeUnits,
+        3,
+        LimitBehaviour::Truncate,
+    )));
+    model.replace_text(utf16("b😀"));
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn truncating_to_nothing_rejects() {
+    let mut model = cm("abc|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.replace_text(utf16("d"));
+    assert_eq!(update.limit_result, LimitResult::Rejected);
+    assert_eq!(tx(&model), "abc|");
+}
+
+#[test]
+fn replacing_a_selection_counts_the_removed_text() {
+    let mut model = cm("a{bc}|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Reject,
+    )));
+    let update = model.replace_text(utf16("de"));
+    assert_eq!(update.limit_result, LimitResult::Applied);
+    assert_eq!(tx(&model), "ade|");
+}
+
+#[test]
+fn shortening_content_already_over_limit_is_allowed() {
+    let mut model = cm("a{bcd}|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        2,
+        LimitBehaviour::Reject,
+    )));
+    let update = model.replace_text(utf16("x"));
+    assert_eq!(update.limit_result, LimitResult::Applied);
+    assert_eq!(tx(&model), "ax|");
+}
+
+#[test]
+fn message_html_limit_counts_formatting() {
+    let mut model = cm("<strong>ab|</strong>");
+    model.set_content_limit(Some(limit(
+        LimitUnit::MessageHtmlBytes,
+        "<strong>abc</strong>".len(),
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.replace_text(utf16("cd"));
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    assert_eq!(tx(&model), "<strong>abc|</strong>");
+}
+
+#[test]
+fn replace_text_in_respects_limit() {
+    let mut model = cm("ab|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::PlainTextBytes,
+        3,
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.replace_text_in(utf16("xy"), 0, 0);
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    assert_eq!(tx(&model), "x|ab");
+}
+
+#[test]
+fn removing_the_limit_allows_any_text() {
+    let mut model = cm("ab|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        2,
+        LimitBehaviour::Reject,
+    )));
+    model.set_content_limit(None);
+    model.replace_text(utf16("cd"));
+    assert_eq!(tx(&model), "abcd|");
+}
+
+#[test]
+fn rejected_text_keeps_the_redo_history() {
+    let mut model = cm("ab|");
+    model.replace_text(utf16("c"));
+    model.undo();
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        2,
+        LimitBehaviour::Reject,
+    )));
+    model.replace_text(utf16("d"));
+    model.redo();
+    assert_eq!(tx(&model), "abc|");
+}
+
+#[test]
+fn rejected_text_does_not_change_the_menu_state() {
+    let mut model = cm("{<strong>ab</strong>}|c");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Reject,
+    )));
+    let action_states = model.action_states.clone();
+    let update = model.replace_text(utf16("xyz"));
+    assert_eq!(update.limit_result, LimitResult::Rejected);
+    assert_eq!(model.action_states, action_states);
+    assert_eq!(tx(&model), "<strong>{ab}|</strong>c");
+}
+
+#[test]
+fn pasted_lines_over_limit_are_truncated() {
+    let mut model = cm("a|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        6,
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.replace_text(utf16("bc\ndef"));
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    assert_eq!(tx(&model), "<p>abc</p><p>d|</p>");
+}
+
+#[test]
+fn composing_text_over_limit_is_truncated() {
+    let mut model = cm("ab|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Truncate,
+    )));
+    model.set_composing_text(utf16("k"));
+    let update = model.set_composing_text(utf16("ka"));
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    model.commit_composition();
+    assert_eq!(tx(&model), "abk|");
+}
+
+#[test]
+fn composing_text_over_limit_is_rejected() {
+    let mut model = cm("abc|");
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        3,
+        LimitBehaviour::Reject,
+    )));
+    let update = model.set_composing_text(utf16("k"));
+    assert_eq!(update.limit_result, LimitResult::Rejected);
+    assert_eq!(model.state.composition, None);
+    assert_eq!(tx(&model), "abc|");
+}
+
+#[test]
+fn committing_a_composition_respects_a_limit_set_while_composing() {
+    let mut model = cm("ab|");
+    model.set_composing_text(utf16("xyz"));
+    model.set_content_limit(Some(limit(
+        LimitUnit::Graphemes,
+        4,
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.commit_composition();
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    assert_eq!(tx(&model), "abxy|");
+    model.undo();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn replacing_formatted_text_counts_the_removed_text() {
+    let mut model = cm("<strong>a{b</strong>c}|d");
+    model.set_content_limit(Some(limit(
+        LimitUnit::PlainTextBytes,
+        4,
+        LimitBehaviour::Truncate,
+    )));
+    let update = model.replace_text(utf16("xyz"));
+    assert_eq!(update.limit_result, LimitResult::Truncated);
+    assert_eq!(model.get_content_as_plain_text(), "axyd");
+}
+
+#[test]
+fn replacing_text_across_paragraphs_counts_the_joined_paragraphs() {
+    let mut model = cm("<p>a{b</p><p>c}|d</p>");
+    model.set_content_limit(Some(limit(
+        LimitUnit::CodeUnits,
+        7,
+        LimitBehaviour::Reject,
+    )));
+    let update = model.replace_text(utf16("wxyz"));
+    assert_eq!(update.limit_result, LimitResult::Applied);
+    assert_eq!(model.get_content_as_plain_text(), "awxyzd\n");
+}
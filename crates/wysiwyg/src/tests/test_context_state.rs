@@ -0,0 +1,134 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::cm;
+use crate::tests::testutils_conversion::utf16;
+use crate::{BlockType, ContextState, Location, MenuState};
+
+#[test]
+fn context_of_plain_text_is_a_paragraph() {
+    let model = cm("ab|c");
+    assert_eq!(model.get_context_state(), ContextState::default());
+}
+
+#[test]
+fn context_reports_the_block_type() {
+    let model = cm("<blockquote><p>ab|</p></blockquote>");
+    assert_eq!(model.get_context_state().block_type, BlockType::Quote);
+
+    let model = cm("<pre><code>ab|</code></pre>");
+    assert_eq!(model.get_context_state().block_type, BlockType::CodeBlock);
+
+    let model = cm("<ul><li>ab|</li></ul>");
+    assert_eq!(model.get_context_state().block_type, BlockType::ListItem);
+
+    let model = cm("<p>ab</p><p>c|d</p>");
+    assert_eq!(model.get_context_state().block_type, BlockType::Paragraph);
+}
+
+#[test]
+fn context_reports_headings_and_other_opaque_blocks() {
+    let mut model = cm("<p>a</p><h2>x</h2><p>b|</p>");
+    model.select(Location::from(3), Location::from(3));
+    assert_eq!(
+        model.get_context_state().block_type,
+        BlockType::Heading { level: 2 }
+    );
+
+    let mut model = cm("<p>a</p><div>x</div><p>b|</p>");
+    model.select(Location::from(3), Location::from(3));
+    assert_eq!(model.get_context_state().block_type, BlockType::Other);
+}
+
+#[test]
+fn context_reports_the_deepest_block_type() {
+    let model = cm("<ul><li><blockquote><p>ab|</p></blockquote></li></ul>");
+    assert_eq!(model.get_context_state().block_type, BlockType::Quote);
+    assert_eq!(model.get_context_state().list_depth, 1);
+}
+
+#[test]
+fn context_reports_the_list_depth() {
+    let model = cm("<ol><li><p>a</p><ul><li>b|</li></ul></li></ol>");
+    let context = model.get_context_state();
+    assert_eq!(context.block_type, BlockType::ListItem);
+    assert_eq!(context.list_depth, 2);
+
+    let model = cm("<ol><li><p>a|</p><ul><li>b</li></ul></li></ol>");
+    assert_eq!(model.get_context_state().list_depth, 1);
+}
+
+#[test]
+fn context_reports_the_link_under_the_cursor() {
+    let model = cm("<a href=\"https://matrix.org\">li|nk</a>");
+    assert_eq!(
+        model.get_context_state().link_url,
+        Some(utf16("https://matrix.org"))
+    );
+
+    let model = cm("<a href=\"https://matrix.org\">link|</a>after");
+    assert_eq!(
+        model.get_context_state().link_url,
+        Some(utf16("https://matrix.org"))
+    );
+}
+
+#[test]
+fn context_does_not_report_a_link_starting_at_the_cursor() {
+    let model = cm("before|<a href=\"https://matrix.org\">link</a>");
+    assert_eq!(model.get_context_state().link_url, None);
+}
+
+#[test]
+fn context_reports_a_selected_mention() {
+    let model =
+        cm("{<a href=\"https://matrix.to/#/@test:example.org\">test</a>}|");
+    assert!(model.get_context_state().in_mention);
+
+    let model =
+        cm("<a href=\"https://matrix.to/#/@test:example.org\">test</a>|");
+    assert!(!model.get_context_state().in_mention);
+}
+
+#[test]
+fn context_reports_a_cursor_inside_an_immutable_link() {
+    let model = cm(
+        "<a contenteditable=\"false\" href=\"https://matrix.org\">te|st</a>",
+    );
+    assert!(model.get_context_state().in_mention);
+}
+
+#[test]
+fn menu_state_update_contains_the_context_state() {
+    let mut model = cm("<blockquote><p>ab|</p></blockquote>");
+    let update = model.replace_text(Utf16String::from("c"));
+    let MenuState::Update(menu_state) = update.menu_state else {
+        panic!("Expected a menu state update");
+    };
+    assert_eq!(
+        menu_state.context_state.unwrap().block_type,
+        BlockType::Quote
+    );
+}
+
+#[test]
+fn menu_state_is_updated_when_only_the_context_changes() {
+    let mut model = cm("<ul><li><p>a</p><ul><li>b|</li></ul></li></ul>");
+    let update = model.select(1.into(), 1.into());
+    assert!(matches!(update.menu_state, MenuState::Update(_)));
+    let update = model.select(1.into(), 1.into());
+    assert_eq!(update.menu_state, MenuState::Keep);
+}
@@ -0,0 +1,202 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{ComposerModel, ToTree};
+
+const PARTY: &str =
+    "<img data-mx-emoticon=\"\" title=\":party:\" height=\"32\" \
+    src=\"mxc://example.org/party\" alt=\":party:\">";
+
+fn insert_party(model: &mut ComposerModel<Utf16String>) {
+    model.insert_custom_emoji(utf16("mxc://example.org/party"), utf16("party"));
+}
+
+#[test]
+fn custom_emoji_are_parsed_as_images() {
+    let model = cm(
+        "a<img data-mx-emoticon src=\"mxc://example.org/party\" alt=\":party:\">b|",
+    );
+    assert_eq!(
+        tx(&model),
+        "a<img data-mx-emoticon=\"\" src=\"mxc://example.org/party\" \
+        alt=\":party:\">b|"
+    );
+    assert_eq!(model.state.dom.text_len(), 3);
+}
+
+#[test]
+fn images_are_shown_in_the_tree() {
+    let model = cm("a<img src=\"mxc://example.org/cat\" alt=\"cat\">|");
+    assert_eq!(
+        model.state.dom.to_tree(),
+        r#"
+├>"a"
+└>img "cat", mxc://example.org/cat
+"#
+    );
+}
+
+#[test]
+fn images_not_from_matrix_media_are_kept_as_opaque_nodes() {
+    let model = cm("<img src=\"https://example.org/cat.png\" alt=\"cat\">|");
+    assert_eq!(model.state.dom.to_tree(), "\n└>opaque img \"cat\"\n");
+}
+
+#[test]
+fn images_are_written_as_their_alt_text_in_plain_text() {
+    let model = cm("hi <img src=\"mxc://example.org/cat\" alt=\"cat\">|");
+    assert_eq!(model.get_content_as_plain_text(), "hi cat");
+}
+
+#[test]
+fn images_are_written_as_their_alt_text_in_markdown() {
+    let model = cm("**hi** <img src=\"mxc://example.org/cat\" alt=\"cat\">|");
+    assert_eq!(model.get_content_as_markdown(), "**hi** cat");
+}
+
+#[test]
+fn inserting_a_custom_emoji_into_an_empty_model() {
+    let mut model = cm("|");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("{PARTY}|"));
+}
+
+#[test]
+fn inserting_a_custom_emoji_in_the_middle_of_text() {
+    let mut model = cm("ab|cd");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("ab{PARTY}|cd"));
+}
+
+#[test]
+fn inserting_a_custom_emoji_at_the_start_of_text() {
+    let mut model = cm("|ab");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("{PARTY}|ab"));
+}
+
+#[test]
+fn inserting_a_custom_emoji_in_an_empty_paragraph() {
+    let mut model = cm("<p>ab</p><p>|</p>");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("<p>ab</p><p>{PARTY}|</p>"));
+}
+
+#[test]
+fn inserting_a_custom_emoji_inside_formatting() {
+    let mut model = cm("<b>a|b</b>");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("<b>a{PARTY}|b</b>"));
+}
+
+#[test]
+fn inserting_a_custom_emoji_replaces_the_selection() {
+    let mut model = cm("a{bc}|d");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("a{PARTY}|d"));
+}
+
+#[test]
+fn inserting_a_custom_emoji_next_to_a_horizontal_rule_adds_a_paragraph() {
+    let mut model = cm("<p>ab|</p><hr />");
+    model.select(4.into(), 4.into());
+    insert_party(&mut model);
+    assert_eq!(tx(&model), format!("<p>ab</p><hr /><p>{PARTY}|</p>"));
+}
+
+#[test]
+fn shortcodes_are_wrapped_in_colons_only_once() {
+    let mut model = cm("|");
+    model.insert_custom_emoji(
+        utf16("mxc://example.org/party"),
+        utf16(":party:"),
+    );
+    assert_eq!(tx(&model), format!("{PARTY}|"));
+}
+
+#[test]
+fn custom_emoji_with_an_invalid_url_are_not_inserted() {
+    let mut model = cm("a|");
+    model.insert_custom_emoji(
+        utf16("https://example.org/party.png"),
+        utf16("party"),
+    );
+    assert_eq!(tx(&model), "a|");
+}
+
+#[test]
+fn custom_emoji_are_not_inserted_in_inline_code() {
+    let mut model = cm("<code>a|b</code>");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), "<code>a|b</code>");
+}
+
+#[test]
+fn custom_emoji_are_not_inserted_in_code_blocks() {
+    let mut model = cm("<pre><code>a|b</code></pre>");
+    insert_party(&mut model);
+    assert_eq!(tx(&model), "<pre><code>a|b</code></pre>");
+}
+
+#[test]
+fn inserting_a_custom_emoji_can_be_undone() {
+    let mut model = cm("ab|");
+    insert_party(&mut model);
+    model.undo();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn custom_emoji_are_removed_with_a_single_backspace() {
+    let mut model = cm("ab|");
+    insert_party(&mut model);
+    model.backspace();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn typing_after_a_custom_emoji() {
+    let mut model = cm("ab|");
+    insert_party(&mut model);
+    model.replace_text(utf16("c"));
+    assert_eq!(tx(&model), format!("ab{PARTY}c|"));
+}
+
+#[test]
+fn custom_emoji_are_written_as_their_shortcode_in_plain_text() {
+    let mut model = cm("hi |");
+    insert_party(&mut model);
+    assert_eq!(model.get_content_as_plain_text(), "hi :party:");
+}
+
+#[test]
+fn custom_emoji_are_kept_in_message_html() {
+    let mut model = cm("hi |");
+    insert_party(&mut model);
+    assert_eq!(model.get_content_as_message_html(), format!("hi {PARTY}"));
+}
+
+#[test]
+fn only_allowed_attributes_are_kept_on_images() {
+    let model = cm("<img src=\"mxc://example.org/cat\" alt=\"cat\" \
+        width=\"32\" onerror=\"alert(1)\" style=\"color:red\" class=\"x\">|");
+    assert_eq!(
+        tx(&model),
+        "<img width=\"32\" src=\"mxc://example.org/cat\" alt=\"cat\">|"
+    );
+}
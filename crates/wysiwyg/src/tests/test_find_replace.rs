@@ -0,0 +1,181 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{FindMatch, FindOptions};
+
+fn m(start: usize, end: usize) -> FindMatch {
+    FindMatch { start, end }
+}
+
+#[test]
+fn find_returns_all_matches() {
+    let mut model = cm("abc abc ab|");
+    let matches = model.find(utf16("abc"), FindOptions::default());
+    assert_eq!(matches, vec![m(0, 3), m(4, 7)]);
+}
+
+#[test]
+fn find_with_empty_query_returns_nothing() {
+    let mut model = cm("abc|");
+    assert!(model.find(utf16(""), FindOptions::default()).is_empty());
+}
+
+#[test]
+fn find_ignores_case_by_default() {
+    let mut model = cm("Abc aBC|");
+    let matches = model.find(utf16("abc"), FindOptions::default());
+    assert_eq!(matches, vec![m(0, 3), m(4, 7)]);
+}
+
+#[test]
+fn find_can_be_case_sensitive() {
+    let mut model = cm("Abc abc|");
+    let options = FindOptions {
+        case_sensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(model.find(utf16("abc"), options), vec![m(4, 7)]);
+}
+
+#[test]
+fn find_can_match_whole_words_only() {
+    let mut model = cm("cat concat cat, cats|");
+    let options = FindOptions {
+        whole_word: true,
+        ..Default::default()
+    };
+    assert_eq!(model.find(utf16("cat"), options), vec![m(0, 3), m(11, 14)]);
+}
+
+#[test]
+fn find_matches_across_text_nodes() {
+    let mut model = cm("a<b>bc</b><i>d</i>e|");
+    let matches = model.find(utf16("bcd"), FindOptions::default());
+    assert_eq!(matches, vec![m(1, 4)]);
+}
+
+#[test]
+fn find_does_not_match_across_paragraphs_or_line_breaks() {
+    let mut model = cm("<p>ab</p><p>cd|</p>");
+    assert!(model.find(utf16("bc"), FindOptions::default()).is_empty());
+    let mut model = cm("ab<br />cd|");
+    assert!(model.find(utf16("bc"), FindOptions::default()).is_empty());
+}
+
+#[test]
+fn find_skips_mentions() {
+    let mut model = cm(
+        "test <a href=\"https://matrix.to/#/@test:example.org\">test</a> test|",
+    );
+    let matches = model.find(utf16("test"), FindOptions::default());
+    assert_eq!(matches, vec![m(0, 4), m(7, 11)]);
+}
+
+#[test]
+fn replace_all_replaces_every_match() {
+    let mut model = cm("colour, Colour, colours|");
+    model.find(utf16("colour"), FindOptions::default());
+    model.replace_all(utf16("colour"), utf16("color"), FindOptions::default());
+    assert_eq!(tx(&model), "color, color, color|s");
+}
+
+#[test]
+fn replace_all_with_no_match_does_nothing() {
+    let mut model = cm("abc|");
+    model.replace_all(utf16("x"), utf16("y"), FindOptions::default());
+    assert_eq!(tx(&model), "abc|");
+    assert!(model.previous_states.is_empty());
+}
+
+#[test]
+fn replace_all_keeps_formatting_of_first_replaced_character() {
+    let mut model = cm("a<b>bc</b><i>d</i>e|");
+    model.replace_all(utf16("bcd"), utf16("xyz"), FindOptions::default());
+    assert_eq!(tx(&model), "a<b>xyz|</b>e");
+
+    let mut model = cm("<b>ab</b>c <b>ab</b>c|");
+    model.replace_all(utf16("bc"), utf16("BC"), FindOptions::default());
+    assert_eq!(tx(&model), "<b>aBC</b> <b>aBC|</b>");
+}
+
+#[test]
+fn replace_all_keeps_links() {
+    let mut model = cm("<a href=\"https://matrix.org\">mtrix</a> site|");
+    model.replace_all(utf16("mtrix"), utf16("matrix"), FindOptions::default());
+    assert_eq!(
+        tx(&model),
+        "<a href=\"https://matrix.org\">matrix|</a> site"
+    );
+}
+
+#[test]
+fn replace_all_is_a_single_undo_step() {
+    let mut model = cm("a a a|");
+    model.replace_all(utf16("a"), utf16("bb"), FindOptions::default());
+    assert_eq!(tx(&model), "bb bb bb|");
+    model.undo();
+    assert_eq!(tx(&model), "a a a|");
+}
+
+#[test]
+fn replace_all_can_delete_matches() {
+    let mut model = cm("a<b>b</b>c a<b>b</b>c|");
+    model.replace_all(utf16("b"), utf16(""), FindOptions::default());
+    assert_eq!(tx(&model), "ac a|c");
+}
+
+#[test]
+fn replace_all_uses_the_given_options() {
+    let mut model = cm("Cat cat|");
+    let options = FindOptions {
+        case_sensitive: true,
+        ..Default::default()
+    };
+    model.replace_all(utf16("cat"), utf16("dog"), options);
+    assert_eq!(tx(&model), "Cat dog|");
+}
+
+#[test]
+fn replace_all_ignores_the_options_of_the_last_search() {
+    let mut model = cm("Cat cat|");
+    let options = FindOptions {
+        case_sensitive: true,
+        ..Default::default()
+    };
+    model.find(utf16("cat"), options);
+    model.replace_all(utf16("cat"), utf16("dog"), FindOptions::default());
+    assert_eq!(tx(&model), "dog dog|");
+}
+
+#[test]
+fn replace_match_replaces_one_match() {
+    let mut model = cm("one two one two|");
+    model.find(utf16("two"), FindOptions::default());
+    model.replace_match(1, utf16("three"));
+    assert_eq!(tx(&model), "one two one three|");
+    model.replace_match(0, utf16("2"));
+    assert_eq!(tx(&model), "one 2| one three");
+}
+
+#[test]
+fn replace_match_without_search_or_out_of_range_does_nothing() {
+    let mut model = cm("abc|");
+    model.replace_match(0, utf16("x"));
+    assert_eq!(tx(&model), "abc|");
+    model.find(utf16("b"), FindOptions::default());
+    model.replace_match(1, utf16("x"));
+    assert_eq!(tx(&model), "abc|");
+}
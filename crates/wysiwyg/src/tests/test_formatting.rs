@@ -18,7 +18,7 @@ use widestring::Utf16String;
 
 use crate::InlineFormatType::Bold;
 use crate::Location;
-use crate::{ComposerModel, InlineFormatType};
+use crate::{ComposerAction, ComposerModel, InlineFormatType, ToTree};
 
 #[test]
 fn selecting_and_bolding_multiple_times() {
@@ -363,3 +363,63 @@ fn formatting_in_an_empty_paragraph_applies_formatting() {
     model.replace_text("B".into());
     assert_eq!(tx(&model), "<p>A</p><p><strong>B|</strong></p>");
 }
+
+#[test]
+fn superscript_and_subscript_wrap_the_selection() {
+    let mut model = cm("x{2}|");
+    model.superscript();
+    assert_eq!(tx(&model), "x<sup>{2}|</sup>");
+
+    let mut model = cm("H{2}|O");
+    model.subscript();
+    assert_eq!(tx(&model), "H<sub>{2}|</sub>O");
+}
+
+#[test]
+fn superscript_toggles_off() {
+    let mut model = cm("x<sup>{2}|</sup>");
+    model.superscript();
+    assert_eq!(tx(&model), "x{2}|");
+}
+
+#[test]
+fn superscript_replaces_subscript_in_the_selection() {
+    let mut model = cm("a<sub>{b}|c</sub>");
+    model.superscript();
+    assert_eq!(tx(&model), "a<sup>{b}|</sup><sub>c</sub>");
+}
+
+#[test]
+fn subscript_replaces_partially_selected_superscript() {
+    let mut model = cm("{a<sup>b}|c</sup>");
+    model.subscript();
+    assert_eq!(tx(&model), "<sub>{ab}|</sub><sup>c</sup>");
+}
+
+#[test]
+fn subscript_at_cursor_replaces_superscript_for_typed_text() {
+    let mut model = cm("x<sup>2|</sup>");
+    model.subscript();
+    model.replace_text(utf16("3"));
+    assert_eq!(tx(&model), "x<sup>2</sup><sub>3|</sub>");
+    assert!(model.action_is_reversed(ComposerAction::Subscript));
+    assert!(!model.action_is_reversed(ComposerAction::Superscript));
+}
+
+#[test]
+fn superscript_and_subscript_are_parsed() {
+    let model = cm("x<sup>2</sup>H<sub>2</sub>|");
+    assert_eq!(
+        model.state.dom.to_tree().to_string(),
+        "\n├>\"x\"\n├>sup\n│ └>\"2\"\n├>\"H\"\n└>sub\n  └>\"2\"\n"
+    );
+}
+
+#[test]
+fn superscript_and_subscript_are_kept_in_markdown() {
+    let model = cm("x<sup>2</sup>H<sub>2</sub>|");
+    assert_eq!(
+        model.get_content_as_markdown(),
+        utf16("x<sup>2</sup>H<sub>2</sub>")
+    );
+}
@@ -0,0 +1,336 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::dom::parser::parse;
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{ComposerModel, Location, ToHtml, ToTree};
+
+fn model_with_rule() -> ComposerModel<Utf16String> {
+    cm("<p>ab</p><hr /><p>cd|</p>")
+}
+
+fn select(model: &mut ComposerModel<Utf16String>, start: usize, end: usize) {
+    model.select(Location::from(start), Location::from(end));
+}
+
+fn rule_model_at(start: usize, end: usize) -> ComposerModel<Utf16String> {
+    let mut model = model_with_rule();
+    select(&mut model, start, end);
+    model
+}
+
+#[test]
+fn horizontal_rules_are_parsed_between_blocks() {
+    let model = model_with_rule();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>cd|</p>");
+    assert_eq!(model.state.dom.text_len(), 7);
+}
+
+#[test]
+fn horizontal_rules_are_parsed_inside_quotes() {
+    assert_eq!(
+        parse::<Utf16String>("<blockquote><p>a</p><hr></blockquote>")
+            .unwrap()
+            .to_html(),
+        "<blockquote><p>a</p><hr /></blockquote>"
+    );
+}
+
+#[test]
+fn horizontal_rules_in_formatting_become_line_breaks() {
+    assert_eq!(
+        parse::<Utf16String>("<b>a<hr>b</b>").unwrap().to_html(),
+        "<b>a<br />b</b>"
+    );
+}
+
+#[test]
+fn horizontal_rules_in_lists_become_line_breaks() {
+    assert_eq!(
+        parse::<Utf16String>("<ul><li>a<hr>b</li></ul>")
+            .unwrap()
+            .to_html(),
+        "<ul><li>a<br />b</li></ul>"
+    );
+}
+
+#[test]
+fn horizontal_rules_in_tables_become_line_breaks() {
+    assert_eq!(
+        parse::<Utf16String>("<table><tr><td>a<hr>b</td></tr></table>")
+            .unwrap()
+            .to_html(),
+        "<table><tbody><tr><td>a<br />b</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn horizontal_rules_in_code_blocks_become_new_lines() {
+    assert_eq!(
+        parse::<Utf16String>("<pre><code>a<hr>b</code></pre>")
+            .unwrap()
+            .to_html(),
+        "<pre><code>a\nb</code></pre>"
+    );
+}
+
+#[test]
+fn horizontal_rules_are_shown_in_the_tree() {
+    let model = model_with_rule();
+    assert_eq!(
+        model.state.dom.to_tree(),
+        r#"
+├>p
+│ └>"ab"
+├>hr
+└>p
+  └>"cd"
+"#
+    );
+}
+
+#[test]
+fn horizontal_rules_are_written_as_dashes_in_plain_text() {
+    let model = model_with_rule();
+    assert_eq!(model.get_content_as_plain_text(), "ab\n---\ncd\n");
+}
+
+#[test]
+fn horizontal_rules_are_written_as_asterisks_in_markdown() {
+    let model = model_with_rule();
+    assert_eq!(model.get_content_as_markdown(), "ab\n***\ncd");
+}
+
+#[test]
+fn horizontal_rules_are_read_from_markdown() {
+    let mut model = ComposerModel::<Utf16String>::new();
+    model
+        .set_content_from_markdown(&utf16("a\n\n---\n\nb"))
+        .unwrap();
+    assert_eq!(tx(&model), "<p>a</p><hr /><p>b|</p>");
+}
+
+#[test]
+fn horizontal_rules_survive_a_markdown_round_trip() {
+    let mut model = model_with_rule();
+    let markdown = model.get_content_as_markdown();
+    model.set_content_from_markdown(&markdown).unwrap();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>cd|</p>");
+}
+
+#[test]
+fn cursor_can_sit_on_either_side_of_a_horizontal_rule() {
+    assert_eq!(tx(&rule_model_at(3, 3)), "<p>ab</p>|<hr /><p>cd</p>");
+    assert_eq!(tx(&rule_model_at(4, 4)), "<p>ab</p><hr />|<p>cd</p>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_into_an_empty_model() {
+    let mut model = cm("|");
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<hr /><p>&nbsp;|</p>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_at_the_end_of_a_paragraph() {
+    let mut model = cm("ab|");
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>&nbsp;|</p>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_at_the_start_of_a_paragraph() {
+    let mut model = cm("|ab");
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<hr /><p>|ab</p>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_splits_a_paragraph() {
+    let mut model = cm("a|b");
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<p>a</p><hr /><p>|b</p>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_next_to_another_rule() {
+    let mut model = rule_model_at(4, 4);
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><hr /><p>&nbsp;|</p><p>cd</p>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_in_a_list_does_nothing() {
+    let mut model = cm("<ul><li>a|</li></ul>");
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<ul><li>a|</li></ul>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_in_a_quote_does_nothing() {
+    let mut model = cm("<blockquote><p>a|</p></blockquote>");
+    model.insert_horizontal_rule();
+    assert_eq!(tx(&model), "<blockquote><p>a|</p></blockquote>");
+}
+
+#[test]
+fn inserting_a_horizontal_rule_can_be_undone() {
+    let mut model = cm("a|b");
+    model.insert_horizontal_rule();
+    model.undo();
+    assert_eq!(tx(&model), "a|b");
+}
+
+#[test]
+fn typing_before_a_horizontal_rule_adds_a_paragraph() {
+    let mut model = rule_model_at(3, 3);
+    model.replace_text(utf16("X"));
+    assert_eq!(tx(&model), "<p>ab</p><p>X|</p><hr /><p>cd</p>");
+}
+
+#[test]
+fn typing_after_a_horizontal_rule_adds_a_paragraph() {
+    let mut model = rule_model_at(4, 4);
+    model.replace_text(utf16("X"));
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>X|</p><p>cd</p>");
+}
+
+#[test]
+fn enter_before_a_horizontal_rule_adds_an_empty_paragraph() {
+    let mut model = rule_model_at(3, 3);
+    model.enter();
+    assert_eq!(tx(&model), "<p>ab</p><p>&nbsp;</p>|<hr /><p>cd</p>");
+}
+
+#[test]
+fn enter_after_a_horizontal_rule_adds_an_empty_paragraph() {
+    let mut model = rule_model_at(4, 4);
+    model.enter();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>&nbsp;|</p><p>cd</p>");
+}
+
+#[test]
+fn backspace_before_a_horizontal_rule_moves_into_the_previous_block() {
+    let mut model = rule_model_at(3, 3);
+    model.backspace();
+    assert_eq!(tx(&model), "<p>ab|</p><hr /><p>cd</p>");
+}
+
+#[test]
+fn backspace_after_a_horizontal_rule_removes_it() {
+    let mut model = rule_model_at(4, 4);
+    model.backspace();
+    assert_eq!(tx(&model), "<p>ab</p><p>|cd</p>");
+}
+
+#[test]
+fn backspace_at_the_start_of_the_next_paragraph_removes_the_rule() {
+    let mut model = rule_model_at(5, 5);
+    model.backspace();
+    assert_eq!(tx(&model), "<p>ab</p><p>|cd</p>");
+}
+
+#[test]
+fn removing_a_horizontal_rule_can_be_undone() {
+    let mut model = rule_model_at(5, 5);
+    model.backspace();
+    model.undo();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>|cd</p>");
+}
+
+#[test]
+fn delete_at_the_end_of_the_previous_paragraph_removes_the_rule() {
+    let mut model = rule_model_at(2, 2);
+    model.delete();
+    assert_eq!(tx(&model), "<p>ab|</p><p>cd</p>");
+}
+
+#[test]
+fn delete_before_a_horizontal_rule_removes_it() {
+    let mut model = rule_model_at(3, 3);
+    model.delete();
+    assert_eq!(tx(&model), "<p>ab</p><p>|cd</p>");
+}
+
+#[test]
+fn delete_after_a_horizontal_rule_moves_into_the_next_block() {
+    let mut model = rule_model_at(4, 4);
+    model.delete();
+    assert_eq!(tx(&model), "<p>ab</p><hr /><p>|cd</p>");
+}
+
+#[test]
+fn backspace_word_removes_a_horizontal_rule_on_its_own() {
+    let mut model = rule_model_at(5, 5);
+    model.backspace_word();
+    assert_eq!(tx(&model), "<p>ab</p><p>|cd</p>");
+}
+
+#[test]
+fn delete_word_removes_a_horizontal_rule_on_its_own() {
+    let mut model = rule_model_at(2, 2);
+    model.delete_word();
+    assert_eq!(tx(&model), "<p>ab|</p><p>cd</p>");
+}
+
+#[test]
+fn replacing_a_selection_across_a_horizontal_rule_removes_it() {
+    let mut model = rule_model_at(1, 6);
+    model.replace_text(utf16("X"));
+    assert_eq!(tx(&model), "<p>aX|d</p>");
+}
+
+#[test]
+fn replacing_a_selected_horizontal_rule_adds_a_paragraph() {
+    let mut model = rule_model_at(3, 4);
+    model.replace_text(utf16("X"));
+    assert_eq!(tx(&model), "<p>ab</p><p>X|cd</p>");
+}
+
+#[test]
+fn formatting_a_selected_horizontal_rule_does_nothing() {
+    let mut model = rule_model_at(3, 4);
+    model.bold();
+    assert_eq!(tx(&model), "<p>ab</p>{<hr />}|<p>cd</p>");
+}
+
+#[test]
+fn formatting_across_a_horizontal_rule_skips_it() {
+    let mut model = rule_model_at(1, 6);
+    model.bold();
+    assert_eq!(
+        tx(&model),
+        "<p>a<strong>{b</strong></p><hr /><p><strong>c}|</strong>d</p>"
+    );
+}
+
+#[test]
+fn inline_code_across_a_horizontal_rule_skips_it() {
+    let mut model = rule_model_at(1, 6);
+    model.inline_code();
+    assert_eq!(
+        tx(&model),
+        "<p>a<code>{b</code></p><hr /><p><code>c}|</code>d</p>"
+    );
+}
+
+#[test]
+fn linking_a_selected_horizontal_rule_does_nothing() {
+    let mut model = rule_model_at(3, 4);
+    model.set_link(utf16("https://matrix.org"), vec![]);
+    assert_eq!(tx(&model), "<p>ab</p>{<hr />}|<p>cd</p>");
+}
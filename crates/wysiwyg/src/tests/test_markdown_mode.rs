@@ -0,0 +1,197 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::{Location, TextUpdate};
+
+#[test]
+fn switching_to_markdown_mode_writes_markdown_as_text() {
+    let mut model = cm("a <strong>bold</strong> word|");
+    model.switch_to_markdown_mode();
+    assert!(model.is_markdown_mode());
+    assert_eq!(tx(&model), "a __bold__ word|");
+}
+
+#[test]
+fn switching_to_markdown_mode_keeps_cursor_on_same_text() {
+    let mut model = cm("a <strong>bo|ld</strong> word");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "a __bo|ld__ word");
+}
+
+#[test]
+fn switching_to_markdown_mode_keeps_cursor_after_formatting() {
+    let mut model = cm("a <strong>bold</strong> w|ord");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "a __bold__ w|ord");
+}
+
+#[test]
+fn switching_to_markdown_mode_keeps_selection() {
+    let mut model = cm("a <em>i{t</em> and <strong>bo}|ld</strong>");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "a *i{t* and __bo}|ld__");
+}
+
+#[test]
+fn switching_to_markdown_mode_keeps_backwards_selection() {
+    let mut model = cm("a <em>i|{t</em> and <strong>bo}ld</strong>");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "a *i|{t* and __bo}ld__");
+}
+
+#[test]
+fn switching_to_markdown_mode_writes_a_paragraph_per_line() {
+    let mut model = cm("<ol><li>one</li><li>tw|o</li></ol>");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "<p>1. one</p><p>2. tw|o</p>");
+}
+
+#[test]
+fn switching_to_markdown_mode_maps_cursor_in_link() {
+    let mut model = cm("<a href=\"https://matrix.org\">Mat|rix</a> site");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "[Mat|rix](&lt;https://matrix.org&gt;) site");
+}
+
+#[test]
+fn switching_to_markdown_mode_of_empty_model() {
+    let mut model = cm("|");
+    model.switch_to_markdown_mode();
+    assert!(model.is_markdown_mode());
+    assert_eq!(tx(&model), "|");
+}
+
+#[test]
+fn switching_to_markdown_mode_twice_does_nothing() {
+    let mut model = cm("<strong>a|</strong>");
+    model.switch_to_markdown_mode();
+    let update = model.switch_to_markdown_mode();
+    assert!(matches!(update.text_update, TextUpdate::Keep));
+    assert_eq!(tx(&model), "__a|__");
+}
+
+#[test]
+fn switching_to_rich_mode_formats_markdown() {
+    let mut model = cm("|");
+    model.switch_to_markdown_mode();
+    model.replace_text("a __bold__ word".into());
+    model.switch_to_rich_mode().unwrap();
+    assert!(!model.is_markdown_mode());
+    assert_eq!(tx(&model), "a <strong>bold</strong> word|");
+}
+
+#[test]
+fn switching_to_rich_mode_keeps_cursor_on_same_text() {
+    let mut model = cm("|");
+    model.switch_to_markdown_mode();
+    model.replace_text("a __bold__ word".into());
+    model.select(Location::from(6), Location::from(6));
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(tx(&model), "a <strong>bo|ld</strong> word");
+}
+
+#[test]
+fn switching_to_rich_mode_keeps_selection_across_paragraphs() {
+    let mut model = cm("<p>*it*</p><p></p><p>__bold__|</p>");
+    model.state.markdown_mode = true;
+    model.select(Location::from(2), Location::from(10));
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(
+        tx(&model),
+        "<p><em>i{t</em></p><p><strong>bo}|ld</strong></p>"
+    );
+}
+
+#[test]
+fn switching_to_rich_mode_with_cursor_in_emphasis_syntax() {
+    let mut model = cm("a __bold_|_");
+    model.state.markdown_mode = true;
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(tx(&model), "a <strong>bold|</strong>");
+}
+
+#[test]
+fn switching_to_rich_mode_with_cursor_before_emphasis_syntax() {
+    let mut model = cm("a |__bold__");
+    model.state.markdown_mode = true;
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(tx(&model), "a |<strong>bold</strong>");
+}
+
+#[test]
+fn switching_to_rich_mode_with_cursor_in_link_syntax() {
+    let mut model = cm("see [link](https://ex|ample.org)");
+    model.state.markdown_mode = true;
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(tx(&model), "see |<a href=\"https://example.org\">link</a>");
+}
+
+#[test]
+fn switching_to_rich_mode_with_cursor_in_heading_syntax_goes_to_end() {
+    let mut model = cm("#|# title");
+    model.state.markdown_mode = true;
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(
+        tx(&model),
+        "<h2 contenteditable=\"false\">title</h2><p>\n|</p>"
+    );
+}
+
+#[test]
+fn switching_to_rich_mode_when_not_in_markdown_mode_does_nothing() {
+    let mut model = cm("__a__|");
+    let update = model.switch_to_rich_mode().unwrap();
+    assert!(matches!(update.text_update, TextUpdate::Keep));
+    assert_eq!(tx(&model), "__a__|");
+}
+
+#[test]
+fn switching_modes_round_trips() {
+    let mut model = cm("<p>a <em>b|c</em></p><ul><li>d</li></ul>");
+    model.switch_to_markdown_mode();
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(tx(&model), "<p>a <em>b|c</em></p><ul><li>d</li></ul>");
+}
+
+#[test]
+fn switching_to_markdown_mode_is_one_undo_step() {
+    let mut model = cm("a <strong>bo|ld</strong>");
+    model.switch_to_markdown_mode();
+    model.undo();
+    assert!(!model.is_markdown_mode());
+    assert_eq!(tx(&model), "a <strong>bo|ld</strong>");
+    model.redo();
+    assert!(model.is_markdown_mode());
+    assert_eq!(tx(&model), "a __bo|ld__");
+}
+
+#[test]
+fn switching_to_rich_mode_is_one_undo_step() {
+    let mut model = cm("a <strong>bo|ld</strong>");
+    model.switch_to_markdown_mode();
+    model.switch_to_rich_mode().unwrap();
+    model.undo();
+    assert!(model.is_markdown_mode());
+    assert_eq!(tx(&model), "a __bo|ld__");
+}
+
+#[test]
+fn switching_modes_maps_cursor_after_multi_code_unit_characters() {
+    let mut model = cm("😀 <strong>bo|ld</strong>");
+    model.switch_to_markdown_mode();
+    assert_eq!(tx(&model), "😀 __bo|ld__");
+    model.switch_to_rich_mode().unwrap();
+    assert_eq!(tx(&model), "😀 <strong>bo|ld</strong>");
+}
@@ -12,11 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::PatternKey::{At, Hash, Slash};
+use crate::PatternKey::{At, Colon, Custom, Hash, Slash};
 use crate::{
-    tests::testutils_composer_model::cm, Location, MenuAction, PatternKey,
-    SuggestionPattern,
+    tests::testutils_composer_model::cm, ComposerModel, Location, MenuAction,
+    PatternKey, SuggestionPattern, SuggestionTrigger,
 };
+use widestring::Utf16String;
 
 // MenuAction computation tests.
 #[test]
@@ -122,6 +123,325 @@ fn slash_pattern_is_not_detected_if_not_at_the_beginning_of_dom() {
 }
 
 // MenuAction update tests.
+#[test]
+fn colon_pattern_is_detected() {
+    let model = cm(":smi|");
+    assert_eq!(model.compute_menu_action(), sp(Colon, "smi", 0, 4));
+}
+
+#[test]
+fn colon_pattern_is_detected_after_text() {
+    let model = cm("Hello :smi|");
+    assert_eq!(model.compute_menu_action(), sp(Colon, "smi", 6, 10));
+}
+
+#[test]
+fn colon_pattern_is_not_detected_if_too_short() {
+    let model = cm(":s|");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn colon_pattern_respects_configured_min_length() {
+    let mut model = cm(":s|");
+    model.set_emoji_suggestion_min_length(1);
+    assert_eq!(model.compute_menu_action(), sp(Colon, "s", 0, 2));
+}
+
+#[test]
+fn colon_pattern_is_not_detected_below_configured_min_length() {
+    let mut model = cm(":smi|");
+    model.set_emoji_suggestion_min_length(4);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn colon_pattern_is_not_detected_after_digits() {
+    let model = cm("10:30|");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn colon_pattern_is_not_detected_after_digits_in_another_node() {
+    let model = cm("<strong>10</strong>:30|");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn colon_pattern_is_detected_after_letters_in_another_node() {
+    let model = cm("<strong>a </strong>:smi|");
+    assert_eq!(model.compute_menu_action(), sp(Colon, "smi", 2, 6));
+}
+
+#[test]
+fn colon_pattern_is_not_detected_once_closed() {
+    let model = cm(":smile:|");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn colon_pattern_is_not_detected_in_inline_code() {
+    let model = cm("<code>:smi|</code>");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn colon_pattern_is_not_detected_in_code_block() {
+    let model = cm("<pre><code>:smi|</code></pre>");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_pattern_is_not_detected_without_trigger() {
+    let model = cm("!remi|");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_pattern_is_detected() {
+    let model = with_trigger("Hey !remi|", trigger('!'));
+    assert_eq!(model.compute_menu_action(), sp(Custom('!'), "remi", 4, 9));
+}
+
+#[test]
+fn empty_custom_pattern_is_detected() {
+    let model = with_trigger("$|", trigger('$'));
+    assert_eq!(model.compute_menu_action(), sp(Custom('$'), "", 0, 1));
+}
+
+#[test]
+fn custom_pattern_is_not_detected_if_preceded_by_non_whitespace_char() {
+    let model = with_trigger("US$10|", trigger('$'));
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_pattern_is_not_detected_in_code() {
+    let model = with_trigger("<code>$AB|</code>", trigger('$'));
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_pattern_only_at_start_is_detected_at_start() {
+    let model = with_trigger(
+        "!remi|",
+        SuggestionTrigger {
+            only_at_start: true,
+            ..trigger('!')
+        },
+    );
+    assert_eq!(model.compute_menu_action(), sp(Custom('!'), "remi", 0, 5));
+}
+
+#[test]
+fn custom_pattern_only_at_start_is_not_detected_after_text() {
+    let model = with_trigger(
+        "Hey !remi|",
+        SuggestionTrigger {
+            only_at_start: true,
+            ..trigger('!')
+        },
+    );
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_pattern_respects_min_length() {
+    let t = SuggestionTrigger {
+        min_length: 2,
+        ..trigger('$')
+    };
+    let model = with_trigger("$A|", t.clone());
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+    let model = with_trigger("$AB|", t);
+    assert_eq!(model.compute_menu_action(), sp(Custom('$'), "AB", 0, 3));
+}
+
+#[test]
+fn custom_pattern_is_not_detected_after_whitespace_by_default() {
+    let model = with_trigger("!remind me|", trigger('!'));
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_pattern_allowing_whitespace_is_detected_after_whitespace() {
+    let model = with_trigger(
+        "Hey !remind me to|",
+        SuggestionTrigger {
+            allow_whitespace: true,
+            ..trigger('!')
+        },
+    );
+    assert_eq!(
+        model.compute_menu_action(),
+        sp(Custom('!'), "remind me to", 4, 17)
+    );
+}
+
+#[test]
+fn custom_pattern_allowing_whitespace_extends_to_end_of_word() {
+    let model = with_trigger(
+        "!remind me t|o later",
+        SuggestionTrigger {
+            allow_whitespace: true,
+            ..trigger('!')
+        },
+    );
+    assert_eq!(
+        model.compute_menu_action(),
+        sp(Custom('!'), "remind me to", 0, 13)
+    );
+}
+
+#[test]
+fn custom_pattern_allowing_whitespace_uses_the_last_trigger() {
+    let model = with_trigger(
+        "!a b !c d|",
+        SuggestionTrigger {
+            allow_whitespace: true,
+            ..trigger('!')
+        },
+    );
+    assert_eq!(model.compute_menu_action(), sp(Custom('!'), "c d", 5, 9));
+}
+
+#[test]
+fn custom_pattern_allowing_whitespace_needs_trigger_to_start_a_word() {
+    let model = with_trigger(
+        "Hey! how are|",
+        SuggestionTrigger {
+            allow_whitespace: true,
+            ..trigger('!')
+        },
+    );
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn custom_trigger_does_not_replace_built_in_key() {
+    let model = with_trigger("@alic|", trigger('@'));
+    assert_eq!(model.compute_menu_action(), sp(At, "alic", 0, 5));
+}
+
+#[test]
+fn adding_a_trigger_replaces_one_for_the_same_key() {
+    let mut model = cm("|");
+    model.add_suggestion_trigger(trigger('!'));
+    model.add_suggestion_trigger(trigger('+'));
+    model.add_suggestion_trigger(SuggestionTrigger {
+        min_length: 2,
+        ..trigger('!')
+    });
+    assert_eq!(
+        model.suggestion_triggers(),
+        [
+            trigger('+'),
+            SuggestionTrigger {
+                min_length: 2,
+                ..trigger('!')
+            }
+        ]
+    );
+}
+
+#[test]
+fn multi_word_at_pattern_is_not_detected_by_default() {
+    let model = cm("@John Sm|");
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_is_detected() {
+    let model = with_max_words("Hey @John Sm|", 2);
+    assert_eq!(model.compute_menu_action(), sp(At, "John Sm", 4, 12));
+}
+
+#[test]
+fn multi_word_hash_pattern_is_detected() {
+    let model = with_max_words("#Element Web|", 2);
+    assert_eq!(model.compute_menu_action(), sp(Hash, "Element Web", 0, 12));
+}
+
+#[test]
+fn multi_word_at_pattern_is_detected_after_trailing_space() {
+    let model = with_max_words("@John |", 2);
+    assert_eq!(model.compute_menu_action(), sp(At, "John ", 0, 6));
+}
+
+#[test]
+fn multi_word_at_pattern_extends_to_end_of_word() {
+    let model = with_max_words("@John Sm|ith", 2);
+    assert_eq!(model.compute_menu_action(), sp(At, "John Smith", 0, 11));
+}
+
+#[test]
+fn multi_word_at_pattern_is_not_detected_beyond_word_limit() {
+    let model = with_max_words("@John Smith Jr|", 2);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+    let model = with_max_words("@John Smith |", 2);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_is_not_detected_after_punctuation() {
+    let model = with_max_words("@John, how|", 3);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_allows_punctuation_in_names() {
+    let model = with_max_words("@Jean-Luc O'Br|", 2);
+    assert_eq!(model.compute_menu_action(), sp(At, "Jean-Luc O'Br", 0, 14));
+}
+
+#[test]
+fn multi_word_at_pattern_spans_text_nodes() {
+    let model = with_max_words("<em>@John</em> Sm|", 2);
+    assert_eq!(model.compute_menu_action(), sp(At, "John Sm", 0, 8));
+}
+
+#[test]
+fn multi_word_at_pattern_spans_text_nodes_in_paragraphs() {
+    let model = with_max_words("<p>a</p><p>Hi <em>@John</em> Sm|</p>", 2);
+    assert_eq!(model.compute_menu_action(), sp(At, "John Sm", 5, 13));
+}
+
+#[test]
+fn multi_word_at_pattern_is_not_detected_across_line_break() {
+    let model = with_max_words("@John<br />Sm|", 2);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_is_not_detected_across_paragraphs() {
+    let model = with_max_words("<p>@John</p><p>Sm|</p>", 2);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_is_not_detected_in_code() {
+    let model = with_max_words("<code>@John Sm|</code>", 2);
+    assert_eq!(model.compute_menu_action(), MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_is_updated_on_whitespace_input() {
+    let mut model = with_max_words("@John|", 2);
+    let update = model.replace_text(" ".into());
+    assert_eq!(update.menu_action, sp(At, "John ", 0, 6));
+    let update = model.replace_text("S".into());
+    assert_eq!(update.menu_action, sp(At, "John S", 0, 7));
+    let update = model.replace_text(",".into());
+    assert_eq!(update.menu_action, MenuAction::None);
+}
+
+#[test]
+fn multi_word_at_pattern_is_closed_by_enter() {
+    let mut model = with_max_words("@John Sm|", 2);
+    let update = model.enter();
+    assert_eq!(update.menu_action, MenuAction::None);
+}
+
 #[test]
 fn at_pattern_is_updated_on_character_input() {
     let mut model = cm("|");
@@ -203,6 +523,33 @@ fn menu_action_retuns_keep_after_format_with_cursor() {
     assert_eq!(update.menu_action, MenuAction::Keep);
 }
 
+/// A model for [html] with a single custom suggestion trigger.
+fn with_trigger(
+    html: &str,
+    trigger: SuggestionTrigger,
+) -> ComposerModel<Utf16String> {
+    let mut model = cm(html);
+    model.set_suggestion_triggers(vec![trigger]);
+    model
+}
+
+/// A model for [html] allowing mention suggestions of [max_words] words.
+fn with_max_words(html: &str, max_words: usize) -> ComposerModel<Utf16String> {
+    let mut model = cm(html);
+    model.set_mention_suggestion_max_words(max_words);
+    model
+}
+
+/// A trigger for [key] with no extra rules.
+fn trigger(key: char) -> SuggestionTrigger {
+    SuggestionTrigger {
+        key,
+        only_at_start: false,
+        allow_whitespace: false,
+        min_length: 0,
+    }
+}
+
 /// Short wrapper around [MenuAction::Suggestion(SuggestionPattern)].
 fn sp(k: PatternKey, t: &str, s: usize, e: usize) -> MenuAction {
     MenuAction::Suggestion(SuggestionPattern {
@@ -48,12 +48,12 @@ fn selecting_multiple_nodes_updates_reversed_actions() {
     let model = cm("<ol><li>{ab</li><li><b>cd</b>}|</li></ol>");
     assert!(model.action_is_reversed(ComposerAction::OrderedList));
     let model = cm("<ol><li>{ab</li></ol>cd}|");
-    assert!(model.action_is_enabled(ComposerAction::OrderedList));
+    assert!(model.action_is_mixed(ComposerAction::OrderedList));
 
     let mut model = cm("<a href=\"https://matrix.org\">{link}|</a>ab");
     assert!(model.action_is_reversed(ComposerAction::Link));
     model.select(Location::from(2), Location::from(6));
-    assert!(model.action_is_enabled(ComposerAction::Link));
+    assert!(model.action_is_mixed(ComposerAction::Link));
 
     let mut model = cm("<del>{ab<em>cd}|</em></del>");
     assert!(model.action_is_reversed(ComposerAction::StrikeThrough));
@@ -302,3 +302,46 @@ fn assert_formatting_actions_and_links_are_disabled(
 fn replace_text(model: &mut ComposerModel<Utf16String>, new_text: &str) {
     model.replace_text(utf16(new_text));
 }
+
+#[test]
+fn partially_formatted_selection_marks_actions_as_mixed() {
+    let mut model = cm("<strong>a{bc</strong>de}|");
+    assert!(model.action_is_mixed(ComposerAction::Bold));
+    assert!(model.action_is_enabled(ComposerAction::Italic));
+
+    model.select(Location::from(1), Location::from(3));
+    assert!(model.action_is_reversed(ComposerAction::Bold));
+}
+
+#[test]
+fn mixed_state_is_not_reported_for_adjacent_nodes() {
+    let model = cm("<em>ab</em>{cd}|<del>ef</del>");
+    assert!(model.action_is_enabled(ComposerAction::Italic));
+    assert!(model.action_is_enabled(ComposerAction::StrikeThrough));
+}
+
+#[test]
+fn mixed_state_is_reported_for_quotes_and_lists() {
+    let model = cm("<blockquote><p>{ab</p></blockquote><p>cd}|</p>");
+    assert!(model.action_is_mixed(ComposerAction::Quote));
+
+    let model = cm("<ul><li>{ab</li></ul><ol><li>cd}|</li></ol>");
+    assert!(model.action_is_mixed(ComposerAction::UnorderedList));
+    assert!(model.action_is_mixed(ComposerAction::OrderedList));
+}
+
+#[test]
+fn mixed_state_is_never_reported_for_a_cursor() {
+    let model = cm("<strong>ab|</strong>cd");
+    assert!(model.action_is_reversed(ComposerAction::Bold));
+    let model = cm("<strong>ab</strong>|cd");
+    assert!(!model.action_is_mixed(ComposerAction::Bold));
+}
+
+#[test]
+fn formatting_a_mixed_selection_extends_the_format() {
+    let mut model = cm("<em>a{bc</em>de}|f");
+    assert!(model.action_is_mixed(ComposerAction::Italic));
+    model.italic();
+    assert!(model.action_is_reversed(ComposerAction::Italic));
+}
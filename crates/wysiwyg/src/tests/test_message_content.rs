@@ -0,0 +1,143 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::cm;
+use crate::tests::testutils_conversion::utf16;
+use crate::{
+    DomNode, ListType, MessageContent, MessageContentOptions, MessageType,
+};
+
+fn content(html: &str) -> MessageContent<Utf16String> {
+    cm(html).to_message_content(MessageContentOptions::default())
+}
+
+#[test]
+fn plain_text_has_no_formatted_body() {
+    let content = content("hello|");
+    assert_eq!(content.msgtype, MessageType::Text);
+    assert_eq!(content.body, "hello");
+    assert_eq!(content.format, None);
+    assert_eq!(content.formatted_body, None);
+}
+
+#[test]
+fn paragraphs_are_not_formatting() {
+    let content = content("<p>a</p><p>b|</p>");
+    assert_eq!(content.body, "a\nb");
+    assert_eq!(content.formatted_body, None);
+}
+
+#[test]
+fn formatted_text_has_formatted_body() {
+    let content = content("<strong>hello</strong> world|");
+    assert_eq!(content.body, "hello world");
+    assert_eq!(content.format.unwrap(), "org.matrix.custom.html");
+    assert_eq!(
+        content.formatted_body.unwrap(),
+        "<strong>hello</strong> world"
+    );
+}
+
+#[test]
+fn body_can_be_markdown() {
+    let model = cm("<em>hello</em>|");
+    let content = model.to_message_content(MessageContentOptions {
+        msgtype: MessageType::Text,
+        markdown_body: true,
+    });
+    assert_eq!(content.body, "*hello*");
+}
+
+#[test]
+fn body_is_plain_text_if_content_cannot_be_markdown() {
+    let mut model = cm("|");
+    model.state.dom.append_at_end_of_document(DomNode::new_list(
+        ListType::Unordered,
+        vec![DomNode::new_text(utf16("hello"))],
+    ));
+    assert!(model.try_get_content_as_markdown().is_err());
+    let content = model.to_message_content(MessageContentOptions {
+        msgtype: MessageType::Text,
+        markdown_body: true,
+    });
+    assert_eq!(content.body, "hello");
+}
+
+#[test]
+fn msgtype_is_taken_from_options() {
+    let model = cm("waves|");
+    let content = model.to_message_content(MessageContentOptions {
+        msgtype: MessageType::Emote,
+        markdown_body: false,
+    });
+    assert_eq!(content.msgtype, MessageType::Emote);
+    assert!(content.to_json().contains(r#""msgtype":"m.emote""#));
+}
+
+#[test]
+fn user_mentions_are_listed_once() {
+    let content = content(
+        "<a href=\"https://matrix.to/#/@alice:example.org\">Alice</a> \
+         <a href=\"https://matrix.to/#/%40bob%3Aexample.org?via=a.org\">Bob</a> \
+         <a href=\"https://matrix.to/#/@alice:example.org\">Alice</a>|",
+    );
+    assert_eq!(
+        content.mentions.user_ids,
+        vec![utf16("@alice:example.org"), utf16("@bob:example.org")]
+    );
+    assert!(!content.mentions.room);
+    assert!(content.formatted_body.is_some());
+}
+
+#[test]
+fn mention_nodes_are_user_mentions() {
+    let content =
+        content("<a href=\"https://matrix.to/#/@test:example.org\">test</a>|");
+    assert_eq!(content.mentions.user_ids, vec![utf16("@test:example.org")]);
+    assert!(content.formatted_body.is_some());
+}
+
+#[test]
+fn room_mentions_are_not_user_mentions() {
+    let content =
+        content("<a href=\"https://matrix.to/#/#room:example.org\">Room</a>|");
+    assert!(content.mentions.user_ids.is_empty());
+}
+
+#[test]
+fn at_room_mentions_the_room() {
+    let content = content("@room hello|");
+    assert!(content.mentions.room);
+}
+
+#[test]
+fn json_contains_all_fields() {
+    let content = content(
+        "<strong>\"hi\"</strong> <a href=\"https://matrix.to/#/@alice:example.org\">Alice</a>|",
+    );
+    assert_eq!(
+        content.to_json(),
+        r#"{"msgtype":"m.text","body":"\"hi\" Alice","format":"org.matrix.custom.html","formatted_body":"<strong>\"hi\"</strong> <a href=\"https://matrix.to/#/@alice:example.org\">Alice</a>","m.mentions":{"user_ids":["@alice:example.org"]}}"#
+    );
+}
+
+#[test]
+fn json_of_plain_text_omits_formatted_body() {
+    assert_eq!(
+        content("|").to_json(),
+        r#"{"msgtype":"m.text","body":"","m.mentions":{}}"#
+    );
+}
@@ -0,0 +1,215 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::Direction::{Backwards, Forwards};
+use crate::Granularity::{Character, Document, Line, Paragraph, Word};
+
+#[test]
+fn move_by_character() {
+    let mut model = cm("a|bc");
+    model.move_cursor(Forwards, Character, false);
+    assert_eq!(tx(&model), "ab|c");
+    model.move_cursor(Backwards, Character, false);
+    model.move_cursor(Backwards, Character, false);
+    assert_eq!(tx(&model), "|abc");
+    model.move_cursor(Backwards, Character, false);
+    assert_eq!(tx(&model), "|abc");
+}
+
+#[test]
+fn move_by_character_skips_whole_graphemes() {
+    let mut model = cm("|\u{1F4A9}b");
+    model.move_cursor(Forwards, Character, false);
+    assert_eq!(tx(&model), "\u{1F4A9}|b");
+
+    let mut model = cm("a😮‍💨|");
+    model.move_cursor(Backwards, Character, false);
+    assert_eq!(tx(&model), "a|😮‍💨");
+}
+
+#[test]
+fn move_by_character_collapses_a_selection() {
+    let mut model = cm("a{bc}|d");
+    model.move_cursor(Backwards, Character, false);
+    assert_eq!(tx(&model), "a|bcd");
+
+    let mut model = cm("a{bc}|d");
+    model.move_cursor(Forwards, Character, false);
+    assert_eq!(tx(&model), "abc|d");
+}
+
+#[test]
+fn move_by_character_across_formatting() {
+    let mut model = cm("a|<b>b</b>c");
+    model.move_cursor(Forwards, Character, false);
+    model.move_cursor(Forwards, Character, false);
+    assert_eq!(tx(&model), "a<b>b</b>c|");
+}
+
+#[test]
+fn move_by_character_skips_mentions() {
+    let mut model =
+        cm("a|<a href=\"https://matrix.to/#/@test:example.org\">test</a>b");
+    model.move_cursor(Forwards, Character, false);
+    model.move_cursor(Forwards, Character, false);
+    assert_eq!(
+        tx(&model),
+        "a<a href=\"https://matrix.to/#/@test:example.org\" contenteditable=\"false\">test</a>b|"
+    );
+}
+
+#[test]
+fn move_by_word_forwards() {
+    let mut model = cm("|abc def, ghi");
+    model.move_cursor(Forwards, Word, false);
+    assert_eq!(tx(&model), "abc| def, ghi");
+    model.move_cursor(Forwards, Word, false);
+    assert_eq!(tx(&model), "abc def|, ghi");
+    model.move_cursor(Forwards, Word, false);
+    assert_eq!(tx(&model), "abc def,| ghi");
+    model.move_cursor(Forwards, Word, false);
+    assert_eq!(tx(&model), "abc def, ghi|");
+}
+
+#[test]
+fn move_by_word_backwards() {
+    let mut model = cm("abc def ghi|");
+    model.move_cursor(Backwards, Word, false);
+    assert_eq!(tx(&model), "abc def |ghi");
+    model.move_cursor(Backwards, Word, false);
+    assert_eq!(tx(&model), "abc |def ghi");
+    model.move_cursor(Backwards, Word, false);
+    assert_eq!(tx(&model), "|abc def ghi");
+}
+
+#[test]
+fn move_by_word_matches_backspace_word() {
+    let text = "abc  def, <b>ghi</b>  jk|";
+    let mut moved = cm(text);
+    moved.move_cursor(Backwards, Word, true);
+    moved.delete();
+    let mut deleted = cm(text);
+    deleted.backspace_word();
+    assert_eq!(tx(&moved), tx(&deleted));
+}
+
+#[test]
+fn move_by_word_across_paragraphs() {
+    let mut model = cm("<p>abc|</p><p>def</p>");
+    model.move_cursor(Forwards, Word, false);
+    assert_eq!(tx(&model), "<p>abc</p><p>def|</p>");
+}
+
+#[test]
+fn move_by_line_stops_at_line_breaks() {
+    let mut model = cm("ab<br />c|d<br />ef");
+    model.move_cursor(Backwards, Line, false);
+    assert_eq!(tx(&model), "ab<br />|cd<br />ef");
+    model.move_cursor(Forwards, Line, false);
+    assert_eq!(tx(&model), "ab<br />cd|<br />ef");
+}
+
+#[test]
+fn move_by_paragraph() {
+    let mut model = cm("<p>ab</p><p>c|d<br />ef</p><p>gh</p>");
+    model.move_cursor(Forwards, Paragraph, false);
+    assert_eq!(tx(&model), "<p>ab</p><p>cd<br />ef|</p><p>gh</p>");
+    model.move_cursor(Backwards, Paragraph, false);
+    assert_eq!(tx(&model), "<p>ab</p><p>|cd<br />ef</p><p>gh</p>");
+}
+
+#[test]
+fn move_by_paragraph_in_list_items() {
+    let mut model = cm("<ol><li>ab</li><li>c|d</li></ol>");
+    model.move_cursor(Backwards, Paragraph, false);
+    assert_eq!(tx(&model), "<ol><li>ab</li><li>|cd</li></ol>");
+}
+
+#[test]
+fn move_by_document() {
+    let mut model = cm("<p>ab</p><p>c|d</p>");
+    model.move_cursor(Forwards, Document, false);
+    assert_eq!(tx(&model), "<p>ab</p><p>cd|</p>");
+    model.move_cursor(Backwards, Document, false);
+    assert_eq!(tx(&model), "<p>|ab</p><p>cd</p>");
+}
+
+#[test]
+fn extend_selection_keeps_the_start_of_the_selection() {
+    let mut model = cm("ab|c def");
+    model.move_cursor(Forwards, Word, true);
+    assert_eq!(tx(&model), "ab{c}| def");
+    model.move_cursor(Forwards, Word, true);
+    assert_eq!(tx(&model), "ab{c def}|");
+    model.move_cursor(Backwards, Document, true);
+    assert_eq!(tx(&model), "|{ab}c def");
+}
+
+#[test]
+fn select_word_at_cursor_inside_a_word() {
+    let mut model = cm("abc d|ef ghi");
+    model.select_word_at_cursor();
+    assert_eq!(tx(&model), "abc {def}| ghi");
+}
+
+#[test]
+fn select_word_at_cursor_prefers_words_over_spaces() {
+    let mut model = cm("abc |def");
+    model.select_word_at_cursor();
+    assert_eq!(tx(&model), "abc {def}|");
+
+    let mut model = cm("abc|, def");
+    model.select_word_at_cursor();
+    assert_eq!(tx(&model), "abc{,}| def");
+
+    let mut model = cm("abc|\n");
+    model.select_word_at_cursor();
+    assert_eq!(tx(&model), "{abc}|\n");
+}
+
+#[test]
+fn select_word_at_cursor_spans_formatting() {
+    let mut model = cm("a<b>b|c</b>d e");
+    model.select_word_at_cursor();
+    assert_eq!(tx(&model), "{a<b>bc</b>d}| e");
+}
+
+#[test]
+fn select_word_at_cursor_in_empty_model_does_nothing() {
+    let mut model = cm("|");
+    model.select_word_at_cursor();
+    assert_eq!(tx(&model), "|");
+}
+
+#[test]
+fn select_block_at_cursor() {
+    let mut model = cm("<p>ab</p><p>c|d<br />ef</p><p>gh</p>");
+    model.select_block_at_cursor();
+    assert_eq!(tx(&model), "<p>ab</p><p>{cd<br />ef}|</p><p>gh</p>");
+}
+
+#[test]
+fn select_block_at_cursor_with_selection_across_blocks() {
+    let mut model = cm("<ul><li>a{b</li><li>c}|d</li><li>ef</li></ul>");
+    model.select_block_at_cursor();
+    assert_eq!(tx(&model), "<ul><li>{ab</li><li>cd}|</li><li>ef</li></ul>");
+}
+
+#[test]
+fn select_all() {
+    let mut model = cm("<p>a|b</p><p>cd</p>");
+    model.select_all();
+    assert_eq!(tx(&model), "<p>{ab</p><p>cd}|</p>");
+}
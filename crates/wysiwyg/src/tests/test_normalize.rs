@@ -0,0 +1,235 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::dom::nodes::ContainerNode;
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_dom::{b, dom, handle, tn};
+use crate::{
+    ContainerNodeKind, DomNode, InvariantViolation, ListType, Location, ToHtml,
+};
+
+#[test]
+fn well_formed_dom_has_no_violations() {
+    let model = cm("<p>a<b>b</b></p><ol><li>c</li></ol>|");
+    assert_eq!(model.state.dom.validate(), vec![]);
+}
+
+#[test]
+fn validate_reports_every_violation() {
+    let dom = dom(&[tn("a"), tn(""), tn("b"), b(&[])]);
+    assert_eq!(
+        dom.validate(),
+        vec![
+            InvariantViolation::EmptyTextNode(handle(vec![1])),
+            InvariantViolation::AdjacentTextNodes(handle(vec![0])),
+            InvariantViolation::AdjacentTextNodes(handle(vec![1])),
+            InvariantViolation::EmptyInlineContainer(handle(vec![3])),
+        ]
+    );
+}
+
+#[test]
+fn empty_formatting_in_empty_block_is_not_a_violation() {
+    let dom = dom(&[DomNode::new_paragraph(vec![b(&[])])]);
+    assert_eq!(dom.validate(), vec![]);
+}
+
+#[test]
+fn validate_reports_misplaced_list_items() {
+    let dom = dom(&[
+        DomNode::new_list_item(vec![tn("a")]),
+        DomNode::new_list(ListType::Ordered, vec![tn("b")]),
+    ]);
+    assert_eq!(
+        dom.validate(),
+        vec![
+            InvariantViolation::ListItemOutsideList(handle(vec![0])),
+            InvariantViolation::NonListItemInList(handle(vec![1, 0])),
+        ]
+    );
+}
+
+#[test]
+fn normalize_merges_text_and_removes_empty_nodes() {
+    let mut dom = dom(&[tn("a"), tn(""), tn("b"), b(&[tn("")]), tn("c")]);
+    dom.normalize();
+    assert_eq!(dom.to_html(), "abc");
+    assert_eq!(dom.validate(), vec![]);
+}
+
+#[test]
+fn normalize_keeps_formatting_of_empty_blocks() {
+    let mut dom = dom(&[
+        DomNode::new_paragraph(vec![tn("a")]),
+        DomNode::new_paragraph(vec![b(&[])]),
+    ]);
+    dom.normalize();
+    assert_eq!(dom.to_html(), "<p>a</p><p><b></b></p>");
+}
+
+#[test]
+fn normalize_replaces_nested_generic_containers_with_their_children() {
+    let mut dom = dom(&[
+        tn("a"),
+        DomNode::Container(ContainerNode::new(
+            Utf16String::from("div"),
+            ContainerNodeKind::Generic,
+            None,
+            vec![tn("b")],
+        )),
+    ]);
+    dom.normalize();
+    assert_eq!(dom.to_html(), "ab");
+    assert_eq!(dom.validate(), vec![]);
+}
+
+#[test]
+fn normalize_puts_list_items_into_lists() {
+    let mut dom = dom(&[
+        DomNode::new_list(
+            ListType::Ordered,
+            vec![
+                DomNode::new_list_item(vec![tn("a")]),
+                DomNode::new_list(
+                    ListType::Ordered,
+                    vec![DomNode::new_list_item(vec![tn("b")])],
+                ),
+            ],
+        ),
+        DomNode::new_list_item(vec![tn("c")]),
+        DomNode::new_paragraph(vec![tn("d")]),
+        DomNode::new_list_item(vec![tn("e")]),
+    ]);
+    dom.normalize();
+    assert_eq!(
+        dom.to_html(),
+        "<ol><li><p>a</p><ol><li>b</li></ol></li><li>c</li></ol>\
+        <p>d</p><ul><li>e</li></ul>"
+    );
+    assert_eq!(dom.validate(), vec![]);
+}
+
+#[test]
+fn normalize_repairs_tables() {
+    let mut dom = dom(&[
+        DomNode::new_table(vec![
+            DomNode::new_table_row(vec![
+                DomNode::new_table_cell(false, vec![tn("a")]),
+                DomNode::new_table_cell(false, vec![tn("b")]),
+            ]),
+            DomNode::new_table_cell(false, vec![tn("c")]),
+        ]),
+        DomNode::new_table(vec![]),
+        DomNode::new_table_row(vec![DomNode::new_table_cell(
+            false,
+            vec![tn("d")],
+        )]),
+    ]);
+    dom.normalize();
+    assert_eq!(
+        dom.to_html(),
+        "<table><tbody>\
+        <tr><td>a</td><td>b</td></tr>\
+        <tr><td>c</td><td></td></tr>\
+        <tr><td>d</td><td></td></tr>\
+        </tbody></table>"
+    );
+    assert_eq!(dom.validate(), vec![]);
+}
+
+#[test]
+fn normalize_wraps_inline_nodes_next_to_blocks_in_paragraphs() {
+    let mut dom = dom(&[
+        tn("a"),
+        DomNode::new_quote(vec![DomNode::new_paragraph(vec![tn("b")])]),
+    ]);
+    dom.normalize();
+    assert_eq!(dom.to_html(), "<p>a</p><blockquote><p>b</p></blockquote>");
+    assert_eq!(dom.validate(), vec![]);
+}
+
+#[test]
+fn set_content_from_html_normalizes_the_content() {
+    let mut model = cm("|");
+    model
+        .set_content_from_html(&Utf16String::from("<li>a</li><li>b</li>"))
+        .unwrap();
+    assert_eq!(tx(&model), "<ul><li>a</li><li>b|</li></ul>");
+}
+
+#[test]
+fn editing_repairs_an_inconsistent_dom() {
+    let mut model = cm("<p>a|</p>");
+    model
+        .state
+        .dom
+        .append_at_end_of_document(DomNode::new_list_item(vec![
+            DomNode::new_text(Utf16String::from("b")),
+        ]));
+    model.replace_text(Utf16String::from("c"));
+    assert_eq!(tx(&model), "<p>ac|</p><ul><li>b</li></ul>");
+    assert_eq!(model.state.dom.validate(), vec![]);
+}
+
+#[test]
+fn text_anchor_follows_text_into_new_paragraphs() {
+    let mut dom = dom(&[tn("abc"), DomNode::new_paragraph(vec![tn("def")])]);
+    // "de|f"
+    let anchor = dom.text_anchor(5);
+    dom.normalize();
+    assert_eq!(dom.to_html(), "<p>abc</p><p>def</p>");
+    assert_eq!(dom.location_of_text_anchor(anchor), 6);
+}
+
+#[test]
+fn text_anchor_stays_in_an_empty_block() {
+    let mut dom = dom(&[
+        DomNode::new_paragraph(vec![tn("a"), tn("b")]),
+        DomNode::new_paragraph(vec![]),
+        DomNode::new_paragraph(vec![tn("c")]),
+    ]);
+    let anchor = dom.text_anchor(3);
+    dom.normalize();
+    assert_eq!(dom.to_html(), "<p>ab</p><p>\u{a0}</p><p>c</p>");
+    assert_eq!(dom.location_of_text_anchor(anchor), 3);
+}
+
+#[test]
+fn repairing_adjacent_text_nodes_keeps_the_cursor() {
+    let mut model = cm("hello |");
+    model.state.dom.append_at_end_of_document(DomNode::new_text(
+        Utf16String::from("world!"),
+    ));
+    model.select(Location::from(8), Location::from(8));
+    model.create_update_replace_all();
+    assert_eq!(model.state.dom.children().len(), 1);
+    assert_eq!(tx(&model), "hello wo|rld!");
+}
+
+#[test]
+fn repairing_a_dom_that_needs_paragraphs_keeps_the_cursor_in_its_text() {
+    let mut model = cm("abc|");
+    model
+        .state
+        .dom
+        .append_at_end_of_document(DomNode::new_paragraph(vec![
+            DomNode::new_text(Utf16String::from("def")),
+        ]));
+    // "de|f", which moves one place right when "abc" gets its paragraph
+    model.select(Location::from(5), Location::from(5));
+    model.create_update_replace_all();
+    assert_eq!(tx(&model), "<p>abc</p><p>de|f</p>");
+}
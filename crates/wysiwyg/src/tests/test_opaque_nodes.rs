@@ -0,0 +1,187 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dom::nodes::DomNode;
+use crate::dom::Dom;
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::ToHtml;
+
+#[test]
+fn unknown_elements_are_kept() {
+    let model = cm("a<s>2</s>b|");
+    assert_eq!(tx(&model), "a<s contenteditable=\"false\">2</s>b|");
+}
+
+#[test]
+fn unknown_elements_round_trip_through_message_html() {
+    let model =
+        cm("<span data-mx-spoiler=\"reason\">hidden <b>bold</b></span> text|");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<span data-mx-spoiler=\"reason\">hidden <b>bold</b></span> text"
+    );
+}
+
+#[test]
+fn nested_elements_are_kept_whole() {
+    let model = cm("<details><summary>a</summary>b</details>|");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<details><summary>a</summary>b</details>"
+    );
+    assert_eq!(model.state.dom.text_len(), 1);
+}
+
+#[test]
+fn images_have_no_closing_tag() {
+    let model = cm("<img alt=\"cat\" width=\"10\">|");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<img alt=\"cat\" width=\"10\">"
+    );
+}
+
+#[test]
+fn images_that_are_not_mxc_urls_lose_their_src() {
+    let model = cm("<img src=\"https://example.org/cat.png\" alt=\"cat\">|");
+    assert_eq!(model.get_content_as_message_html(), "<img alt=\"cat\">");
+}
+
+#[test]
+fn plain_text_uses_text_content_or_alt() {
+    let model = cm("x<s>2</s> <img src=\"https://a/b.png\" alt=\"cat\">|");
+    assert_eq!(model.get_content_as_plain_text(), "x2 cat");
+}
+
+#[test]
+fn markdown_keeps_the_element_as_html() {
+    let model = cm("x<s>2</s>|");
+    assert_eq!(model.get_content_as_markdown(), "x<s>2</s>");
+}
+
+#[test]
+fn editor_html_can_be_parsed_again() {
+    let model = cm("a<s>2</s>|");
+    let model = cm(&tx(&model));
+    assert_eq!(tx(&model), "a<s contenteditable=\"false\">2</s>|");
+}
+
+#[test]
+fn backspace_deletes_unknown_element() {
+    let mut model = cm("a<s>2</s>|b");
+    model.backspace();
+    assert_eq!(tx(&model), "a|b");
+}
+
+#[test]
+fn delete_deletes_unknown_element() {
+    let mut model = cm("a|<s>2</s>b");
+    model.delete();
+    assert_eq!(tx(&model), "a|b");
+}
+
+#[test]
+fn typing_after_unknown_element_does_not_edit_it() {
+    let mut model = cm("<s>2</s>|");
+    model.replace_text(utf16("x"));
+    assert_eq!(tx(&model), "<s contenteditable=\"false\">2</s>x|");
+}
+
+#[test]
+fn typing_before_unknown_element_does_not_edit_it() {
+    let mut model = cm("|<s>2</s>");
+    model.replace_text(utf16("x"));
+    assert_eq!(tx(&model), "x|<s contenteditable=\"false\">2</s>");
+}
+
+#[test]
+fn selecting_over_unknown_element_deletes_it() {
+    let mut model = cm("{a<s>2</s>b}|c");
+    model.replace_text(utf16("x"));
+    assert_eq!(tx(&model), "x|c");
+}
+
+#[test]
+fn elements_matrix_does_not_allow_are_removed_keeping_their_contents() {
+    let model = cm("a<kbd>2</kbd>b|");
+    assert_eq!(tx(&model), "a2b|");
+}
+
+#[test]
+fn scripts_styles_and_iframes_are_dropped_with_their_contents() {
+    let model = cm("a<script>alert(1)</script><style>b{}</style>\
+        <iframe src=\"https://example.org\">x</iframe>b|");
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn event_handlers_and_styles_are_dropped() {
+    let model = cm("<span data-mx-spoiler=\"\" onclick=\"alert(1)\" \
+        style=\"color: red\">a</span>|");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<span data-mx-spoiler=\"\">a</span>"
+    );
+}
+
+#[test]
+fn contents_of_unknown_elements_are_sanitised() {
+    let model = cm("<details><summary>s</summary>\
+        <img src=\"x\" onerror=\"alert(1)\"><script>alert(2)</script>\
+        <kbd>k</kbd><a href=\"javascript:alert(3)\">j</a>\
+        <a href=\"https://matrix.org\">m</a></details>|");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<details><summary>s</summary><img>k<a>j</a>\
+        <a href=\"https://matrix.org\">m</a></details>"
+    );
+    assert_eq!(model.get_content_as_plain_text(), "skjm");
+}
+
+#[test]
+fn unknown_elements_built_by_hand_are_sanitised_when_written() {
+    let dom: Dom<_> = Dom::new(vec![
+        DomNode::new_opaque(
+            utf16("span"),
+            vec![(utf16("onclick"), utf16("alert(1)"))],
+            utf16("a"),
+            utf16("a"),
+        ),
+        DomNode::new_opaque(
+            utf16("script"),
+            Vec::new(),
+            utf16("alert(1)"),
+            utf16("<b>"),
+        ),
+    ]);
+    assert_eq!(dom.to_message_html().to_string(), "<span>a</span>&lt;b&gt;");
+}
+
+#[test]
+fn unknown_block_elements_are_blocks() {
+    let model = cm("<p>a</p><h1>x</h1><p>b|</p>");
+    assert_eq!(
+        tx(&model),
+        "<p>a</p><h1 contenteditable=\"false\">x</h1><p>b|</p>"
+    );
+    assert_eq!(model.state.dom.text_len(), 5);
+}
+
+#[test]
+fn typing_next_to_unknown_block_element_goes_into_a_paragraph() {
+    let mut model = cm("|<h1>x</h1>");
+    model.replace_text(utf16("y"));
+    assert_eq!(tx(&model), "<p>y|</p><h1 contenteditable=\"false\">x</h1>");
+}
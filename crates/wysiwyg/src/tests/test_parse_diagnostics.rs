@@ -0,0 +1,175 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    dom::Dom, parse_with_diagnostics, ParseWarning, ParseWarningKind, ToHtml,
+};
+use widestring::Utf16String;
+
+fn warnings(html: &str) -> Vec<ParseWarning> {
+    let (_, warnings): (Dom<Utf16String>, _) =
+        parse_with_diagnostics(html).unwrap();
+    warnings
+}
+
+fn warning(kind: ParseWarningKind, name: &str, line: u64) -> ParseWarning {
+    ParseWarning::new(kind, Some(name), Some(line))
+}
+
+#[test]
+fn supported_html_has_no_warnings() {
+    assert_eq!(
+        warnings("<p>a <b>b</b> <a href=\"https://x.org\">c</a></p>"),
+        vec![]
+    );
+}
+
+#[test]
+fn parsed_dom_is_returned_alongside_warnings() {
+    let (dom, _): (Dom<Utf16String>, _) =
+        parse_with_diagnostics("<b>a</b><s>2</s>").unwrap();
+    assert_eq!(
+        dom.to_html().to_string(),
+        "<b>a</b><s contenteditable=\"false\">2</s>"
+    );
+}
+
+#[test]
+fn unknown_elements_are_reported_with_their_line() {
+    assert_eq!(
+        warnings("a\n<s>2</s>\n<span>b</span>"),
+        vec![
+            warning(ParseWarningKind::UnknownElement, "s", 2),
+            warning(ParseWarningKind::UnknownElement, "span", 3),
+        ]
+    );
+}
+
+#[test]
+fn elements_matrix_does_not_allow_are_reported() {
+    assert_eq!(
+        warnings("a<kbd>2</kbd>\n<script>x</script>"),
+        vec![
+            warning(ParseWarningKind::ElementUnwrapped, "kbd", 1),
+            warning(ParseWarningKind::NodeDropped, "script", 2),
+        ]
+    );
+}
+
+#[test]
+fn attributes_matrix_does_not_allow_are_reported() {
+    assert_eq!(
+        warnings("<span data-mx-spoiler=\"\" onclick=\"x\">a</span>"),
+        vec![
+            warning(ParseWarningKind::UnknownElement, "span", 1),
+            warning(ParseWarningKind::AttributeDropped, "onclick", 1),
+        ]
+    );
+}
+
+#[test]
+fn dropped_attributes_are_reported() {
+    assert_eq!(
+        warnings("<ol start=\"3\"><li class=\"x\">a</li></ol>"),
+        vec![
+            warning(ParseWarningKind::AttributeDropped, "start", 1),
+            warning(ParseWarningKind::AttributeDropped, "class", 1),
+        ]
+    );
+}
+
+#[test]
+fn dropped_comments_are_reported_with_their_line() {
+    assert_eq!(
+        warnings("a<!-- c -->\n<p>b<!-- d --></p>"),
+        vec![
+            warning(ParseWarningKind::NodeDropped, "#comment", 1),
+            warning(ParseWarningKind::NodeDropped, "#comment", 2),
+        ]
+    );
+}
+
+#[test]
+fn link_attributes_are_not_reported() {
+    assert_eq!(
+        warnings("<a href=\"https://x.org\" title=\"t\">a</a>"),
+        vec![]
+    );
+}
+
+#[test]
+fn mention_attributes_generated_by_the_editor_are_not_reported() {
+    assert_eq!(
+        warnings(
+            "<a href=\"https://matrix.to/#/@test:example.org\" \
+            contenteditable=\"false\" data-mention-type=\"user\">test</a>"
+        ),
+        vec![warning(
+            ParseWarningKind::AttributeDropped,
+            "data-mention-type",
+            1
+        )]
+    );
+}
+
+#[test]
+fn removed_nbsp_is_reported() {
+    assert_eq!(
+        warnings("<p>a</p>\n<p>&nbsp;</p>"),
+        vec![ParseWarning::new(
+            ParseWarningKind::NbspRemoved,
+            None,
+            Some(2)
+        )]
+    );
+}
+
+#[test]
+fn code_blocks_are_reported() {
+    assert_eq!(
+        warnings("<pre><code>a\nb</code></pre>"),
+        vec![warning(ParseWarningKind::CodeBlockRestructured, "pre", 1)]
+    );
+}
+
+#[test]
+fn removed_reply_fallback_is_reported() {
+    assert_eq!(
+        warnings("<mx-reply><blockquote>\nquote\n</blockquote></mx-reply>a"),
+        vec![warning(
+            ParseWarningKind::ReplyFallbackRemoved,
+            "mx-reply",
+            1
+        )]
+    );
+}
+
+#[test]
+fn lines_after_a_reply_fallback_refer_to_the_original_html() {
+    assert_eq!(
+        warnings("<mx-reply>\nquote\n</mx-reply>a\n<s>2</s>"),
+        vec![
+            warning(ParseWarningKind::ReplyFallbackRemoved, "mx-reply", 1),
+            warning(ParseWarningKind::UnknownElement, "s", 4),
+        ]
+    );
+}
+
+#[test]
+fn warnings_can_be_displayed() {
+    assert_eq!(
+        warning(ParseWarningKind::AttributeDropped, "class", 2).to_string(),
+        "attribute dropped: class (line 2)"
+    );
+}
@@ -0,0 +1,149 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{
+    parse_with_reply_fallback, reply_fallback_body, reply_fallback_html,
+    ComposerModel, MessageContentOptions, MessageType, ReplyParent, ToHtml,
+};
+
+fn parent(
+    body: &str,
+    formatted_body: Option<&str>,
+) -> ReplyParent<Utf16String> {
+    ReplyParent {
+        event_id: utf16("$parent"),
+        room_id: utf16("!room:example.org"),
+        sender: utf16("@alice:example.org"),
+        msgtype: MessageType::Text,
+        body: utf16(body),
+        formatted_body: formatted_body.map(utf16),
+    }
+}
+
+#[test]
+fn set_content_from_html_strips_reply_fallback() {
+    let mut model = ComposerModel::new();
+    model
+        .set_content_from_html(&utf16(
+            "<mx-reply><blockquote><a href=\"https://matrix.to/#/!r:e.org/$e\">\
+             In reply to</a> <a href=\"https://matrix.to/#/@a:e.org\">@a:e.org</a>\
+             <br />quoted</blockquote></mx-reply>my <em>reply</em>",
+        ))
+        .unwrap();
+    assert_eq!(tx(&model), "my <em>reply|</em>");
+}
+
+#[test]
+fn parse_with_reply_fallback_returns_the_fallback() {
+    let (dom, fallback) = parse_with_reply_fallback::<Utf16String>(
+        "<mx-reply><blockquote>quoted</blockquote></mx-reply>reply",
+    )
+    .unwrap();
+    assert_eq!(dom.to_html(), "reply");
+    assert_eq!(
+        fallback.unwrap(),
+        "<mx-reply><blockquote>quoted</blockquote></mx-reply>"
+    );
+}
+
+#[test]
+fn parse_with_reply_fallback_without_fallback() {
+    let (dom, fallback) =
+        parse_with_reply_fallback::<Utf16String>("<b>reply</b>").unwrap();
+    assert_eq!(dom.to_html(), "<b>reply</b>");
+    assert_eq!(fallback, None);
+}
+
+#[test]
+fn fallback_body_quotes_each_line() {
+    assert_eq!(
+        reply_fallback_body(&parent("hello\nworld", None)),
+        "> <@alice:example.org> hello\n> world\n\n"
+    );
+}
+
+#[test]
+fn fallback_body_of_emote_has_a_star() {
+    let mut parent = parent("waves", None);
+    parent.msgtype = MessageType::Emote;
+    assert_eq!(
+        reply_fallback_body(&parent),
+        "> * <@alice:example.org> waves\n\n"
+    );
+}
+
+#[test]
+fn fallback_body_does_not_nest_fallbacks() {
+    assert_eq!(
+        reply_fallback_body(&parent("> <@bob:example.org> hi\n\nhello", None)),
+        "> <@alice:example.org> hello\n\n"
+    );
+}
+
+#[test]
+fn fallback_html_escapes_plain_text_body() {
+    assert_eq!(
+        reply_fallback_html(&parent("a < b\nc", None)),
+        "<mx-reply><blockquote>\
+         <a href=\"https://matrix.to/#/!room:example.org/$parent\">In reply to</a> \
+         <a href=\"https://matrix.to/#/@alice:example.org\">@alice:example.org</a>\
+         <br />a &lt; b<br />c\
+         </blockquote></mx-reply>"
+    );
+}
+
+#[test]
+fn fallback_html_uses_formatted_body_without_its_fallback() {
+    let parent = parent(
+        "hello",
+        Some("<mx-reply><blockquote>older</blockquote></mx-reply><b>hello</b>"),
+    );
+    assert!(reply_fallback_html(&parent)
+        .to_string()
+        .ends_with("<br /><b>hello</b></blockquote></mx-reply>"));
+}
+
+#[test]
+fn reply_content_has_fallbacks_and_mentions_the_parent_sender() {
+    let model = cm("my reply|");
+    let content = model.to_reply_message_content(
+        MessageContentOptions::default(),
+        &parent("hello", None),
+    );
+    assert_eq!(content.body, "> <@alice:example.org> hello\n\nmy reply");
+    let formatted_body = content.formatted_body.unwrap().to_string();
+    assert!(formatted_body.starts_with("<mx-reply><blockquote>"));
+    assert!(formatted_body.ends_with("</mx-reply>my reply"));
+    assert_eq!(content.format.unwrap(), "org.matrix.custom.html");
+    assert_eq!(content.mentions.user_ids, vec![utf16("@alice:example.org")]);
+    assert_eq!(content.in_reply_to.unwrap(), "$parent");
+}
+
+#[test]
+fn reply_content_json_has_relation() {
+    let model = cm("reply|");
+    let json = model
+        .to_reply_message_content(
+            MessageContentOptions::default(),
+            &parent("hello", None),
+        )
+        .to_json();
+    assert!(json.ends_with(
+        r#""m.mentions":{"user_ids":["@alice:example.org"]},"m.relates_to":{"m.in_reply_to":{"event_id":"$parent"}}}"#
+    ));
+}
@@ -17,7 +17,7 @@ use widestring::Utf16String;
 use crate::{
     dom::DomCreationError,
     tests::{testutils_composer_model::tx, testutils_conversion::utf16},
-    HtmlParseError,
+    ComposerModel, HtmlParseError,
 };
 
 use super::testutils_composer_model::cm;
@@ -74,3 +74,77 @@ fn set_contents_with_line_break_in_code_block() {
     let model = cm("<pre>\n<code>|Test</code></pre>");
     assert_eq!(tx(&model), "<pre><code>|Test</code></pre>");
 }
+
+#[test]
+fn set_content_from_html_drops_comments() {
+    let mut model = cm("|");
+    model.set_content_from_html(&utf16("a<!-- c -->b")).unwrap();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn set_content_from_html_processing_instruction_is_an_error() {
+    let mut model = cm("|");
+    let error = model
+        .set_content_from_html(&utf16("<?xml x?>hi"))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        DomCreationError::HtmlParseError(HtmlParseError::new(vec![
+            "Bad character".into()
+        ]))
+    );
+}
+
+#[test]
+fn set_content_from_html_misnested_formatting_is_an_error() {
+    let mut model = cm("|");
+    let error = model
+        .set_content_from_html(&utf16("<b><p>x</b>y</p>"))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        DomCreationError::HtmlParseError(HtmlParseError::new(vec![
+            "Formatting element not current node".into()
+        ]))
+    );
+}
+
+#[test]
+fn set_content_from_html_text_directly_in_table_is_an_error() {
+    let mut model = cm("|");
+    let error = model
+        .set_content_from_html(&utf16("<table>x<tr><td>a</td></tr></table>"))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        DomCreationError::HtmlParseError(HtmlParseError::new(vec![
+            "Non-space table text".into()
+        ]))
+    );
+}
+
+#[test]
+fn set_content_from_html_drops_templates() {
+    let mut model = cm("|");
+    model
+        .set_content_from_html(&utf16("a<template>x</template>"))
+        .unwrap();
+    assert_eq!(tx(&model), "a|");
+}
+
+#[test]
+fn try_from_html_does_not_panic_on_unusual_html() {
+    for html in [
+        "a<!-- c -->b",
+        "<b><p>x</b>y</p>",
+        "<table>x<tr><td>a</td></tr></table>",
+        "<?xml x?>hi",
+        "<template>x</template>",
+    ] {
+        let mut model = cm("|");
+        let expected = model.set_content_from_html(&utf16(html)).is_ok();
+        let result = ComposerModel::<Utf16String>::try_from_html(html, 0, 0);
+        assert_eq!(result.is_ok(), expected, "{html}");
+    }
+}
@@ -0,0 +1,287 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::tests::testutils_composer_model::cm;
+use crate::{
+    ComposerModel, MenuAction, ParsedCommand, PatternKey, SlashCommand,
+    SuggestionPattern,
+};
+
+#[test]
+fn commands_are_filtered_by_slash_suggestion() {
+    let mut model = model_with_commands("|");
+    let update = model.replace_text("/in".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    assert_eq!(
+        names(model.commands_for_suggestion(&suggestion)),
+        ["invite"]
+    );
+}
+
+#[test]
+fn all_commands_match_an_empty_slash_suggestion() {
+    let mut model = model_with_commands("|");
+    let update = model.replace_text("/".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    assert_eq!(
+        names(model.commands_for_suggestion(&suggestion)),
+        ["invite", "me", "spoiler", "topic"]
+    );
+}
+
+#[test]
+fn no_commands_match_other_suggestions() {
+    let model = model_with_commands("|");
+    let suggestion = SuggestionPattern {
+        key: PatternKey::At,
+        text: "in".into(),
+        start: 0,
+        end: 3,
+    };
+    assert!(model.commands_for_suggestion(&suggestion).is_empty());
+}
+
+#[test]
+fn commands_can_be_replaced() {
+    let mut model = model_with_commands("|");
+    model.set_slash_commands(vec![command("shrug", &[], true)]);
+    assert_eq!(names(model.slash_commands().to_vec()), ["shrug"]);
+}
+
+#[test]
+fn adding_a_command_replaces_one_with_the_same_name() {
+    let mut model = cm("|");
+    model.add_slash_command(command("me", &[], false));
+    model.add_slash_command(command("shrug", &[], true));
+    model.add_slash_command(command("me", &[], true));
+    let commands = model.slash_commands().to_vec();
+    assert_eq!(names(commands.clone()), ["shrug", "me"]);
+    assert!(commands[1].takes_remainder);
+}
+
+#[test]
+fn parse_command_without_arguments() {
+    let model = model_with_commands("/topic|");
+    assert_eq!(model.parse_command(), Some(parsed("topic", &[], None)));
+}
+
+#[test]
+fn parse_command_with_arguments() {
+    let model = model_with_commands("/invite @alice:example.org|");
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed("invite", &["@alice:example.org"], None))
+    );
+}
+
+#[test]
+fn parse_command_ignores_extra_whitespace_between_arguments() {
+    let model = model_with_commands("/invite   @alice:example.org  |");
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed("invite", &["@alice:example.org"], None))
+    );
+}
+
+#[test]
+fn parse_command_reads_a_mention_as_its_url() {
+    let model = model_with_commands(
+        "/invite <a href=\"https://matrix.to/#/@test:example.org\">\
+        Alice</a>|",
+    );
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed(
+            "invite",
+            &["https://matrix.to/#/@test:example.org"],
+            None
+        ))
+    );
+}
+
+#[test]
+fn parse_command_reads_arguments_after_a_mention() {
+    let mut model = model_with_commands("|");
+    model.set_slash_commands(vec![command("ban", &["user", "reason"], true)]);
+    model
+        .set_content_from_html(
+            &"/ban <a href=\"https://matrix.to/#/@test:example.org\">Bob</a> \
+            spam for <em>days</em>"
+                .into(),
+        )
+        .unwrap();
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed(
+            "ban",
+            &["https://matrix.to/#/@test:example.org", "spam"],
+            Some("for <em>days</em>")
+        ))
+    );
+}
+
+#[test]
+fn parse_command_reads_an_at_room_mention_as_its_text() {
+    let mut model = model_with_commands("|");
+    model.set_slash_commands(vec![command("notify", &["who"], false)]);
+    model
+        .set_content_from_html(&"/notify @room".into())
+        .unwrap();
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed("notify", &["@room"], None))
+    );
+}
+
+#[test]
+fn parse_command_leaves_out_missing_arguments() {
+    let model = model_with_commands("/invite |");
+    assert_eq!(model.parse_command(), Some(parsed("invite", &[], None)));
+}
+
+#[test]
+fn parse_command_with_plain_remainder() {
+    let model = model_with_commands("/me waves|");
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed("me", &[], Some("waves")))
+    );
+}
+
+#[test]
+fn parse_command_with_rich_remainder() {
+    let model =
+        model_with_commands("/me waves <strong>enthusiastically</strong>|");
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed(
+            "me",
+            &[],
+            Some("waves <strong>enthusiastically</strong>")
+        ))
+    );
+}
+
+#[test]
+fn parse_command_with_arguments_and_remainder() {
+    let mut model = model_with_commands("|");
+    model.set_slash_commands(vec![command("spoiler", &["reason"], true)]);
+    model
+        .set_content_from_html(
+            &"/spoiler plot the <em>butler</em> did it".into(),
+        )
+        .unwrap();
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed(
+            "spoiler",
+            &["plot"],
+            Some("the <em>butler</em> did it")
+        ))
+    );
+}
+
+#[test]
+fn parse_command_with_empty_remainder() {
+    let model = model_with_commands("/me |");
+    assert_eq!(model.parse_command(), Some(parsed("me", &[], Some(""))));
+}
+
+#[test]
+fn parse_command_keeps_following_lines_in_remainder() {
+    let model = model_with_commands("<p>/me waves</p><p>and smiles|</p>");
+    assert_eq!(
+        model.parse_command(),
+        Some(parsed("me", &[], Some("<p>waves</p><p>and smiles</p>")))
+    );
+}
+
+#[test]
+fn parse_command_does_not_change_the_content() {
+    let model = model_with_commands("/me waves|");
+    model.parse_command();
+    assert_eq!(model.get_content_as_html(), "/me waves");
+}
+
+#[test]
+fn parse_unknown_command() {
+    let model = model_with_commands("/shrug|");
+    assert_eq!(model.parse_command(), None);
+}
+
+#[test]
+fn parse_command_needs_the_whole_name() {
+    let model = model_with_commands("/mee|");
+    assert_eq!(model.parse_command(), None);
+}
+
+#[test]
+fn parse_command_not_at_start() {
+    let model = model_with_commands("Hi /me waves|");
+    assert_eq!(model.parse_command(), None);
+}
+
+#[test]
+fn parse_command_in_code() {
+    let model = model_with_commands("<pre><code>/me waves|</code></pre>");
+    assert_eq!(model.parse_command(), None);
+}
+
+#[test]
+fn parse_command_in_empty_model() {
+    let model = model_with_commands("|");
+    assert_eq!(model.parse_command(), None);
+}
+
+fn model_with_commands(html: &str) -> ComposerModel<Utf16String> {
+    let mut model = cm(html);
+    model.set_slash_commands(vec![
+        command("invite", &["user_id"], false),
+        command("me", &[], true),
+        command("spoiler", &[], true),
+        command("topic", &[], false),
+    ]);
+    model
+}
+
+fn command(name: &str, args: &[&str], takes_remainder: bool) -> SlashCommand {
+    SlashCommand {
+        name: name.into(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        takes_remainder,
+        description: format!("The {name} command"),
+    }
+}
+
+fn parsed(
+    name: &str,
+    args: &[&str],
+    remainder_html: Option<&str>,
+) -> ParsedCommand {
+    ParsedCommand {
+        name: name.into(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        remainder_html: remainder_html.map(String::from),
+    }
+}
+
+fn names(commands: Vec<SlashCommand>) -> Vec<String> {
+    commands.into_iter().map(|c| c.name).collect()
+}
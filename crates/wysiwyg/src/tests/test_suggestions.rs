@@ -67,3 +67,83 @@ fn test_set_link_suggestion_with_attributes() {
         "<a contenteditable=\"false\" data-mention-type=\"user\" href=\"https://matrix.to/#/@alice:matrix.org\">Alice</a>&nbsp;|",
     );
 }
+
+#[test]
+fn test_replace_text_suggestion_for_emoji_shortcode() {
+    let mut model = cm("Hi |");
+    let update = model.replace_text(":smi".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    model.replace_text_suggestion("😄".into(), suggestion);
+    assert_eq!(tx(&model), "Hi 😄&nbsp;|");
+}
+
+#[test]
+fn test_insert_custom_emoji_suggestion() {
+    let mut model = cm("Hi |");
+    let update = model.replace_text(":par".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    model.insert_custom_emoji_suggestion(
+        "mxc://example.org/party".into(),
+        "party".into(),
+        suggestion,
+    );
+    assert_eq!(
+        tx(&model),
+        "Hi <img data-mx-emoticon=\"\" title=\":party:\" height=\"32\" src=\"mxc://example.org/party\" alt=\":party:\">&nbsp;|",
+    );
+}
+
+#[test]
+fn test_insert_custom_emoji_suggestion_ignores_non_mxc_urls() {
+    let mut model = cm("|");
+    let update = model.replace_text(":par".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    model.insert_custom_emoji_suggestion(
+        "https://example.org/party.png".into(),
+        "party".into(),
+        suggestion,
+    );
+    assert_eq!(tx(&model), ":par|");
+}
+
+#[test]
+fn test_insert_custom_emoji_suggestion_can_be_undone() {
+    let mut model = cm("|");
+    let update = model.replace_text(":par".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    model.insert_custom_emoji_suggestion(
+        "mxc://example.org/party".into(),
+        "party".into(),
+        suggestion,
+    );
+    model.undo();
+    assert_eq!(tx(&model), ":par|");
+}
+
+#[test]
+fn test_set_link_suggestion_spanning_words_and_nodes() {
+    let mut model = cm("<em>@John</em>|");
+    model.set_mention_suggestion_max_words(2);
+    let update = model.replace_text(" Sm".into());
+    let MenuAction::Suggestion(suggestion) = update.menu_action else {
+        panic!("No suggestion pattern found")
+    };
+    model.set_link_suggestion(
+        "https://matrix.to/#/@john:matrix.org".into(),
+        "John Smith".into(),
+        suggestion,
+        vec![],
+    );
+    assert_eq!(
+        tx(&model),
+        "<a href=\"https://matrix.to/#/@john:matrix.org\">John Smith</a>&nbsp;|",
+    );
+}
@@ -0,0 +1,538 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use widestring::Utf16String;
+
+use crate::dom::parser::parse;
+use crate::tests::testutils_composer_model::{cm, tx};
+use crate::tests::testutils_conversion::utf16;
+use crate::{ActionState, BlockType, ComposerAction, ComposerModel, ToHtml};
+
+fn action_state(
+    model: &ComposerModel<Utf16String>,
+    action: ComposerAction,
+) -> ActionState {
+    model.action_states().get(&action).unwrap().clone()
+}
+
+#[test]
+fn tables_are_parsed_into_rows_and_cells() {
+    let model = cm("<table><tr><td>a</td><td>b|</td></tr></table>");
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>b|</td></tr></tbody></table>"
+    );
+    assert_eq!(model.state.dom.text_len(), 3);
+}
+
+#[test]
+fn header_row_is_written_in_thead() {
+    let model = cm("<table><thead><tr><th>a</th></tr></thead>\
+        <tbody><tr><td>b|</td></tr></tbody></table>");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<table><thead><tr><th>a</th></tr></thead>\
+        <tbody><tr><td>b</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn short_rows_are_padded_when_parsing() {
+    let dom = parse::<Utf16String>(
+        "<table><tr><th>a</th><th>b</th></tr><tr><td>c</td></tr></table>",
+    )
+    .unwrap();
+    assert_eq!(
+        dom.to_html(),
+        "<table><thead><tr><th>a</th><th>b</th></tr></thead>\
+        <tbody><tr><td>c</td><td></td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn elements_between_rows_are_dropped_when_parsing() {
+    let dom = parse::<Utf16String>(
+        "<table><caption>x</caption>\n  <tr><td>a</td></tr>\n</table>",
+    )
+    .unwrap();
+    assert_eq!(
+        dom.to_html(),
+        "<table><tbody><tr><td>a</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn tables_without_cells_are_dropped_when_parsing() {
+    let dom = parse::<Utf16String>("a<table></table>").unwrap();
+    assert_eq!(dom.to_html(), "a");
+}
+
+#[test]
+fn cells_keep_their_formatting() {
+    let model = cm("<table><tr><td><b>a</b> <a href=\"https://x.org\">b</a>|\
+        </td></tr></table>");
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<table><tbody><tr><td><b>a</b> <a href=\"https://x.org\">b</a>\
+        </td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn tables_are_written_as_gfm_pipe_tables() {
+    let model = cm("<table><tr><th>a</th><th>b</th></tr>\
+        <tr><td><em>c</em></td><td>d|</td></tr></table>");
+    assert_eq!(
+        model.get_content_as_markdown(),
+        utf16("| a | b |\n| --- | --- |\n| *c* | d |\n")
+    );
+}
+
+#[test]
+fn pipes_in_cells_are_escaped_in_markdown() {
+    let mut model = ComposerModel::new();
+    model
+        .set_content_from_html(&utf16("<table><tr><td>a|b</td></tr></table>"))
+        .unwrap();
+    assert_eq!(
+        model.get_content_as_markdown(),
+        utf16("| a\\|b |\n| --- |\n")
+    );
+}
+
+#[test]
+fn paragraph_after_table_is_separated_by_a_blank_line_in_markdown() {
+    let model = cm("<table><tr><td>a</td></tr></table><p>b|</p>");
+    assert_eq!(
+        model.get_content_as_markdown(),
+        utf16("| a |\n| --- |\n\nb")
+    );
+}
+
+#[test]
+fn gfm_pipe_tables_are_parsed_from_markdown() {
+    let mut model = cm("|");
+    model
+        .set_content_from_markdown(&utf16(
+            "| a | b |\n| --- | --- |\n| c | *d* |",
+        ))
+        .unwrap();
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<table><thead><tr><th>a</th><th>b</th></tr></thead>\
+        <tbody><tr><td>c</td><td><em>d</em></td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn markdown_tables_round_trip() {
+    let markdown = "| a | b |\n| --- | --- |\n| c | d |\n";
+    let mut model = cm("|");
+    model.set_content_from_markdown(&utf16(markdown)).unwrap();
+    assert_eq!(model.get_content_as_markdown(), utf16(markdown));
+}
+
+#[test]
+fn plain_text_puts_rows_on_lines_and_separates_cells() {
+    let model = cm("<p>x</p><table><tr><td>a</td><td>b</td></tr>\
+        <tr><td>c</td><td>d|</td></tr></table><p>y</p>");
+    assert_eq!(model.get_content_as_plain_text(), "x\na | b\nc | d\ny\n");
+}
+
+#[test]
+fn inserting_a_table_in_an_empty_model() {
+    let mut model = cm("|");
+    model.insert_table(2, 2);
+    assert_eq!(
+        tx(&model),
+        "<table><thead><tr><th>|</th><th></th></tr></thead>\
+        <tbody><tr><td></td><td></td></tr></tbody></table><p>&nbsp;</p>"
+    );
+}
+
+#[test]
+fn inserting_a_table_splits_the_text_at_the_cursor() {
+    let mut model = cm("ab|c");
+    model.insert_table(1, 2);
+    assert_eq!(
+        tx(&model),
+        "<p>ab</p><table><tbody><tr><td>|</td><td></td></tr></tbody>\
+        </table><p>c</p>"
+    );
+}
+
+#[test]
+fn inserting_a_table_replaces_the_selection() {
+    let mut model = cm("a{bc}|d");
+    model.insert_table(1, 1);
+    assert_eq!(
+        tx(&model),
+        "<p>a</p><table><tbody><tr><td>|</td></tr></tbody></table><p>d</p>"
+    );
+}
+
+#[test]
+fn inserting_a_table_can_be_undone() {
+    let mut model = cm("ab|");
+    model.insert_table(2, 2);
+    model.undo();
+    assert_eq!(tx(&model), "ab|");
+}
+
+#[test]
+fn tables_can_not_be_inserted_in_lists_quotes_or_tables() {
+    for html in [
+        "<ul><li>a|</li></ul>",
+        "<blockquote><p>a|</p></blockquote>",
+        "<table><tr><td>a|</td></tr></table>",
+    ] {
+        let mut model = cm(html);
+        model.insert_table(2, 2);
+        assert_eq!(tx(&model), tx(&cm(html)));
+    }
+}
+
+#[test]
+fn typing_in_cells() {
+    let mut model = cm("|");
+    model.insert_table(1, 2);
+    model.replace_text(utf16("a"));
+    model.next_table_cell();
+    model.replace_text(utf16("b"));
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>b|</td></tr></tbody></table>\
+        <p>&nbsp;</p>"
+    );
+}
+
+#[test]
+fn tab_selects_the_contents_of_the_next_cell() {
+    let mut model = cm("<table><tr><td>a|</td><td>bc</td></tr></table>");
+    model.next_table_cell();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>{bc}|</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn tab_moves_to_the_next_row() {
+    let mut model = cm("<table><tr><td>a</td><td>b|</td></tr>\
+        <tr><td>c</td><td>d</td></tr></table>");
+    model.next_table_cell();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>b</td></tr>\
+        <tr><td>{c}|</td><td>d</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn tab_in_the_last_cell_adds_a_row() {
+    let mut model = cm("<table><tr><td>a</td><td>b|</td></tr></table>");
+    model.next_table_cell();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>b</td></tr>\
+        <tr><td>|</td><td></td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn shift_tab_moves_to_the_previous_cell() {
+    let mut model = cm("<table><tr><td>a</td><td>b</td></tr>\
+        <tr><td>|c</td><td>d</td></tr></table>");
+    model.previous_table_cell();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>{b}|</td></tr>\
+        <tr><td>c</td><td>d</td></tr></tbody></table>"
+    );
+    model.previous_table_cell();
+    model.previous_table_cell();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>{a}|</td><td>b</td></tr>\
+        <tr><td>c</td><td>d</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn tab_outside_of_tables_does_nothing() {
+    let mut model = cm("a|");
+    model.next_table_cell();
+    model.previous_table_cell();
+    assert_eq!(tx(&model), "a|");
+}
+
+#[test]
+fn adding_a_row_below_the_cursor() {
+    let mut model =
+        cm("<table><tr><th>a|</th></tr><tr><td>b</td></tr></table>");
+    model.add_table_row();
+    assert_eq!(
+        tx(&model),
+        "<table><thead><tr><th>a|</th></tr></thead>\
+        <tbody><tr><td></td></tr><tr><td>b</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn removing_a_row_moves_to_the_next_one() {
+    let mut model = cm("<table><tr><td>a|</td><td>b</td></tr>\
+        <tr><td>c</td><td>d</td></tr></table>");
+    model.remove_table_row();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>|c</td><td>d</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn removing_the_last_row_moves_to_the_previous_one() {
+    let mut model =
+        cm("<table><tr><td>a</td></tr><tr><td>b|</td></tr></table>");
+    model.remove_table_row();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>|a</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn removing_the_only_row_removes_the_table() {
+    let mut model = cm("<p>a</p><table><tr><td>b|</td></tr></table><p>c</p>");
+    model.remove_table_row();
+    assert_eq!(tx(&model), "<p>a</p><p>|c</p>");
+}
+
+#[test]
+fn adding_a_column_after_the_cursor() {
+    let mut model = cm("<table><tr><th>a</th><th>b</th></tr>\
+        <tr><td>c|</td><td>d</td></tr></table>");
+    model.add_table_column();
+    assert_eq!(
+        tx(&model),
+        "<table><thead><tr><th>a</th><th></th><th>b</th></tr></thead>\
+        <tbody><tr><td>c|</td><td></td><td>d</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn removing_a_column() {
+    let mut model = cm("<table><tr><td>a</td><td>b</td></tr>\
+        <tr><td>c</td><td>d|</td></tr></table>");
+    model.remove_table_column();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td></tr><tr><td>|c</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn removing_the_only_column_removes_the_table() {
+    let mut model =
+        cm("<table><tr><td>a|</td></tr><tr><td>b</td></tr></table>");
+    model.remove_table_column();
+    assert_eq!(tx(&model), "|");
+}
+
+#[test]
+fn table_operations_outside_of_tables_do_nothing() {
+    let mut model = cm("a|");
+    model.add_table_row();
+    model.remove_table_row();
+    model.add_table_column();
+    model.remove_table_column();
+    assert_eq!(tx(&model), "a|");
+}
+
+#[test]
+fn backspace_at_the_start_of_a_cell_does_nothing() {
+    let mut model = cm("<table><tr><td>a</td><td>|b</td></tr></table>");
+    model.backspace();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>|b</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn backspace_inside_a_cell_deletes_text() {
+    let mut model = cm("<table><tr><td>a</td><td>bc|</td></tr></table>");
+    model.backspace();
+    model.backspace();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td><td>|</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn delete_at_the_end_of_a_cell_does_nothing() {
+    let mut model = cm("<table><tr><td>a|</td><td>b</td></tr></table>");
+    model.delete();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a|</td><td>b</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn backspace_after_a_table_does_not_merge_into_it() {
+    let mut model = cm("<table><tr><td>a</td></tr></table><p>|b</p>");
+    model.backspace();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a</td></tr></tbody></table><p>|b</p>"
+    );
+}
+
+#[test]
+fn typing_over_a_selection_across_cells_clears_the_selected_contents() {
+    let mut model = cm("<table><tr><td>a{b</td><td>c}|d</td></tr></table>");
+    model.replace_text(utf16("x"));
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>ax|</td><td>d</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn deleting_a_selection_across_rows_clears_the_selected_contents() {
+    let mut model = cm("<table><tr><td>a{b</td><td>c</td></tr>\
+        <tr><td>d</td><td>e}|f</td></tr></table>");
+    model.delete();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a|</td><td></td></tr>\
+        <tr><td></td><td>f</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn deleting_a_selection_into_a_table_keeps_the_blocks_apart() {
+    let mut model =
+        cm("<p>a{b</p><table><tr><td>c</td><td>d}|e</td></tr></table>");
+    model.backspace();
+    assert_eq!(
+        tx(&model),
+        "<p>a|</p><table><tbody><tr><td></td><td>e</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn deleting_a_selection_out_of_a_table_keeps_the_blocks_apart() {
+    let mut model = cm("<table><tr><td>a{b</td></tr></table><p>c}|d</p>");
+    model.delete();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a|</td></tr></tbody></table><p>d</p>"
+    );
+}
+
+#[test]
+fn typing_over_a_whole_table_replaces_it() {
+    let mut model = cm("<table><tr><td>{a</td><td>b}|</td></tr></table>");
+    model.replace_text(utf16("x"));
+    assert_eq!(tx(&model), "x|");
+}
+
+#[test]
+fn deleting_a_whole_table_removes_it() {
+    let mut model = cm("<table><tr><td>{a</td></tr>\
+        <tr><td>b}|</td></tr></table>");
+    model.backspace();
+    assert_eq!(tx(&model), "|");
+}
+
+#[test]
+fn deleting_a_table_and_the_text_before_it() {
+    let mut model = cm("<p>a{b</p><table><tr><td>c}|</td></tr></table>");
+    model.delete();
+    assert_eq!(tx(&model), "<p>a|</p>");
+}
+
+#[test]
+fn deleting_a_selection_around_a_table_removes_it() {
+    let mut model =
+        cm("<p>a{b</p><table><tr><td>c</td></tr></table><p>d}|e</p>");
+    model.delete();
+    assert_eq!(tx(&model), "<p>a|e</p>");
+}
+
+#[test]
+fn enter_in_a_cell_does_nothing() {
+    let mut model = cm("<table><tr><td>a|b</td></tr></table>");
+    model.enter();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a|b</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn formatting_a_selection_across_cells() {
+    let mut model = cm("<table><tr><td>a{b</td><td>c}|d</td></tr></table>");
+    model.bold();
+    assert_eq!(
+        tx(&model),
+        "<table><tbody><tr><td>a<strong>{b</strong></td>\
+        <td><strong>c}|</strong>d</td></tr></tbody></table>"
+    );
+}
+
+#[test]
+fn block_actions_are_disabled_in_tables() {
+    let model = cm("<table><tr><td>a|</td></tr></table>");
+    for action in [
+        ComposerAction::OrderedList,
+        ComposerAction::UnorderedList,
+        ComposerAction::Quote,
+        ComposerAction::CodeBlock,
+    ] {
+        assert_eq!(action_state(&model, action), ActionState::Disabled);
+    }
+    assert_eq!(
+        action_state(&model, ComposerAction::Bold),
+        ActionState::Enabled
+    );
+}
+
+#[test]
+fn context_state_reports_table_cells() {
+    let model = cm("<table><tr><td>a|</td></tr></table>");
+    assert_eq!(model.get_context_state().block_type, BlockType::TableCell);
+}
+
+#[test]
+fn paragraph_before_table_is_separated_by_a_blank_line_in_markdown() {
+    let model = cm("<p>a</p><table><tr><td>b|</td></tr></table>");
+    assert_eq!(
+        model.get_content_as_markdown(),
+        utf16("a\n\n| b |\n| --- |\n")
+    );
+}
+
+#[test]
+fn paragraph_before_table_round_trips_through_markdown() {
+    let mut model = cm("<p>a</p><table><tr><td>b|</td></tr></table>");
+    let markdown = model.get_content_as_markdown();
+    model.set_content_from_markdown(&markdown).unwrap();
+    assert_eq!(
+        model.get_content_as_message_html(),
+        "<p>a</p><table><thead><tr><th>b</th></tr></thead></table>"
+    );
+}
@@ -33,6 +33,22 @@ fn undoing_action_restores_previous_state() {
     assert_eq!(prev.dom.children().len(), model.state.dom.children().len());
 }
 
+#[test]
+fn undoing_does_not_normalize_the_restored_state() {
+    let mut model = cm("hello |");
+    let mut prev = model.state.clone();
+    prev.dom
+        .append_at_end_of_document(DomNode::Text(TextNode::from(utf16(
+            "world!",
+        ))));
+    model.previous_states.push(prev.clone());
+
+    model.undo();
+
+    assert_eq!(model.state.dom, prev.dom);
+    assert!(!model.state.dom.validate().is_empty());
+}
+
 #[test]
 fn inserting_text_creates_previous_state() {
     let mut model = cm("|");
@@ -0,0 +1,312 @@
+// Copyright 2022 The Matrix.org Foundation C.I.C.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks that every public operation of the core `ComposerModel` is
+//! available under the same name from both the UniFFI and wasm bindings,
+//! so that cross-platform code doesn't need to special-case either.
+//! The check parses the Rust sources of the core and wasm crates, and the
+//! UDL file of the UniFFI bindings, the same way their code generators do.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syn::{ImplItem, Item, Meta, NestedMeta, Type, Visibility};
+use weedle::attribute::{ExtendedAttribute, IdentifierOrString};
+use weedle::interface::InterfaceMember;
+use weedle::namespace::NamespaceMember;
+use weedle::Definition;
+
+/// Core methods that are deliberately left out of the bindings.
+const NOT_BOUND: &[&str] = &[
+    // These take types that only exist inside the core crate
+    "can_indent",
+    "can_unindent",
+    "from_state",
+    // A variant of `delete` that doesn't record history, used internally
+    "do_delete",
+];
+
+/// Core methods that the bindings expose under other names, or through
+/// several methods.
+const RENAMED: &[(&str, &[&str])] = &[
+    ("from_html", &["new_composer_model_from_html"]),
+    ("get_current_state", &["get_current_dom_state"]),
+    ("get_selection", &["selection_start", "selection_end"]),
+    (
+        "set_slash_commands",
+        &["clear_slash_commands", "add_slash_command"],
+    ),
+    (
+        "set_suggestion_triggers",
+        &["clear_suggestion_triggers", "add_suggestion_trigger"],
+    ),
+    // The bindings report errors instead of panicking, so they only expose
+    // the fallible variants, under the plain names
+    ("try_from_html", &["new_composer_model_from_html"]),
+    ("try_get_content_as_markdown", &["get_content_as_markdown"]),
+];
+
+/// Operations only offered by the UniFFI bindings.
+const FFI_ONLY: &[&str] = &[
+    // wasm-bindgen can't receive arrays of structs, so web clients must
+    // use `clear_` and `add_` to replace these lists
+    "set_slash_commands",
+    "set_suggestion_triggers",
+    // A copy of the DOM, which web clients walk using `document`
+    "get_dom_snapshot",
+];
+
+/// Operations only offered by the wasm bindings.
+const WASM_ONLY: &[&str] = &[
+    // Handles into the live DOM, which mobile clients read using
+    // `get_dom_snapshot`
+    "document",
+];
+
+#[test]
+fn core_methods_are_in_both_bindings() {
+    let core = core_methods();
+    let ffi = ffi_methods();
+    let wasm = wasm_methods();
+
+    let mut missing = Vec::new();
+    for method in &core {
+        if NOT_BOUND.contains(&method.as_str()) {
+            continue;
+        }
+        let names = RENAMED
+            .iter()
+            .find(|(core_name, _)| core_name == method)
+            .map_or_else(|| vec![method.as_str()], |(_, names)| names.to_vec());
+        for name in names {
+            if !ffi.contains(name) {
+                missing.push(format!("{name} is missing from wysiwyg-ffi"));
+            }
+            if !wasm.contains(name) {
+                missing.push(format!("{name} is missing from wysiwyg-wasm"));
+            }
+        }
+    }
+    assert!(missing.is_empty(), "{}", missing.join("\n"));
+}
+
+#[test]
+fn bindings_offer_the_same_operations() {
+    let ffi = ffi_methods();
+    let wasm = wasm_methods();
+
+    let ffi_only: Vec<&String> = ffi
+        .difference(&wasm)
+        .filter(|name| !FFI_ONLY.contains(&name.as_str()))
+        .collect();
+    let wasm_only: Vec<&String> = wasm
+        .difference(&ffi)
+        .filter(|name| !WASM_ONLY.contains(&name.as_str()))
+        .collect();
+    assert!(ffi_only.is_empty(), "Only in wysiwyg-ffi: {ffi_only:?}");
+    assert!(wasm_only.is_empty(), "Only in wysiwyg-wasm: {wasm_only:?}");
+}
+
+#[test]
+fn exceptions_refer_to_real_methods() {
+    let core = core_methods();
+    let ffi = ffi_methods();
+    let wasm = wasm_methods();
+
+    for name in NOT_BOUND.iter().chain(RENAMED.iter().map(|(name, _)| name)) {
+        assert!(core.contains(*name), "{name} is not a core method");
+    }
+    for name in FFI_ONLY {
+        assert!(ffi.contains(*name), "{name} is not in wysiwyg-ffi");
+    }
+    for name in WASM_ONLY {
+        assert!(wasm.contains(*name), "{name} is not in wysiwyg-wasm");
+    }
+}
+
+fn repo_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .join(relative)
+}
+
+fn parse_rust(path: PathBuf) -> syn::File {
+    let source = fs::read_to_string(&path).unwrap();
+    syn::parse_file(&source)
+        .unwrap_or_else(|e| panic!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Whether `ty` is `ComposerModel`, with or without type arguments.
+fn is_composer_model(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "ComposerModel"),
+        _ => false,
+    }
+}
+
+fn has_attribute(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident(name))
+}
+
+/// The public methods of the inherent `impl` blocks for `ComposerModel` in
+/// `items`, except those with any of the `skipped` attributes.
+fn public_model_methods(items: &[Item], skipped: &[&str]) -> BTreeSet<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(block)
+                if block.trait_.is_none()
+                    && is_composer_model(&block.self_ty) =>
+            {
+                Some(block)
+            }
+            _ => None,
+        })
+        .flat_map(|block| &block.items)
+        .filter_map(|item| match item {
+            ImplItem::Method(method)
+                if matches!(method.vis, Visibility::Public(_))
+                    && !skipped
+                        .iter()
+                        .any(|name| has_attribute(&method.attrs, name)) =>
+            {
+                Some(method.sig.ident.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The public, non-deprecated methods of `ComposerModel` in the core crate.
+fn core_methods() -> BTreeSet<String> {
+    let dir = repo_path("crates/wysiwyg/src/composer_model");
+    let mut methods = BTreeSet::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let file = parse_rust(entry.unwrap().path());
+        methods.extend(public_model_methods(&file.items, &["deprecated"]));
+    }
+    methods
+}
+
+/// The functions in the UDL namespace and the methods and constructors of
+/// the `ComposerModel` interface. As in UniFFI, a constructor is called
+/// `new` unless it has a `Name` attribute.
+fn ffi_methods() -> BTreeSet<String> {
+    let udl = fs::read_to_string(repo_path(
+        "bindings/wysiwyg-ffi/src/wysiwyg_composer.udl",
+    ))
+    .unwrap();
+    let definitions = weedle::parse(&udl)
+        .unwrap_or_else(|e| panic!("Failed to parse the UDL: {e:?}"));
+    let mut methods = BTreeSet::new();
+    for definition in &definitions {
+        match definition {
+            Definition::Namespace(namespace) => {
+                for member in &namespace.members.body {
+                    if let NamespaceMember::Operation(op) = member {
+                        methods.extend(op.identifier.map(|id| id.0.to_owned()));
+                    }
+                }
+            }
+            Definition::Interface(interface)
+                if interface.identifier.0 == "ComposerModel" =>
+            {
+                for member in &interface.members.body {
+                    match member {
+                        InterfaceMember::Constructor(constructor) => {
+                            methods.insert(
+                                name_attribute(&constructor.attributes)
+                                    .unwrap_or_else(|| "new".to_owned()),
+                            );
+                        }
+                        InterfaceMember::Operation(op) => {
+                            methods.extend(
+                                op.identifier.map(|id| id.0.to_owned()),
+                            );
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    methods
+}
+
+/// The value of a `[Name=...]` attribute, if there is one.
+fn name_attribute(
+    attributes: &Option<weedle::attribute::ExtendedAttributeList>,
+) -> Option<String> {
+    attributes
+        .iter()
+        .flat_map(|list| &list.body.list)
+        .find_map(|attribute| match attribute {
+            ExtendedAttribute::Ident(ident)
+                if ident.lhs_identifier.0 == "Name" =>
+            {
+                Some(match &ident.rhs {
+                    IdentifierOrString::Identifier(id) => id.0.to_owned(),
+                    IdentifierOrString::String(s) => s.0.to_owned(),
+                })
+            }
+            _ => None,
+        })
+}
+
+/// Whether an item is exported to JavaScript. The `start` function is only
+/// run when the module loads.
+fn is_exported(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("wasm_bindgen"))
+        .any(|attr| match attr.parse_meta() {
+            Ok(Meta::Path(_)) => true,
+            Ok(Meta::List(list)) => !list.nested.iter().any(|nested| {
+                matches!(nested, NestedMeta::Meta(meta) if meta.path().is_ident("start"))
+            }),
+            _ => false,
+        })
+}
+
+/// The exported free functions and `ComposerModel` methods of the wasm
+/// bindings.
+fn wasm_methods() -> BTreeSet<String> {
+    let file = parse_rust(repo_path("bindings/wysiwyg-wasm/src/lib.rs"));
+    let exported: Vec<Item> = file
+        .items
+        .into_iter()
+        .filter(|item| match item {
+            Item::Impl(block) => is_exported(&block.attrs),
+            Item::Fn(function) => is_exported(&function.attrs),
+            _ => false,
+        })
+        .collect();
+    let mut methods = public_model_methods(&exported, &[]);
+    for item in &exported {
+        if let Item::Fn(function) = item {
+            if matches!(function.vis, Visibility::Public(_)) {
+                methods.insert(function.sig.ident.to_string());
+            }
+        }
+    }
+    methods
+}
@@ -135,7 +135,10 @@ internal class EditorViewModel(
     }
 
     fun getMarkdown(): String =
-        composer?.getContentAsMarkdown().orEmpty()
+        runCatching { composer?.getContentAsMarkdown() }
+            .onFailure(::onComposerFailure)
+            .getOrNull()
+            .orEmpty()
 
     fun getCurrentFormattedText(): CharSequence {
         return stringToSpans(getContentAsMessageHtml())
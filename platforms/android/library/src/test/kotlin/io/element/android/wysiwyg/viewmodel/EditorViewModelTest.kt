@@ -66,7 +66,7 @@ internal class EditorViewModelTest {
 
         private val composerStateUpdate = MockComposerUpdateFactory.create(
             textUpdate = MockTextUpdateFactory.createReplaceAll(updatedParagraph, 2, 3),
-            menuState = MenuState.Update(actionStates = actionStates),
+            menuState = MenuState.Update(actionStates = actionStates, contextState = null),
         )
         private val replaceTextResult = ReplaceTextResult(updatedParagraph, 2..3)
     }
@@ -296,7 +296,7 @@ internal class EditorViewModelTest {
         val name = "jonny"
         val url = "https://matrix.to/#/@test:matrix.org"
         val suggestionPattern =
-            SuggestionPattern(PatternKey.AT, text = "jonny", 0.toUInt(), 5.toUInt())
+            SuggestionPattern(PatternKey.At, text = "jonny", 0.toUInt(), 5.toUInt())
         composer.givenReplaceTextResult(MockComposerUpdateFactory.create(
             menuAction = MenuAction.Suggestion(suggestionPattern)
         ))
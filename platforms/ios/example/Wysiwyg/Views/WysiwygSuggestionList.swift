@@ -73,6 +73,8 @@ struct WysiwygSuggestionList: View {
                             .accessibilityIdentifier(command.accessibilityIdentifier)
                         }
                     }
+                case .colon, .custom:
+                    EmptyView()
                 }
             }
             .padding(.horizontal, 8)
@@ -78,7 +78,7 @@ final class ComposerModelWrapper: ComposerModelWrapperProtocol {
     }
 
     func getContentAsMarkdown() -> String {
-        model.getContentAsMarkdown()
+        (try? model.getContentAsMarkdown()) ?? model.getContentAsPlainText()
     }
 
     func getContentAsPlainText() -> String {
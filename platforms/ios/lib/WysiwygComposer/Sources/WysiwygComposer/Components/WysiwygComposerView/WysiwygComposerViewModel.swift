@@ -320,7 +320,7 @@ public extension WysiwygComposerViewModel {
         // this adjusts an iOS behaviour that extends a link when typing after it
         // which does not reflect the model state.
         switch update.menuState() {
-        case let .update(newState):
+        case let .update(newState, _):
             if newState[.link] != actionStates[.link] {
                 applyUpdate(update, skipTextViewUpdate: true)
                 textView.apply(attributedContent)
@@ -419,7 +419,7 @@ private extension WysiwygComposerViewModel {
         }
 
         switch update.menuState() {
-        case let .update(actionStates: actionStates):
+        case let .update(actionStates: actionStates, contextState: _):
             self.actionStates = actionStates
         default:
             break
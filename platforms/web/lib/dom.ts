@@ -79,6 +79,10 @@ export function refreshComposerView(
                 writeChildren(child, li);
             } else if (nodeType === 'line_break') {
                 createNode(list, 'li', 'br');
+            } else if (nodeType === 'horizontal_rule') {
+                createNode(list, 'li', 'hr');
+            } else if (nodeType === 'image' || nodeType === 'custom_emoji') {
+                createNode(list, 'li', `img ${child.text(composerModel)}`);
             } else if (nodeType === 'text') {
                 const li = createNode(list, 'li');
                 createNode(li, 'span', '"', new Map([['class', 'quote']]));
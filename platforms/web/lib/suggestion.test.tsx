@@ -30,6 +30,14 @@ describe('getSuggestionChar', () => {
         });
     });
 
+    it('returns the trigger character of a custom suggestion', () => {
+        const suggestion = {
+            key: 4,
+            custom_key: '!',
+        } as unknown as SuggestionPattern;
+        expect(getSuggestionChar(suggestion)).toBe('!');
+    });
+
     it('returns empty string if given index is too high', () => {
         const suggestion = { key: 200 } as unknown as SuggestionPattern;
         expect(getSuggestionChar(suggestion)).toBe('');
@@ -51,6 +59,18 @@ describe('getSuggestionType', () => {
         expect(getSuggestionType(slashSuggestion)).toBe('command');
     });
 
+    it('returns the expected type for an emoji shortcode', () => {
+        const colonSuggestion = { key: 3 } as unknown as SuggestionPattern;
+
+        expect(getSuggestionType(colonSuggestion)).toBe('emoji');
+    });
+
+    it('returns the expected type for a custom trigger', () => {
+        const customSuggestion = { key: 4 } as unknown as SuggestionPattern;
+
+        expect(getSuggestionType(customSuggestion)).toBe('custom');
+    });
+
     it('returns unknown for any other implementations', () => {
         const slashSuggestion = { key: 200 } as unknown as SuggestionPattern;
 
@@ -21,7 +21,7 @@ import { MappedSuggestion, SuggestionChar, SuggestionType } from './types';
 export function getSuggestionChar(
     suggestion: SuggestionPattern,
 ): SuggestionChar {
-    return SUGGESTIONS[suggestion.key] || '';
+    return SUGGESTIONS[suggestion.key] || suggestion.custom_key || '';
 }
 
 export function getSuggestionType(
@@ -33,6 +33,10 @@ export function getSuggestionType(
             return 'mention';
         case 2:
             return 'command';
+        case 3:
+            return 'emoji';
+        case 4:
+            return 'custom';
         default:
             return 'unknown';
     }
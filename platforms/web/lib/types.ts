@@ -31,7 +31,7 @@ export type WysiwygEvent = WysiwygInputEvent | KeyboardEvent;
 
 export type ActionTypes = typeof ACTION_TYPES[number];
 
-export type ActionState = 'enabled' | 'reversed' | 'disabled';
+export type ActionState = 'enabled' | 'reversed' | 'disabled' | 'mixed';
 
 export type AllActionStates = Record<ActionTypes, ActionState>;
 
@@ -58,8 +58,13 @@ export type InputEventProcessor = (
     editor: HTMLElement,
 ) => WysiwygEvent | null;
 
-export type SuggestionChar = typeof SUGGESTIONS[number] | '';
-export type SuggestionType = 'mention' | 'command' | 'unknown';
+export type SuggestionChar = typeof SUGGESTIONS[number] | string;
+export type SuggestionType =
+    | 'mention'
+    | 'command'
+    | 'emoji'
+    | 'custom'
+    | 'unknown';
 export type MappedSuggestion = {
     keyChar: SuggestionChar;
     text: string;